@@ -22,13 +22,24 @@ fn process_geojson(geo_json: &GeoJson) {
         extent: 4096,
         buffer: 64,
         line_metrics: false,
+        ..Options::default()
     };
     let now = Instant::now();
     let geojsonvt = GeoJSONVT::from_geojson(geo_json, &options);
     let end = now.elapsed().as_millis();
     println!("cost: {:?}mm", end);
-    println!("total: {}", geojsonvt.total());
-    println! {"stat: {:?}", geojsonvt.stats()};
+    let stats = geojsonvt.index_stats();
+    println!(
+        "tiles: {} ({} with features)",
+        stats.total_tiles, stats.non_empty_tiles
+    );
+    let mut zooms: Vec<_> = stats.tiles_per_zoom.keys().copied().collect();
+    zooms.sort();
+    for z in zooms {
+        let non_empty = stats.non_empty_tiles_per_zoom.get(&z).unwrap_or(&0);
+        println!("  z{z}: {} ({non_empty})", stats.tiles_per_zoom[&z]);
+    }
+    println! {"stat: {:?}", stats};
 }
 fn main() {
     let geo_json = read_geo_json(r#"examples/usa_zip_codes_geo_100m.json"#);
@@ -1,11 +1,35 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::types::{
-    VtFeature, VtGeometry, VtGeometryCollection, VtLineString, VtLinearRing, VtMultiPolygon,
-    VtPoint, VtPolygon,
+    VtFeature, VtGeometry, VtGeometryCollection, VtLineString, VtLinearRing, VtMultiPoint,
+    VtMultiPolygon, VtPoint, VtPolygon,
 };
 use crate::types::{calc_progress, get_bbox_range, get_coordinate, intersect};
 
+/// Which ends of a clipping range keep the points lying exactly on them, see
+/// [`crate::Options::point_boundary`]. Lines and polygons are always clipped inclusively.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum Boundary {
+    /// Points on either end are kept, so a point on the edge shared by two tiles is in both.
+    #[default]
+    Both,
+    /// Points on the lower end are kept, those on the upper end dropped.
+    LowerInclusive,
+    /// Points on the upper end are kept, those on the lower end dropped.
+    UpperInclusive,
+}
+
+impl Boundary {
+    fn contains(&self, v: f64, k1: f64, k2: f64) -> bool {
+        match self {
+            Boundary::Both => k1 <= v && v <= k2,
+            Boundary::LowerInclusive => k1 <= v && v < k2,
+            Boundary::UpperInclusive => k1 < v && v <= k2,
+        }
+    }
+}
+
 /// Clips a set of geographical features (`VtFeature`) to a specified range.
 ///
 /// This function clips the input collection of geographical features based on the given clipping range defined by `k1` and `k2`.
@@ -13,6 +37,7 @@ use crate::types::{calc_progress, get_bbox_range, get_coordinate, intersect};
 /// For features that are partially within the clipping range, the function performs the clipping operation and returns the clipped feature collection.
 ///
 /// Additionally, this function supports line metric calculation, which is controlled by the `line_metric` parameter.
+/// Clipped rings keep the area of their source ring, as in geojson-vt, unless `clipped_area` is set.
 ///
 /// # Type Parameters
 /// - `I`: Specifies the dimension for clipping. Typically, `0` represents the x-axis and `1` represents the y-axis.
@@ -24,72 +49,96 @@ use crate::types::{calc_progress, get_bbox_range, get_coordinate, intersect};
 /// - `min_all`: The minimum boundary value of all features.
 /// - `max_all`: The maximum boundary value of all features.
 /// - `line_metric`: A boolean flag indicating whether to calculate line metrics during the clipping process.
+/// - `clipped_area`: Whether clipped rings get the area of their part within the range, see `Options::remove_slivers`.
+/// - `point_boundary`: Which ends of the range keep the points lying exactly on them.
 ///
 /// # Returns
 /// - An `Option` containing a vector of `VtFeature` objects representing the clipped features. Returns `None` if no features are within the clipping range.
+#[allow(clippy::too_many_arguments)]
 pub fn clip<const I: usize>(
-    features: &[Rc<VtFeature>],
+    features: &[Arc<VtFeature>],
     k1: f64,
     k2: f64,
     min_all: f64,
     max_all: f64,
     line_metric: bool,
-) -> Vec<Rc<VtFeature>> {
-    if min_all >= k1 && max_all <= k2 {
+    clipped_area: bool,
+    point_boundary: Boundary,
+) -> Vec<Arc<VtFeature>> {
+    let inside = |min: f64, max: f64| {
+        point_boundary.contains(min, k1, k2) && point_boundary.contains(max, k1, k2)
+    };
+    if inside(min_all, max_all) {
         return features.to_vec();
     } else if max_all < k1 || min_all > k2 {
         return vec![];
     }
-    let mut clipped_features: Vec<Rc<VtFeature>> = Vec::with_capacity(features.len());
+    let mut clipped_features: Vec<Arc<VtFeature>> = Vec::with_capacity(features.len());
     for feature in features {
         let bbox = feature.bbox.as_ref().unwrap();
         let (min, max) = get_bbox_range::<I>(bbox);
-        if min >= k1 && max <= k2 {
+        if inside(min, max) {
             clipped_features.push(feature.clone());
         } else if max < k1 || min > k2 {
             continue;
         } else {
-            let clipper = Clipper::<I>::new(k1, k2, line_metric);
+            let mut clipper = Clipper::<I>::new(k1, k2, line_metric, point_boundary);
+            clipper.clipped_area = clipped_area;
             let clipped_geometry = clipper.clip_geometry(&feature.geometry);
             if clipped_geometry.is_none() {
                 continue;
             }
             let clipped_geometry = clipped_geometry.unwrap();
-            if line_metric {
-                if let VtGeometry::MultiLineString(lines) = &clipped_geometry {
-                    for segment in lines {
-                        let feature = VtFeature::new(
-                            VtGeometry::LineString(segment.clone()),
-                            feature.properties.clone(),
-                            feature.id.clone(),
-                        );
-                        clipped_features.push(Rc::new(feature));
-                    }
-                    continue;
-                };
+            if line_metric && let VtGeometry::MultiLineString(lines) = &clipped_geometry {
+                for segment in lines {
+                    let feature = VtFeature::new(
+                        VtGeometry::LineString(segment.clone()),
+                        feature.properties.clone(),
+                        feature.id.clone(),
+                    );
+                    clipped_features.push(Arc::new(feature));
+                }
+                continue;
             }
             let feature = VtFeature::new(
                 clipped_geometry,
                 feature.properties.clone(),
                 feature.id.clone(),
             );
-            clipped_features.push(Rc::new(feature));
+            clipped_features.push(Arc::new(feature));
         }
     }
     clipped_features
 }
 
+/// Clips `ring` to the range `k1..=k2` along axis `I`, or returns `None` if too little of it
+/// is left. The ring gets the area of its clipped part.
+pub(crate) fn clip_ring<const I: usize>(
+    ring: &VtLinearRing,
+    k1: f64,
+    k2: f64,
+) -> Option<VtLinearRing> {
+    let mut clipper = Clipper::<I>::new(k1, k2, false, Boundary::Both);
+    clipper.clipped_area = true;
+    clipper.clip_ring(ring)
+}
+
 struct Clipper<const I: usize> {
     k1: f64,
     k2: f64,
     line_metrics: bool,
+    /// Whether clipped rings get the area of their clipped part rather than the source ring's.
+    clipped_area: bool,
+    point_boundary: Boundary,
 }
 impl<const I: usize> Clipper<I> {
-    pub fn new(k1: f64, k2: f64, line_metrics: bool) -> Self {
+    pub fn new(k1: f64, k2: f64, line_metrics: bool, point_boundary: Boundary) -> Self {
         Self {
             k1,
             k2,
             line_metrics,
+            clipped_area: false,
+            point_boundary,
         }
     }
     pub fn clip_geometry(&self, geometry: &VtGeometry) -> Option<VtGeometry> {
@@ -108,25 +157,18 @@ impl<const I: usize> Clipper<I> {
     }
     fn clip_point(&self, point: &VtPoint) -> Option<VtGeometry> {
         let v = get_coordinate::<I>(point);
-        if v < self.k1 || v > self.k2 {
-            None
-        } else {
+        if self.point_boundary.contains(v, self.k1, self.k2) {
             Some(VtGeometry::Point(*point))
+        } else {
+            None
         }
     }
 
-    fn clip_points(&self, points: &[VtPoint]) -> Option<VtGeometry> {
-        let multi_points = points
-            .iter()
-            .filter_map(|point| {
-                let v = get_coordinate::<I>(point);
-                if v < self.k1 || v > self.k2 {
-                    None
-                } else {
-                    Some(*point)
-                }
-            })
-            .collect::<Vec<_>>();
+    fn clip_points(&self, points: &VtMultiPoint) -> Option<VtGeometry> {
+        let multi_points = points.filtered(|point| {
+            let v = get_coordinate::<I>(point);
+            self.point_boundary.contains(v, self.k1, self.k2)
+        });
         if multi_points.is_empty() {
             None
         } else {
@@ -190,7 +232,8 @@ impl<const I: usize> Clipper<I> {
                     let t_exit = calc_progress::<I>(&a, &b, exit);
                     let p1 = intersect::<I>(&a, &b, enter, t_enter);
                     let p2 = intersect::<I>(&a, &b, exit, t_exit);
-                    slice.elements.push(p1);
+                    // A segment starting inside keeps its original first point.
+                    slice.elements.push(if enter == ak { a } else { p1 });
                     if enter != ak && self.line_metrics {
                         slice.seg_start = line_len + seg_len * t_enter;
                     }
@@ -280,70 +323,87 @@ impl<const I: usize> Clipper<I> {
         let len = ring.elements.len();
         let mut slice = VtLinearRing {
             area: ring.area,
+            dist: ring.dist,
+            seg_start: ring.seg_start,
+            seg_end: ring.seg_end,
             ..Default::default()
         };
         if len < 2 {
             return None;
         }
+        // With ring metrics, each point keeps its distance along the source perimeter.
+        let measured = self.line_metrics && ring.dist > 0. && ring.measures.len() == len;
+        // Twice the signed area of the slice, summed as its points are pushed.
+        let mut shoelace = 0.;
+        let mut push = |slice: &mut VtLinearRing, (point, measure): (VtPoint, Option<f64>)| {
+            if let Some(last) = slice.elements.last() {
+                shoelace += last.x * point.y - point.x * last.y;
+            }
+            slice.elements.push(point);
+            if measured {
+                slice.measures.push(measure);
+            }
+        };
+        let point = |i: usize| (ring.elements[i], ring.measures.get(i).copied().flatten());
+        let intersection = |i: usize, k: f64| {
+            let (a, b) = (&ring.elements[i], &ring.elements[i + 1]);
+            let t = calc_progress::<I>(a, b, k);
+            let measure = if measured {
+                ring.measure_at(i, t)
+            } else {
+                None
+            };
+            (intersect::<I>(a, b, k, t), measure)
+        };
         let k1 = self.k1;
         let k2 = self.k2;
         for (i, w) in ring.elements.windows(2).enumerate() {
-            let a = w[0];
-            let b = w[1];
-            let ak = get_coordinate::<I>(&a);
-            let bk = get_coordinate::<I>(&b);
+            let ak = get_coordinate::<I>(&w[0]);
+            let bk = get_coordinate::<I>(&w[1]);
             let is_last_seg = i == (len - 1);
             if ak < k1 {
                 if bk > k1 {
-                    slice
-                        .elements
-                        .push(intersect::<I>(&a, &b, k1, calc_progress::<I>(&a, &b, k1)));
+                    push(&mut slice, intersection(i, k1));
                 }
                 if bk > k2 {
-                    slice
-                        .elements
-                        .push(intersect::<I>(&a, &b, k2, calc_progress::<I>(&a, &b, k2)));
+                    push(&mut slice, intersection(i, k2));
                 } else if is_last_seg {
-                    slice.elements.push(b);
+                    push(&mut slice, point(i + 1));
                 }
             } else if ak > k2 {
                 if bk < k2 {
-                    slice
-                        .elements
-                        .push(intersect::<I>(&a, &b, k2, calc_progress::<I>(&a, &b, k2)));
+                    push(&mut slice, intersection(i, k2));
                 }
                 if bk < k1 {
-                    slice
-                        .elements
-                        .push(intersect::<I>(&a, &b, k1, calc_progress::<I>(&a, &b, k1)));
+                    push(&mut slice, intersection(i, k1));
                 } else if is_last_seg {
-                    slice.elements.push(b);
+                    push(&mut slice, point(i + 1));
                 }
             } else {
-                slice.elements.push(a);
+                push(&mut slice, point(i));
                 if bk < k1 {
-                    slice
-                        .elements
-                        .push(intersect::<I>(&a, &b, k1, calc_progress::<I>(&a, &b, k1)));
+                    push(&mut slice, intersection(i, k1));
                 } else if bk > k2 {
-                    slice
-                        .elements
-                        .push(intersect::<I>(&a, &b, k2, calc_progress::<I>(&a, &b, k2)));
+                    push(&mut slice, intersection(i, k2));
                 }
             }
         }
-        if !slice.elements.is_empty() {
-            let first = slice.elements.first();
-            let last = slice.elements.last();
-            if first != last {
-                slice.elements.push(*first.unwrap());
-            }
+        if let Some(&first) = slice.elements.first()
+            && slice.elements.last() != Some(&first)
+        {
+            let measure = slice.measures.first().copied().flatten();
+            push(&mut slice, (first, measure));
         }
         if slice.elements.len() < 3 {
-            None
-        } else {
-            Some(slice)
+            return None;
         }
+        if self.clipped_area {
+            slice.area = (shoelace / 2.).abs();
+        }
+        if measured {
+            (slice.seg_start, slice.seg_end) = slice.kept_range().unwrap_or_default();
+        }
+        Some(slice)
     }
     fn new_slice(&self, line: &VtLineString) -> VtLineString {
         let mut slice = VtLineString {
@@ -399,12 +459,14 @@ mod tests {
     }
     #[test]
     fn test_clip_points() {
-        let clip = Clipper::<0>::new(10., 40., false);
+        let clip = Clipper::<0>::new(10., 40., false, Boundary::Both);
         let multi_points1 = create_multi_point(&GEOM1);
         let clipped1 = clip.clip_points(&multi_points1).unwrap();
-        let expected1 = create_multi_point(&[
+        let mut expected1 = create_multi_point(&[
             20, 10, 0, 20, 20, 0, 30, 20, 0, 30, 30, 0, 25, 40, 0, 25, 50, 0, 25, 60, 0,
         ]);
+        // The points keep their position in the source MultiPoint.
+        expected1.indices = vec![3, 4, 5, 6, 9, 10, 13];
         assert_eq!(clipped1, VtGeometry::MultiPoint(expected1));
         let multi_points2 = create_multi_point(&GEOM2);
         let clipped2 = clip.clip_points(&multi_points2);
@@ -414,16 +476,16 @@ mod tests {
     fn test_clip_line_string() {
         let line1 = create_line_string(&GEOM1);
         let line2 = create_line_string(&GEOM2);
-        let clip = Clipper::<0>::new(10., 40., false);
+        let clip = Clipper::<0>::new(10., 40., false, Boundary::Both);
         let clipped1 = clip.clip_line_string(&line1).unwrap();
         let clipped2 = clip.clip_line_string(&line2).unwrap();
 
         let expected1 = vec![
             create_line_string(&[10, 0, 1, 40, 0, 1]),
             create_line_string(&[
-                40, 10, 1, 20, 10, 0, 20, 20, 0, 30, 20, 0, 30, 30, 1, 40, 30, 1,
+                40, 10, 1, 20, 10, 0, 20, 20, 0, 30, 20, 0, 30, 30, 0, 40, 30, 1,
             ]),
-            create_line_string(&[40, 40, 1, 25, 40, 0, 25, 50, 1, 10, 50, 1]),
+            create_line_string(&[40, 40, 1, 25, 40, 0, 25, 50, 0, 10, 50, 1]),
             create_line_string(&[10, 60, 1, 25, 60, 0]),
         ];
         let expected2 = vec![
@@ -434,9 +496,51 @@ mod tests {
         assert_eq!(clipped2, VtGeometry::MultiLineString(expected2));
     }
     #[test]
+    fn test_clip_marks_boundary_points_synthetic() {
+        let line = create_line_string(&GEOM1);
+        let clip = Clipper::<0>::new(10., 40., false, Boundary::Both);
+        let VtGeometry::MultiLineString(lines) = clip.clip_line_string(&line).unwrap() else {
+            panic!("not a multi line string");
+        };
+        for point in lines.iter().flat_map(|line| &line.elements) {
+            if point.x == 10. || point.x == 40. {
+                assert!(point.is_synthetic() && !point.is_original(), "{point:?}");
+            }
+        }
+        assert!(
+            lines
+                .iter()
+                .flat_map(|line| &line.elements)
+                .any(VtPoint::is_original)
+        );
+    }
+    #[test]
+    fn test_clip_keeps_original_endpoints() {
+        let mut line = create_line_string(&[0, 0, 0, 20, 30, 0, 30, 0, 0, 60, 30, 0]);
+        crate::simplify(&mut line.elements, 0.);
+        let clip = Clipper::<0>::new(10., 40., false, Boundary::Both);
+        let VtGeometry::LineString(clipped) = clip.clip_line_string(&line).unwrap() else {
+            panic!("not a line string");
+        };
+        let synthetic = clipped
+            .elements
+            .iter()
+            .map(VtPoint::is_synthetic)
+            .collect::<Vec<_>>();
+        assert_eq!(synthetic, [true, false, false, true]);
+
+        // Endpoints inside the clip keep their importance and are not taken for clip points.
+        let clip = Clipper::<0>::new(-10., 70., false, Boundary::Both);
+        let VtGeometry::LineString(clipped) = clip.clip_line_string(&line).unwrap() else {
+            panic!("not a line string");
+        };
+        assert!(!clipped.elements.iter().any(VtPoint::is_synthetic));
+        assert_eq!(clipped.elements[0].z, f64::MAX);
+    }
+    #[test]
     fn test_clip_line_string_metric() {
         let line = create_line_string(&GEOM1);
-        let clip = Clipper::<0>::new(10., 40., true);
+        let clip = Clipper::<0>::new(10., 40., true, Boundary::Both);
         let clipped = clip.clip_line_string(&line).unwrap();
         match clipped {
             VtGeometry::MultiLineString(lines) => {
@@ -450,15 +554,40 @@ mod tests {
                 )
             }
             _ => {
-                assert!(false, "Expected VtGeometry::MultiLineString")
+                panic!("Expected VtGeometry::MultiLineString")
             }
         }
     }
     #[test]
+    fn test_clip_multi_line_string_metric() {
+        // Each line measures from its own start, not from where the previous line ended.
+        let lines = vec![create_line_string(&GEOM2), create_line_string(&GEOM1)];
+        let clip = Clipper::<0>::new(10., 40., true, Boundary::Both);
+        let VtGeometry::MultiLineString(parts) = clip.clip_multi_line_string(&lines).unwrap()
+        else {
+            panic!("Expected VtGeometry::MultiLineString")
+        };
+        let result = parts
+            .iter()
+            .map(|f| (f.seg_start, f.seg_end))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            result,
+            vec![
+                (10., 40.),
+                (70., 100.),
+                (10., 40.),
+                (70., 130.),
+                (160., 200.),
+                (230., 245.)
+            ]
+        );
+    }
+    #[test]
     fn clip_polygons() {
         let ring1 = create_line_ring(&GEOM1);
         let ring2 = create_line_ring(&GEOM2);
-        let clip = Clipper::<0>::new(10., 40., false);
+        let clip = Clipper::<0>::new(10., 40., false, Boundary::Both);
 
         let polygon1 = vec![ring1];
         let polygon2 = vec![ring2];
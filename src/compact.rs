@@ -0,0 +1,578 @@
+use std::{collections::HashMap, fmt};
+
+use geojson::{Feature, FeatureCollection, Geometry, JsonObject, JsonValue, Value, feature::Id};
+
+use crate::tile::{Tile, TileCoord, TileOrigin, YAxis};
+
+const POINT: u8 = 1;
+const MULTI_POINT: u8 = 2;
+const LINE_STRING: u8 = 3;
+const MULTI_LINE_STRING: u8 = 4;
+const POLYGON: u8 = 5;
+const MULTI_POLYGON: u8 = 6;
+const GEOMETRY_COLLECTION: u8 = 7;
+
+/// Version of the format written by [`Tile::to_compact`], stored in the first byte.
+const VERSION: u8 = 2;
+
+/// Error decoding a [`CompactTile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactTileError {
+    /// The bytes were written in another version of the format.
+    UnsupportedVersion(u8),
+    /// The bytes end in the middle of the tile.
+    Truncated,
+    /// The bytes were not produced by [`Tile::to_compact`].
+    Invalid,
+}
+
+impl fmt::Display for CompactTileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompactTileError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported compact tile version {version}, expected {VERSION}"
+            ),
+            CompactTileError::Truncated => write!(f, "truncated compact tile"),
+            CompactTileError::Invalid => write!(f, "invalid compact tile data"),
+        }
+    }
+}
+
+impl std::error::Error for CompactTileError {}
+
+/// A [`Tile`] packed into a compact binary buffer, for keeping large numbers of tiles in memory
+/// or in an external cache.
+///
+/// Coordinates are stored as zigzag varints, delta-encoded within each feature, and property
+/// keys, property values and feature ids are interned in a string table. Coordinates are
+/// rounded to integers, which the tiles returned by the index already are; feature and geometry
+/// `bbox` and `foreign_members` are not kept, the [`Tile::layers`] are. The first byte is the
+/// version of the format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactTile {
+    bytes: Vec<u8>,
+}
+
+impl CompactTile {
+    /// Wraps bytes previously obtained from [`CompactTile::as_bytes`], failing if they were
+    /// written in another version of the format. The rest of the bytes is checked by
+    /// [`CompactTile::to_tile`].
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<CompactTile, CompactTileError> {
+        match bytes.first() {
+            None => Err(CompactTileError::Truncated),
+            Some(&VERSION) => Ok(CompactTile { bytes }),
+            Some(&version) => Err(CompactTileError::UnsupportedVersion(version)),
+        }
+    }
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    /// Decodes the tile, failing if the bytes were not produced by [`Tile::to_compact`].
+    pub fn to_tile(&self) -> Result<Tile, CompactTileError> {
+        let mut reader = Reader {
+            bytes: &self.bytes,
+            pos: 1,
+        };
+        let point_count = reader.uint()? as u32;
+        let simplified_count = reader.uint()? as u32;
+        let extent = reader.uint()? as u32;
+        let coord = match reader.uint()? {
+            0 => None,
+            z => Some(TileCoord::new(
+                reader.uint()? as u32,
+                reader.uint()? as u32,
+                u8::try_from(z - 1).map_err(|_| CompactTileError::Invalid)?,
+            )),
+        };
+        let axes = reader.uint()?;
+        let origin = match axes & 1 {
+            0 => TileOrigin::TopLeft,
+            _ => TileOrigin::Center,
+        };
+        let y_axis = match axes >> 1 {
+            0 => YAxis::ScreenCoords,
+            _ => YAxis::CartesianCoords,
+        };
+        let strings = (0..reader.len()?)
+            .map(|_| {
+                let len = reader.uint()?;
+                let text = std::str::from_utf8(reader.take(len >> 1)?)
+                    .map_err(|_| CompactTileError::Invalid)?;
+                if len & 1 == 0 {
+                    Ok(JsonValue::String(text.to_string()))
+                } else {
+                    serde_json::from_str(text).map_err(|_| CompactTileError::Invalid)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let features = reader.features(&strings)?;
+        let layers = (0..reader.len()?)
+            .map(|_| {
+                let name = match reader.uint()? {
+                    0 => None,
+                    i => match string(&strings, i - 1)? {
+                        JsonValue::String(name) => Some(name.clone()),
+                        _ => return Err(CompactTileError::Invalid),
+                    },
+                };
+                let features = reader.features(&strings)?;
+                Ok((name, feature_collection(features)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if reader.pos != reader.bytes.len() {
+            return Err(CompactTileError::Invalid);
+        }
+        Ok(Tile {
+            feature_collection: feature_collection(features),
+            point_count,
+            simplified_count,
+            extent,
+            coord,
+            origin,
+            y_axis,
+            layers,
+        })
+    }
+}
+
+impl Tile {
+    /// Packs the tile into a [`CompactTile`]. Features without geometry are skipped.
+    #[must_use = "`to_compact` returns an encoded copy of the tile"]
+    pub fn to_compact(&self) -> CompactTile {
+        let mut strings = StringTable::default();
+        let mut body = Vec::new();
+        write_features(&mut body, &mut strings, &self.feature_collection.features);
+        write_uint(&mut body, self.layers.len() as u64);
+        for (name, layer) in &self.layers {
+            match name {
+                None => write_uint(&mut body, 0),
+                Some(name) => write_uint(
+                    &mut body,
+                    strings.intern(&JsonValue::String(name.clone())) + 1,
+                ),
+            }
+            write_features(&mut body, &mut strings, &layer.features);
+        }
+
+        let mut bytes = Vec::with_capacity(body.len() + strings.bytes.len() + 16);
+        bytes.push(VERSION);
+        write_uint(&mut bytes, self.point_count as u64);
+        write_uint(&mut bytes, self.simplified_count as u64);
+        write_uint(&mut bytes, self.extent as u64);
+        match self.coord {
+            None => write_uint(&mut bytes, 0),
+            Some(coord) => {
+                write_uint(&mut bytes, coord.z() as u64 + 1);
+                write_uint(&mut bytes, coord.x() as u64);
+                write_uint(&mut bytes, coord.y() as u64);
+            }
+        }
+        write_uint(&mut bytes, self.origin as u64 | (self.y_axis as u64) << 1);
+        write_uint(&mut bytes, strings.index.len() as u64);
+        bytes.extend_from_slice(&strings.bytes);
+        bytes.extend_from_slice(&body);
+        CompactTile { bytes }
+    }
+}
+
+fn write_features(bytes: &mut Vec<u8>, strings: &mut StringTable, features: &[Feature]) {
+    let features = features.iter().filter(|f| f.geometry.is_some());
+    write_uint(bytes, features.clone().count() as u64);
+    for feature in features {
+        match &feature.id {
+            None => write_uint(bytes, 0),
+            Some(id) => {
+                let id = match id {
+                    Id::String(s) => JsonValue::String(s.clone()),
+                    Id::Number(n) => JsonValue::Number(n.clone()),
+                };
+                write_uint(bytes, strings.intern(&id) + 1);
+            }
+        }
+        match &feature.properties {
+            None => write_uint(bytes, 0),
+            Some(properties) => {
+                write_uint(bytes, properties.len() as u64 + 1);
+                for (key, value) in properties {
+                    write_uint(bytes, strings.intern(&JsonValue::String(key.clone())));
+                    write_uint(bytes, strings.intern(value));
+                }
+            }
+        }
+        let mut cursor = [0, 0];
+        write_geometry(
+            bytes,
+            &feature.geometry.as_ref().unwrap().value,
+            &mut cursor,
+        );
+    }
+}
+
+/// Interned values, in the order they were first seen. Strings are stored as is, other values
+/// as JSON text, told apart by the low bit of their length.
+#[derive(Default)]
+struct StringTable {
+    index: HashMap<JsonValue, u64>,
+    bytes: Vec<u8>,
+}
+impl StringTable {
+    fn intern(&mut self, value: &JsonValue) -> u64 {
+        if let Some(&i) = self.index.get(value) {
+            return i;
+        }
+        let (text, is_json) = match value {
+            JsonValue::String(s) => (s.clone(), false),
+            value => (value.to_string(), true),
+        };
+        write_uint(&mut self.bytes, (text.len() as u64) << 1 | is_json as u64);
+        self.bytes.extend_from_slice(text.as_bytes());
+        let i = self.index.len() as u64;
+        self.index.insert(value.clone(), i);
+        i
+    }
+}
+
+fn write_geometry(bytes: &mut Vec<u8>, value: &Value, cursor: &mut [i64; 2]) {
+    match value {
+        Value::Point(p) => {
+            bytes.push(POINT);
+            write_point(bytes, p, cursor);
+        }
+        Value::MultiPoint(points) => {
+            bytes.push(MULTI_POINT);
+            write_points(bytes, points, cursor);
+        }
+        Value::LineString(line) => {
+            bytes.push(LINE_STRING);
+            write_points(bytes, line, cursor);
+        }
+        Value::MultiLineString(lines) => {
+            bytes.push(MULTI_LINE_STRING);
+            write_uint(bytes, lines.len() as u64);
+            lines.iter().for_each(|l| write_points(bytes, l, cursor));
+        }
+        Value::Polygon(rings) => {
+            bytes.push(POLYGON);
+            write_uint(bytes, rings.len() as u64);
+            rings.iter().for_each(|r| write_ring(bytes, r, cursor));
+        }
+        Value::MultiPolygon(polygons) => {
+            bytes.push(MULTI_POLYGON);
+            write_uint(bytes, polygons.len() as u64);
+            for rings in polygons {
+                write_uint(bytes, rings.len() as u64);
+                rings.iter().for_each(|r| write_ring(bytes, r, cursor));
+            }
+        }
+        Value::GeometryCollection(geometries) => {
+            bytes.push(GEOMETRY_COLLECTION);
+            write_uint(bytes, geometries.len() as u64);
+            for geometry in geometries {
+                write_geometry(bytes, &geometry.value, cursor);
+            }
+        }
+    }
+}
+fn write_points(bytes: &mut Vec<u8>, points: &[Vec<f64>], cursor: &mut [i64; 2]) {
+    write_uint(bytes, points.len() as u64);
+    points.iter().for_each(|p| write_point(bytes, p, cursor));
+}
+/// Writes a ring like a line, leaving out the closing point of closed rings.
+fn write_ring(bytes: &mut Vec<u8>, ring: &[Vec<f64>], cursor: &mut [i64; 2]) {
+    let closed = ring.len() > 1 && ring.first() == ring.last();
+    let points = if closed {
+        &ring[..ring.len() - 1]
+    } else {
+        ring
+    };
+    write_uint(bytes, (points.len() as u64) << 1 | closed as u64);
+    points.iter().for_each(|p| write_point(bytes, p, cursor));
+}
+fn write_point(bytes: &mut Vec<u8>, p: &[f64], cursor: &mut [i64; 2]) {
+    for (c, v) in cursor.iter_mut().zip(p) {
+        let v = v.round() as i64;
+        write_uint(bytes, zigzag(v - *c));
+        *c = v;
+    }
+}
+fn write_uint(bytes: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        bytes.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    bytes.push(v as u8);
+}
+#[inline]
+fn zigzag(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+fn feature_collection(features: Vec<Feature>) -> FeatureCollection {
+    FeatureCollection {
+        bbox: None,
+        features,
+        foreign_members: None,
+    }
+}
+
+/// Entry `i` of the string table read by [`CompactTile::to_tile`].
+fn string(strings: &[JsonValue], i: u64) -> Result<&JsonValue, CompactTileError> {
+    let i = usize::try_from(i).map_err(|_| CompactTileError::Invalid)?;
+    strings.get(i).ok_or(CompactTileError::Invalid)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, CompactTileError> {
+        let byte = *self
+            .bytes
+            .get(self.pos)
+            .ok_or(CompactTileError::Truncated)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+    fn take(&mut self, len: u64) -> Result<&[u8], CompactTileError> {
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.pos.checked_add(len))
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or(CompactTileError::Truncated)?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+    fn uint(&mut self) -> Result<u64, CompactTileError> {
+        let mut v = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            if shift > 63 {
+                return Err(CompactTileError::Invalid);
+            }
+            v |= ((byte & 0x7f) as u64) << shift;
+            if byte < 0x80 {
+                return Ok(v);
+            }
+            shift += 7;
+        }
+    }
+    /// Reads a count of items, each taking at least one byte, so that a corrupted count fails
+    /// instead of allocating for items that cannot be there.
+    fn len(&mut self) -> Result<u64, CompactTileError> {
+        let len = self.uint()?;
+        if len > (self.bytes.len() - self.pos) as u64 {
+            return Err(CompactTileError::Truncated);
+        }
+        Ok(len)
+    }
+    fn features(&mut self, strings: &[JsonValue]) -> Result<Vec<Feature>, CompactTileError> {
+        (0..self.len()?)
+            .map(|_| {
+                let id = match self.uint()? {
+                    0 => None,
+                    i => Some(match string(strings, i - 1)? {
+                        JsonValue::String(s) => Id::String(s.clone()),
+                        JsonValue::Number(n) => Id::Number(n.clone()),
+                        _ => return Err(CompactTileError::Invalid),
+                    }),
+                };
+                let properties = match self.uint()? {
+                    0 => None,
+                    n => Some(
+                        (1..n)
+                            .map(|_| {
+                                let JsonValue::String(key) = string(strings, self.uint()?)? else {
+                                    return Err(CompactTileError::Invalid);
+                                };
+                                Ok((key.clone(), string(strings, self.uint()?)?.clone()))
+                            })
+                            .collect::<Result<JsonObject, _>>()?,
+                    ),
+                };
+                let mut cursor = [0, 0];
+                let value = self.geometry(&mut cursor)?;
+                Ok(Feature {
+                    bbox: None,
+                    geometry: Some(Geometry::new(value)),
+                    id,
+                    properties,
+                    foreign_members: None,
+                })
+            })
+            .collect()
+    }
+    fn geometry(&mut self, cursor: &mut [i64; 2]) -> Result<Value, CompactTileError> {
+        Ok(match self.byte()? {
+            POINT => Value::Point(self.point(cursor)?),
+            MULTI_POINT => Value::MultiPoint(self.points(cursor)?),
+            LINE_STRING => Value::LineString(self.points(cursor)?),
+            MULTI_LINE_STRING => Value::MultiLineString(
+                (0..self.len()?)
+                    .map(|_| self.points(cursor))
+                    .collect::<Result<_, _>>()?,
+            ),
+            POLYGON => Value::Polygon(
+                (0..self.len()?)
+                    .map(|_| self.ring(cursor))
+                    .collect::<Result<_, _>>()?,
+            ),
+            MULTI_POLYGON => Value::MultiPolygon(
+                (0..self.len()?)
+                    .map(|_| (0..self.len()?).map(|_| self.ring(cursor)).collect())
+                    .collect::<Result<_, _>>()?,
+            ),
+            GEOMETRY_COLLECTION => Value::GeometryCollection(
+                (0..self.len()?)
+                    .map(|_| self.geometry(cursor).map(Geometry::new))
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => return Err(CompactTileError::Invalid),
+        })
+    }
+    fn points(&mut self, cursor: &mut [i64; 2]) -> Result<Vec<Vec<f64>>, CompactTileError> {
+        (0..self.len()?).map(|_| self.point(cursor)).collect()
+    }
+    fn ring(&mut self, cursor: &mut [i64; 2]) -> Result<Vec<Vec<f64>>, CompactTileError> {
+        let len = self.uint()?;
+        if len >> 1 > (self.bytes.len() - self.pos) as u64 {
+            return Err(CompactTileError::Truncated);
+        }
+        let mut ring = (0..len >> 1)
+            .map(|_| self.point(cursor))
+            .collect::<Result<Vec<_>, _>>()?;
+        if len & 1 == 1 {
+            let first = ring.first().ok_or(CompactTileError::Invalid)?.clone();
+            ring.push(first);
+        }
+        Ok(ring)
+    }
+    fn point(&mut self, cursor: &mut [i64; 2]) -> Result<Vec<f64>, CompactTileError> {
+        cursor
+            .iter_mut()
+            .map(|c| {
+                let v = self.uint()?;
+                *c = c.wrapping_add((v >> 1) as i64 ^ -((v & 1) as i64));
+                Ok(*c as f64)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint_round_trip() {
+        for v in [0, 1, -1, 63, -64, 64, 4096, -4160, i64::MAX, i64::MIN] {
+            let mut bytes = Vec::new();
+            write_uint(&mut bytes, zigzag(v));
+            let mut cursor = [0, 0];
+            write_uint(&mut bytes, 0);
+            let point = Reader {
+                bytes: &bytes,
+                pos: 0,
+            }
+            .point(&mut cursor)
+            .unwrap();
+            assert_eq!(cursor, [v, 0]);
+            assert_eq!(point, vec![v as f64, 0.]);
+        }
+    }
+
+    #[test]
+    fn test_compact_round_trip() {
+        let geojson: geojson::GeoJson = r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":3,"properties":{"name":"a","n":1.5,"tags":[1,"x"]},
+             "geometry":{"type":"Point","coordinates":[-64,4160]}},
+            {"type":"Feature","id":"b","properties":{"name":"a","n":2},
+             "geometry":{"type":"MultiPolygon","coordinates":[
+                [[[0,0],[10,0],[10,10],[0,0]]],
+                [[[20,20],[30,20],[30,30],[20,20]],[[22,22],[24,22],[24,24]]]
+             ]}},
+            {"type":"Feature","properties":null,
+             "geometry":{"type":"GeometryCollection","geometries":[
+                {"type":"MultiLineString","coordinates":[[[1,2],[3,4]],[[5,6],[-7,8]]]},
+                {"type":"MultiPoint","coordinates":[[1,1],[2,2]]}
+             ]}}
+        ]}"#
+        .parse()
+        .unwrap();
+        let geojson::GeoJson::FeatureCollection(feature_collection) = geojson else {
+            panic!("not a feature collection");
+        };
+        let tile = Tile {
+            feature_collection,
+            point_count: 20,
+            simplified_count: 18,
+            extent: 4096,
+            coord: Some(TileCoord::new(5, 3, 4)),
+            origin: TileOrigin::Center,
+            y_axis: YAxis::CartesianCoords,
+            layers: Vec::new(),
+        };
+        let compact = tile.to_compact();
+        assert_eq!(compact.to_tile(), Ok(tile.clone()));
+        let bytes = CompactTile::from_bytes(compact.as_bytes().to_vec()).unwrap();
+        assert_eq!(bytes.to_tile(), Ok(tile));
+
+        assert_eq!(Tile::default().to_compact().to_tile(), Ok(Tile::default()));
+    }
+
+    #[test]
+    fn test_malformed_bytes() {
+        assert_eq!(
+            CompactTile::from_bytes(Vec::new()),
+            Err(CompactTileError::Truncated)
+        );
+        assert_eq!(
+            CompactTile::from_bytes(vec![VERSION + 1, 0]),
+            Err(CompactTileError::UnsupportedVersion(VERSION + 1))
+        );
+
+        let tile = Tile {
+            feature_collection: FeatureCollection {
+                bbox: None,
+                features: vec![Feature {
+                    geometry: Some(Geometry::new(Value::LineString(vec![
+                        vec![0., 0.],
+                        vec![10., 20.],
+                    ]))),
+                    properties: Some(JsonObject::from_iter([("a".into(), 1.into())])),
+                    ..Feature::default()
+                }],
+                foreign_members: None,
+            },
+            extent: 4096,
+            ..Tile::default()
+        };
+        let bytes = tile.to_compact().into_bytes();
+        for len in 1..bytes.len() {
+            let truncated = CompactTile::from_bytes(bytes[..len].to_vec()).unwrap();
+            assert!(truncated.to_tile().is_err(), "{len} bytes");
+        }
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        let trailing = CompactTile::from_bytes(trailing).unwrap();
+        assert_eq!(trailing.to_tile(), Err(CompactTileError::Invalid));
+        // A geometry tag that does not exist.
+        let mut tag = bytes;
+        let pos = tag.iter().rposition(|&b| b == LINE_STRING).unwrap();
+        tag[pos] = 0x7f;
+        let tag = CompactTile::from_bytes(tag).unwrap();
+        assert_eq!(tag.to_tile(), Err(CompactTileError::Invalid));
+    }
+}
@@ -0,0 +1,104 @@
+use std::io::Write;
+
+use flate2::{
+    Compression,
+    write::{GzEncoder, ZlibEncoder},
+};
+
+use crate::{
+    GeoJSONVT,
+    tile::{EMPTY_TILE, Tile},
+};
+
+/// Serialization of a tile returned by [`GeoJSONVT::tile_bytes`].
+///
+/// Compression levels range from 0 (none) to 9 (smallest output).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// Uncompressed GeoJSON, as [`Tile::to_geojson_bytes`].
+    Json,
+    /// GeoJSON compressed with gzip, as [`Tile::to_json_gzip`].
+    JsonGzip { level: u32 },
+    /// GeoJSON compressed with zlib, the HTTP `deflate` content encoding, as
+    /// [`Tile::to_json_deflate`].
+    JsonDeflate { level: u32 },
+}
+
+impl Encoding {
+    fn encode(&self, tile: &Tile) -> Vec<u8> {
+        match *self {
+            Encoding::Json => tile.to_geojson_bytes(),
+            Encoding::JsonGzip { level } => tile.to_json_gzip(level),
+            Encoding::JsonDeflate { level } => tile.to_json_deflate(level),
+        }
+    }
+}
+
+impl Tile {
+    /// Serializes the tile's features as GeoJSON compressed with gzip at `level` (0-9).
+    #[must_use = "`to_json_gzip` returns the compressed tile"]
+    pub fn to_json_gzip(&self, level: u32) -> Vec<u8> {
+        let encoder = GzEncoder::new(Vec::new(), Compression::new(level));
+        compress(self, encoder, GzEncoder::finish)
+    }
+    /// Serializes the tile's features as GeoJSON compressed with zlib at `level` (0-9), as
+    /// served with `Content-Encoding: deflate`.
+    #[must_use = "`to_json_deflate` returns the compressed tile"]
+    pub fn to_json_deflate(&self, level: u32) -> Vec<u8> {
+        let encoder = ZlibEncoder::new(Vec::new(), Compression::new(level));
+        compress(self, encoder, ZlibEncoder::finish)
+    }
+}
+
+fn compress<W: Write>(
+    tile: &Tile,
+    mut encoder: W,
+    finish: impl FnOnce(W) -> std::io::Result<Vec<u8>>,
+) -> Vec<u8> {
+    tile.to_geojson_writer(&mut encoder)
+        .and_then(|()| finish(encoder))
+        .expect("compressing into memory never fails")
+}
+
+impl GeoJSONVT {
+    /// Returns the tile at `z/x/y` serialized with `encoding`, see [`GeoJSONVT::tile`].
+    ///
+    /// The payload is cached with the tile, so later requests for the same encoding only copy
+    /// it. It is dropped along with the tile content, when evicted from the index or from the
+    /// cache bounded by [`crate::Options::tile_cache_capacity`]. Empty tiles are encoded on
+    /// every request.
+    #[must_use = "`tile_bytes` returns the serialized tile"]
+    pub fn tile_bytes(&mut self, z: u8, x: u32, y: u32, encoding: Encoding) -> Vec<u8> {
+        if self.tile(z, x, y).is_placeholder() {
+            return encoding.encode(&EMPTY_TILE);
+        }
+        let id = self.content_id(z, x, y);
+        let tile = match self.tiles.get(&id) {
+            Some(tile) => tile,
+            None => self
+                .cache
+                .as_ref()
+                .and_then(|cache| cache.peek(&id))
+                .unwrap(),
+        };
+        if let Some((_, bytes)) = tile.encoded.iter().find(|(e, _)| *e == encoding) {
+            return bytes.to_vec();
+        }
+        let bytes = encoding.encode(&tile.tile);
+        let tile = match self.tiles.get_mut(&id) {
+            Some(tile) => tile,
+            None => self
+                .cache
+                .as_mut()
+                .and_then(|cache| cache.peek_mut(&id))
+                .unwrap(),
+        };
+        tile.encoded.push((encoding, bytes.as_slice().into()));
+        self.encoded_payloads += 1;
+        bytes
+    }
+    /// Number of payloads encoded and cached by [`GeoJSONVT::tile_bytes`] so far.
+    pub fn encoded_payloads(&self) -> usize {
+        self.encoded_payloads
+    }
+}
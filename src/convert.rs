@@ -1,9 +1,14 @@
 use crate::{
     simplify,
-    types::{VtFeature, VtGeometry, VtLineString, VtLinearRing, VtPoint},
+    types::{BBox, VtFeature, VtGeometry, VtLineString, VtLinearRing, VtPoint, VtPolygon},
+};
+use geojson::{Feature, FeatureCollection, Geometry, JsonValue, Position, Value, feature::Id};
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    fmt,
+    ops::Range,
+    sync::Arc,
 };
-use geojson::{Feature, FeatureCollection, Geometry, Value, feature::Id};
-use std::rc::Rc;
 
 /// Converts a GeoJSON FeatureCollection into a vector of VtFeature objects.
 ///
@@ -14,185 +19,1476 @@ use std::rc::Rc;
 /// more suitable internal representation (`VtFeature`). It includes coordinate
 /// conversion from GeoJSON coordinates (longitude and latitude) to Mercator coordinates
 /// within the range of 0 to 1, adds additional parameters such as feature IDs and bounding boxes,
-/// and performs line simplification. The line simplification is controlled by the `tolerance`
-/// parameter.
-///
-/// # Arguments
-///
-/// * `fc` - A `FeatureCollection` containing GeoJSON features to be converted
-/// * `tolerance` - Simplification tolerance (higher means simpler)
-/// * `generate_id` - Whether to auto-generate feature IDs
-///
-/// # Returns
-///
-/// A vector of `VtFeature` objects representing the converted GeoJSON features.
+/// and performs line simplification. The line simplification is controlled by the tolerance
+/// settings of [`ConvertOptions`], see [`convert_with_warnings`] to also get the data quality
+/// issues found in the input.
 ///
 /// # Examples
 ///
-/// ```ignore
-/// let feature_collection = FeatureCollection { ... };
-/// let vt_features = convert(feature_collection, 3.0, false);
 /// ```
-pub fn convert(fc: FeatureCollection, tolerance: f64, generate_id: bool) -> Vec<VtFeature> {
+/// use geojson::{Feature, FeatureCollection, Geometry, Value};
+/// use geojsonvt::{ConvertOptions, IdPolicy, convert};
+///
+/// let point = Feature::from(Geometry::new(Value::Point(vec![0., 0.])));
+/// let options = ConvertOptions {
+///     line_tolerance: 1e-7,
+///     polygon_tolerance: 1e-7,
+///     id_policy: IdPolicy::GenerateAll,
+///     ..ConvertOptions::default()
+/// };
+/// let vt_features = convert(FeatureCollection::from_iter([point]), &options);
+/// assert_eq!(vt_features.len(), 1);
+/// ```
+#[must_use = "`convert` consumes the collection and returns the converted features"]
+pub fn convert(fc: FeatureCollection, options: &ConvertOptions) -> Vec<VtFeature> {
+    let (vt_features, _) = convert_with_warnings(fc, options);
+    vt_features
+}
+
+/// Settings of [`convert_with_warnings`], the projection-time part of [`crate::Options`].
+///
+/// The tolerances are expressed in the projected 0..1 space, i.e. an extent-unit tolerance
+/// divided by `extent * 2^max_zoom`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConvertOptions {
+    pub line_tolerance: f64,
+    pub polygon_tolerance: f64,
+    /// Significant digits kept in projected coordinates, `None` for full precision.
+    pub precision: Option<u8>,
+    pub id_policy: IdPolicy,
+    /// Splits geometry collections into one feature per member.
+    pub flatten_collections: bool,
+    /// Drops consecutive duplicate coordinates of lines and rings after reporting them.
+    pub remove_duplicates: bool,
+    /// Stops once that many features were converted, see
+    /// [`ConvertWarning::FeatureLimitReached`].
+    pub feature_limit: Option<usize>,
+    /// Drops the features repeating an earlier one, see [`ConvertWarning::DuplicateFeatures`].
+    pub dedupe: DedupePolicy,
+    /// Projects the `bbox` of the input features as their bounds rather than computing it from
+    /// their points.
+    pub respect_feature_bbox: bool,
+    /// Drops the lines and polygons too small to show at the tolerances, see
+    /// [`SkipReason::BelowTolerance`].
+    pub skip_below_tolerance: bool,
+    /// With `skip_below_tolerance`, keeps the lines below tolerance as a point at their center
+    /// instead.
+    pub collapse_lines: bool,
+    /// With `skip_below_tolerance`, keeps the polygons below tolerance as a point at their
+    /// center instead.
+    pub collapse_polygons: bool,
+    /// Measures the perimeter of the rings, for `Options::ring_metrics`.
+    pub ring_metrics: bool,
+    /// Skips the features with a coordinate beyond this longitude either side or the poles,
+    /// see [`ConvertError::CoordinateOutOfRange`].
+    pub max_lng: Option<f64>,
+}
+
+/// Same as [`convert`] with the settings of `options`, also returning the data quality issues
+/// found in the input.
+pub fn convert_with_warnings(
+    fc: FeatureCollection,
+    options: &ConvertOptions,
+) -> (Vec<VtFeature>, Vec<ConvertWarning>) {
+    let (vt_features, warnings, _) = convert_with_report(fc, options);
+    (vt_features, warnings)
+}
+
+/// Same as [`convert_with_warnings`], also returning the [`ConversionReport`] of the input.
+pub(crate) fn convert_with_report(
+    fc: FeatureCollection,
+    options: &ConvertOptions,
+) -> (Vec<VtFeature>, Vec<ConvertWarning>, ConversionReport) {
+    let mut ids = IdGenerator::new(options.id_policy.clone(), &fc.features);
+    convert_with_ids(fc, options, &mut ids)
+}
+
+/// Same as [`convert_with_report`], assigning ids with `ids` instead of the id policy of
+/// `options`, so that several inputs can share one generator.
+pub(crate) fn convert_with_ids(
+    fc: FeatureCollection,
+    options: &ConvertOptions,
+    ids: &mut IdGenerator,
+) -> (Vec<VtFeature>, Vec<ConvertWarning>, ConversionReport) {
+    let mut converter = Converter::with_options(options);
     let mut vt_features: Vec<VtFeature> = Vec::with_capacity(fc.features.len());
-    let mut gen_id: u64 = 0;
-    for feature in fc.features {
+    let mut deduplicator = Deduplicator::new(options.dedupe);
+    let total_input_features = fc.features.len();
+    let mut converted = 0;
+    for (index, feature) in fc.features.into_iter().enumerate() {
         if feature.geometry.is_none() {
+            let id = feature.id.clone();
+            converter.report.skip(index, id, SkipReason::NoGeometry);
             continue;
         }
-        let mut id = feature.id.clone();
-        if generate_id {
-            id = Some(Id::Number(gen_id.into()));
-            gen_id += 1;
+        if options
+            .feature_limit
+            .is_some_and(|limit| converted >= limit)
+        {
+            converter
+                .warnings
+                .push(ConvertWarning::FeatureLimitReached {
+                    total_input_features,
+                });
+            break;
+        }
+        let id = ids.id(index, &feature, &mut converter.warnings);
+        if deduplicator.is_duplicate_id(&id) {
+            continue;
         }
-        let vt_feature = convert_feature(feature, tolerance, id);
+        let vt_feature = converter.convert_input_feature(index, feature, id);
         if let Some(vt_feature) = vt_feature {
-            vt_features.push(vt_feature);
+            let start = vt_features.len();
+            if options.flatten_collections {
+                flatten_collection(vt_feature, &mut vt_features);
+            } else {
+                vt_features.push(vt_feature);
+            }
+            if deduplicator.is_duplicate_feature(&vt_features, start) {
+                vt_features.truncate(start);
+                continue;
+            }
+            converted += 1;
         }
     }
-    vt_features
+    converter.warnings.extend(deduplicator.warning());
+    (vt_features, converter.warnings, converter.report)
 }
 
-pub fn convert_feature(feature: Feature, tolerance: f64, id: Option<Id>) -> Option<VtFeature> {
-    let geometry = feature.geometry.as_ref()?;
-    let vt_geometry = convert_geometry(geometry, tolerance)?;
-    Some(VtFeature::new(vt_geometry, Rc::new(feature.properties), id))
+/// Which features are dropped as duplicates of an earlier one when converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum DedupePolicy {
+    #[default]
+    Off,
+    /// Keeps the first feature of each id, once assigned by the [`IdPolicy`]. Features without
+    /// an id are all kept.
+    ById,
+    /// Keeps the first of the features with the same projected geometry and properties,
+    /// whatever their ids.
+    ByGeometryAndProps,
 }
 
-fn convert_geometry(geometry: &Geometry, tolerance: f64) -> Option<VtGeometry> {
-    match &geometry.value {
-        Value::Point(coords) => {
-            if coords.is_empty() {
-                None
-            } else {
-                Some(VtGeometry::Point(convert_coords(coords)))
+/// Key telling string and numeric ids apart, as `Id` is not hashable.
+pub(crate) fn id_key(id: &Id) -> String {
+    match id {
+        Id::String(s) => format!("s{s}"),
+        Id::Number(n) => format!("n{n}"),
+    }
+}
+
+/// Tracks the features kept so far to drop duplicates according to a [`DedupePolicy`].
+pub(crate) struct Deduplicator {
+    policy: DedupePolicy,
+    ids: HashSet<String>,
+    /// Range of the converted features, flattened collections spanning several, kept for each
+    /// hash of geometry and properties.
+    hashed: HashMap<u64, Vec<Range<usize>>>,
+    dropped: usize,
+}
+impl Deduplicator {
+    pub fn new(policy: DedupePolicy) -> Self {
+        Self {
+            policy,
+            ids: HashSet::new(),
+            hashed: HashMap::new(),
+            dropped: 0,
+        }
+    }
+    /// Whether a feature with `id` was already kept, under [`DedupePolicy::ById`].
+    pub fn is_duplicate_id(&mut self, id: &Option<Id>) -> bool {
+        let (DedupePolicy::ById, Some(id)) = (self.policy, id) else {
+            return false;
+        };
+        let duplicate = !self.ids.insert(id_key(id));
+        self.dropped += duplicate as usize;
+        duplicate
+    }
+    /// Whether `features[start..]`, the parts of the feature just converted, repeat a feature
+    /// already kept, under [`DedupePolicy::ByGeometryAndProps`].
+    pub fn is_duplicate_feature(&mut self, features: &[VtFeature], start: usize) -> bool {
+        if self.policy != DedupePolicy::ByGeometryAndProps {
+            return false;
+        }
+        let parts = &features[start..];
+        let mut hasher = Fnv1a::default();
+        for part in parts {
+            hash_geometry(&part.geometry, &mut hasher);
+            if let Some(properties) = part.properties.as_ref() {
+                let mut keys: Vec<_> = properties.keys().collect();
+                keys.sort();
+                for key in keys {
+                    hasher.write(key.as_bytes());
+                    hasher.write(properties[key].to_string().as_bytes());
+                }
             }
         }
-        Value::MultiPoint(coords) => {
-            if coords.is_empty() {
-                None
-            } else {
-                Some(VtGeometry::MultiPoint(
-                    coords.iter().map(|p| convert_coords(p)).collect(),
-                ))
+        let kept = self.hashed.entry(hasher.0).or_default();
+        let same =
+            |a: &VtFeature, b: &VtFeature| a.geometry == b.geometry && a.properties == b.properties;
+        let duplicate = kept.iter().any(|range| {
+            let other = &features[range.clone()];
+            other.len() == parts.len() && other.iter().zip(parts).all(|(a, b)| same(a, b))
+        });
+        if duplicate {
+            self.dropped += 1;
+        } else {
+            kept.push(start..features.len());
+        }
+        duplicate
+    }
+    /// The [`ConvertWarning::DuplicateFeatures`] to report, if any feature was dropped.
+    pub fn warning(&self) -> Option<ConvertWarning> {
+        (self.dropped > 0).then_some(ConvertWarning::DuplicateFeatures {
+            dropped: self.dropped,
+        })
+    }
+}
+
+fn hash_geometry(geometry: &VtGeometry, hasher: &mut Fnv1a) {
+    let points = |points: &[VtPoint], hasher: &mut Fnv1a| {
+        for point in points {
+            hasher.write(&point.x.to_le_bytes());
+            hasher.write(&point.y.to_le_bytes());
+        }
+    };
+    match geometry {
+        VtGeometry::Point(point) => points(std::slice::from_ref(point), hasher),
+        VtGeometry::MultiPoint(multi_point) => points(&multi_point.elements, hasher),
+        VtGeometry::LineString(line) => points(&line.elements, hasher),
+        VtGeometry::MultiLineString(lines) => {
+            lines.iter().for_each(|line| points(&line.elements, hasher))
+        }
+        VtGeometry::Polygon(rings) => rings.iter().for_each(|ring| points(&ring.elements, hasher)),
+        VtGeometry::MultiPolygon(polygons) => polygons
+            .iter()
+            .flatten()
+            .for_each(|ring| points(&ring.elements, hasher)),
+        VtGeometry::GeometryCollection(geometries) => geometries
+            .iter()
+            .for_each(|geometry| hash_geometry(geometry, hasher)),
+    }
+}
+
+/// Hashes the bits of the coordinates of `value`, along with its type and the length of each
+/// of its parts so that different shapes over the same coordinates differ.
+fn hash_value(value: &Value, hasher: &mut Fnv1a) {
+    let position = |position: &Position, hasher: &mut Fnv1a| {
+        hasher.write(&(position.len() as u64).to_le_bytes());
+        for v in position {
+            hasher.write(&v.to_bits().to_le_bytes());
+        }
+    };
+    let line = |line: &[Position], hasher: &mut Fnv1a| {
+        hasher.write(&(line.len() as u64).to_le_bytes());
+        line.iter().for_each(|p| position(p, hasher));
+    };
+    let polygon = |rings: &[Vec<Position>], hasher: &mut Fnv1a| {
+        hasher.write(&(rings.len() as u64).to_le_bytes());
+        rings.iter().for_each(|ring| line(ring, hasher));
+    };
+    match value {
+        Value::Point(point) => {
+            hasher.write(&[0]);
+            position(point, hasher);
+        }
+        Value::MultiPoint(points) => {
+            hasher.write(&[1]);
+            line(points, hasher);
+        }
+        Value::LineString(points) => {
+            hasher.write(&[2]);
+            line(points, hasher);
+        }
+        Value::MultiLineString(lines) => {
+            hasher.write(&[3]);
+            polygon(lines, hasher);
+        }
+        Value::Polygon(rings) => {
+            hasher.write(&[4]);
+            polygon(rings, hasher);
+        }
+        Value::MultiPolygon(polygons) => {
+            hasher.write(&[5]);
+            hasher.write(&(polygons.len() as u64).to_le_bytes());
+            polygons.iter().for_each(|rings| polygon(rings, hasher));
+        }
+        Value::GeometryCollection(geometries) => {
+            hasher.write(&[6]);
+            hasher.write(&(geometries.len() as u64).to_le_bytes());
+            geometries
+                .iter()
+                .for_each(|geometry| hash_value(&geometry.value, hasher));
+        }
+    }
+}
+
+/// How features get their ids when converted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum IdPolicy {
+    /// Keeps the ids of the input features.
+    #[default]
+    Keep,
+    /// Replaces every id with a sequential number starting at 0.
+    GenerateAll,
+    /// Keeps existing ids and numbers the features without one, starting above the largest
+    /// integer id of the input so generated ids never collide with existing ones.
+    GenerateMissing,
+    /// Uses the value of this property as id when it is a string or a number, and no id
+    /// otherwise.
+    Promote(String),
+    /// Derives a number from a hash of the values of these properties, so a feature keeps its
+    /// id whatever its position in the input.
+    HashProperties(Vec<String>),
+    /// Derives a number from a 64-bit FNV-1a hash of the input coordinates of the feature.
+    ///
+    /// The hash is taken before projection and simplification, so ids stay the same whatever
+    /// the tolerances, the input order or the run.
+    HashGeometry,
+}
+
+/// Assigns ids to features one at a time according to an [`IdPolicy`].
+pub(crate) struct IdGenerator {
+    policy: IdPolicy,
+    next: u64,
+    /// Index of the first feature given each hashed id.
+    hashed: HashMap<u64, usize>,
+}
+impl IdGenerator {
+    /// Creates a generator for `features`; streamed input without ids can pass an empty slice.
+    pub fn new<'a>(policy: IdPolicy, features: impl IntoIterator<Item = &'a Feature>) -> Self {
+        let next = match &policy {
+            IdPolicy::GenerateMissing => features
+                .into_iter()
+                .filter_map(|feature| match &feature.id {
+                    Some(Id::Number(n)) => n.as_u64(),
+                    _ => None,
+                })
+                .max()
+                .map_or(0, |max| max + 1),
+            _ => 0,
+        };
+        Self {
+            policy,
+            next,
+            hashed: HashMap::new(),
+        }
+    }
+    /// Returns the id of the feature at `index` of the input, reporting hashed ids already
+    /// given to another feature in `warnings`.
+    pub fn id(
+        &mut self,
+        index: usize,
+        feature: &Feature,
+        warnings: &mut Vec<ConvertWarning>,
+    ) -> Option<Id> {
+        match &self.policy {
+            IdPolicy::Keep => feature.id.clone(),
+            IdPolicy::GenerateMissing if feature.id.is_some() => feature.id.clone(),
+            IdPolicy::GenerateAll | IdPolicy::GenerateMissing => {
+                let id = Id::Number(self.next.into());
+                self.next += 1;
+                Some(id)
+            }
+            IdPolicy::Promote(key) => match feature.property(key) {
+                Some(JsonValue::String(s)) => Some(Id::String(s.clone())),
+                Some(JsonValue::Number(n)) => Some(Id::Number(n.clone())),
+                _ => None,
+            },
+            IdPolicy::HashProperties(keys) => {
+                let mut hasher = Fnv1a::default();
+                for key in keys {
+                    hasher.write(key.as_bytes());
+                    match feature.property(key) {
+                        Some(value) => hasher.write(value.to_string().as_bytes()),
+                        None => hasher.write(&[0xff]),
+                    }
+                }
+                Some(self.hashed_id(hasher.0, index, warnings))
+            }
+            IdPolicy::HashGeometry => {
+                let mut hasher = Fnv1a::default();
+                if let Some(geometry) = &feature.geometry {
+                    hash_value(&geometry.value, &mut hasher);
+                }
+                Some(self.hashed_id(hasher.0, index, warnings))
             }
         }
-        Value::LineString(coords) => {
-            if coords.is_empty() {
-                None
-            } else {
-                Some(VtGeometry::LineString(convert_line_string(
-                    coords, tolerance,
-                )))
+    }
+    fn hashed_id(&mut self, hash: u64, index: usize, warnings: &mut Vec<ConvertWarning>) -> Id {
+        match self.hashed.entry(hash) {
+            Entry::Occupied(first) => warnings.push(ConvertWarning::IdCollision {
+                id: Id::Number(hash.into()),
+                first_index: *first.get(),
+                index,
+            }),
+            Entry::Vacant(entry) => {
+                entry.insert(index);
             }
         }
-        Value::MultiLineString(coords) => {
-            if coords.is_empty() {
-                None
-            } else {
-                Some(VtGeometry::MultiLineString(
-                    coords
-                        .iter()
-                        .map(|coords| convert_line_string(coords, tolerance))
-                        .collect(),
-                ))
+        Id::Number(hash.into())
+    }
+}
+
+/// 64-bit FNV-1a, used over `std::hash` for ids that stay stable across Rust releases.
+struct Fnv1a(u64);
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+        }
+        // Separates consecutive writes so that ("ab", "c") and ("a", "bc") differ.
+        self.0 = (self.0 ^ 0xff).wrapping_mul(0x100000001b3);
+    }
+}
+
+/// Pushes each member of a (possibly nested) geometry collection as its own feature, sharing
+/// the properties and id of `feature`.
+pub(crate) fn flatten_collection(feature: VtFeature, out: &mut Vec<VtFeature>) {
+    let VtGeometry::GeometryCollection(geometries) = feature.geometry else {
+        out.push(feature);
+        return;
+    };
+    for geometry in geometries {
+        let member = VtFeature::new(geometry, feature.properties.clone(), feature.id.clone());
+        flatten_collection(member, out);
+    }
+}
+
+/// Converts a single GeoJSON `Feature` into a `VtFeature`, see [`convert`].
+///
+/// Returns `None` if the feature has no geometry or its geometry is empty.
+pub fn convert_feature(
+    feature: Feature,
+    line_tolerance: f64,
+    polygon_tolerance: f64,
+    precision: Option<u8>,
+    id: Option<Id>,
+) -> Option<VtFeature> {
+    Converter::new(line_tolerance, polygon_tolerance, precision, false).convert_feature(feature, id)
+}
+
+/// Recomputes the simplification importance of every line and ring point of `feature` for new
+/// tolerances, leaving coordinates untouched.
+pub(crate) fn resimplify(
+    feature: &VtFeature,
+    line_tolerance: f64,
+    polygon_tolerance: f64,
+) -> VtFeature {
+    let converter = Converter::new(line_tolerance, polygon_tolerance, None, false);
+    let mut geometry = feature.geometry.clone();
+    converter.simplify_geometry(&mut geometry);
+    VtFeature::new(geometry, feature.properties.clone(), feature.id.clone())
+}
+
+/// Features left out of the index or heavily simplified while converting the input, see
+/// [`crate::GeoJSONVT::conversion_report`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConversionReport {
+    /// Features dropped from the index, in input order.
+    pub skipped: Vec<SkippedFeature>,
+    /// Features keeping less than a quarter of their line and ring points, or of their rings,
+    /// once simplified at `max_zoom`.
+    pub simplified: Vec<SimplifiedFeature>,
+    /// Features with coordinates out of range, also listed in `skipped`, see
+    /// `Options::strict_coordinates`.
+    pub errors: Vec<ConvertError>,
+}
+
+impl ConversionReport {
+    pub fn is_empty(&self) -> bool {
+        self.skipped.is_empty() && self.simplified.is_empty() && self.errors.is_empty()
+    }
+    /// First feature skipped for its input rather than its size, failing `Options::strict`.
+    pub(crate) fn first_invalid(&self) -> Option<&SkippedFeature> {
+        self.skipped
+            .iter()
+            .find(|skipped| skipped.reason != SkipReason::BelowTolerance)
+    }
+    pub(crate) fn skip(&mut self, index: usize, id: Option<Id>, reason: SkipReason) {
+        self.skipped.push(SkippedFeature { index, id, reason });
+    }
+    /// Appends `other`, whose indices refer to another input.
+    pub(crate) fn extend(&mut self, other: ConversionReport) {
+        self.skipped.extend(other.skipped);
+        self.simplified.extend(other.simplified);
+        self.errors.extend(other.errors);
+    }
+}
+
+/// A feature dropped while converting the input, see [`ConversionReport`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedFeature {
+    /// Position of the feature in the input.
+    pub index: usize,
+    /// Id of the input feature, if any.
+    pub id: Option<Id>,
+    pub reason: SkipReason,
+}
+
+impl fmt::Display for SkippedFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "feature {}", self.index)?;
+        match &self.id {
+            Some(Id::String(id)) => write!(f, " (id {id:?})")?,
+            Some(Id::Number(id)) => write!(f, " (id {id})")?,
+            None => {}
+        }
+        let reason = match self.reason {
+            SkipReason::NoGeometry => "has no geometry",
+            SkipReason::EmptyGeometry => "has no coordinates",
+            SkipReason::Collapsed => "collapsed once converted",
+            SkipReason::BelowTolerance => "is too small to show at max_zoom",
+            SkipReason::CoordinateOutOfRange => "has a coordinate out of range",
+        };
+        write!(f, " {reason}")
+    }
+}
+
+/// Why a feature was dropped while converting the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The geometry of the feature is `null`.
+    NoGeometry,
+    /// The geometry has no coordinates, or is a collection of such geometries.
+    EmptyGeometry,
+    /// Every line of the geometry has fewer than 2 points, or every polygon an exterior ring of
+    /// fewer than 4, e.g. once consecutive duplicate coordinates were removed.
+    Collapsed,
+    /// Every line of the geometry is shorter than the line tolerance at `max_zoom`, or every
+    /// polygon has an exterior ring smaller than the polygon tolerance squared, so that no tile
+    /// would draw it. See `Options::collapse_small_lines_to_points` and
+    /// `Options::collapse_small_polygons_to_points` to keep them as points instead.
+    BelowTolerance,
+    /// A coordinate is outside the valid latitudes or longitudes, see [`ConvertError`].
+    CoordinateOutOfRange,
+}
+
+/// Invalid input found while converting a feature, see `Options::strict_coordinates`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConvertError {
+    /// The feature at `index` of the input has a latitude outside `-90..=90` or a longitude
+    /// beyond the allowed world wraps, the first of which is `[lng, lat]`.
+    CoordinateOutOfRange {
+        index: usize,
+        id: Option<Id>,
+        lng: f64,
+        lat: f64,
+    },
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::CoordinateOutOfRange {
+                index, lng, lat, ..
+            } => {
+                write!(
+                    f,
+                    "feature {index} has coordinate [{lng}, {lat}] out of range"
+                )
             }
         }
+    }
+}
 
-        Value::Polygon(coords) => {
-            if coords.is_empty() {
-                None
-            } else {
-                Some(VtGeometry::Polygon(
-                    coords
+impl std::error::Error for ConvertError {}
+
+/// A feature heavily simplified at conversion, see [`ConversionReport`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimplifiedFeature {
+    /// Position of the feature in the input.
+    pub index: usize,
+    /// Id of the input feature, if any.
+    pub id: Option<Id>,
+    /// Number of line and ring points of the input.
+    pub points: usize,
+    /// Number of line and ring points kept at `max_zoom`.
+    pub kept_points: usize,
+    pub rings: usize,
+    /// Number of rings keeping at least 4 points at `max_zoom`.
+    pub kept_rings: usize,
+}
+
+impl SimplifiedFeature {
+    fn count(&mut self, geometry: &VtGeometry) {
+        let kept = |points: &[VtPoint]| points.iter().filter(|p| p.z > 0.).count();
+        let count_ring = |ring: &VtLinearRing| {
+            let kept = kept(&ring.elements);
+            self.points += ring.elements.len();
+            self.kept_points += kept;
+            self.rings += 1;
+            self.kept_rings += usize::from(kept >= 4);
+        };
+        match geometry {
+            VtGeometry::Point(_) | VtGeometry::MultiPoint(_) => {}
+            VtGeometry::LineString(line) => {
+                self.points += line.elements.len();
+                self.kept_points += kept(&line.elements);
+            }
+            VtGeometry::MultiLineString(lines) => {
+                for line in lines {
+                    self.points += line.elements.len();
+                    self.kept_points += kept(&line.elements);
+                }
+            }
+            VtGeometry::Polygon(rings) => rings.iter().for_each(count_ring),
+            VtGeometry::MultiPolygon(polygons) => polygons.iter().flatten().for_each(count_ring),
+            VtGeometry::GeometryCollection(geometries) => {
+                geometries.iter().for_each(|geometry| self.count(geometry))
+            }
+        }
+    }
+    fn is_heavy(&self) -> bool {
+        self.kept_points * 4 < self.points || self.kept_rings * 4 < self.rings
+    }
+}
+
+/// First `[lng, lat]` of `value` beyond the poles or `max_lng` either side, or not a number.
+fn out_of_range(value: &Value, max_lng: f64) -> Option<[f64; 2]> {
+    let in_range =
+        |lng: f64, lat: f64| (-90. ..=90.).contains(&lat) && (-max_lng..=max_lng).contains(&lng);
+    let check = |p: &Vec<f64>| match p[..] {
+        [lng, lat, ..] if !in_range(lng, lat) => Some([lng, lat]),
+        _ => None,
+    };
+    match value {
+        Value::Point(p) => check(p),
+        Value::MultiPoint(points) | Value::LineString(points) => points.iter().find_map(check),
+        Value::MultiLineString(lines) | Value::Polygon(lines) => {
+            lines.iter().flatten().find_map(check)
+        }
+        Value::MultiPolygon(polygons) => polygons.iter().flatten().flatten().find_map(check),
+        Value::GeometryCollection(geometries) => geometries
+            .iter()
+            .find_map(|geometry| out_of_range(&geometry.value, max_lng)),
+    }
+}
+
+/// Whether no part of `geometry` is left with enough points to be drawn.
+fn is_collapsed(geometry: &VtGeometry) -> bool {
+    let polygon_collapsed = |rings: &VtPolygon| rings.first().is_none_or(|r| r.elements.len() < 4);
+    match geometry {
+        VtGeometry::Point(_) | VtGeometry::MultiPoint(_) => false,
+        VtGeometry::LineString(line) => line.elements.len() < 2,
+        VtGeometry::MultiLineString(lines) => lines.iter().all(|line| line.elements.len() < 2),
+        VtGeometry::Polygon(rings) => polygon_collapsed(rings),
+        VtGeometry::MultiPolygon(polygons) => polygons.iter().all(polygon_collapsed),
+        VtGeometry::GeometryCollection(geometries) => geometries.iter().all(is_collapsed),
+    }
+}
+
+/// Whether no part of `geometry` is large enough to be drawn at the tolerances of `max_zoom`,
+/// including lines of zero length and polygons of zero area with a zero tolerance.
+fn is_below_tolerance(geometry: &VtGeometry, line_tolerance: f64, polygon_tolerance: f64) -> bool {
+    let line_small = |line: &VtLineString| line.dist < line_tolerance || line.dist == 0.;
+    let polygon_small = |rings: &VtPolygon| {
+        rings
+            .first()
+            .is_none_or(|ring| ring.area <= polygon_tolerance * polygon_tolerance)
+    };
+    match geometry {
+        VtGeometry::Point(_) | VtGeometry::MultiPoint(_) => false,
+        VtGeometry::LineString(line) => line_small(line),
+        VtGeometry::MultiLineString(lines) => lines.iter().all(line_small),
+        VtGeometry::Polygon(rings) => polygon_small(rings),
+        VtGeometry::MultiPolygon(polygons) => polygons.iter().all(polygon_small),
+        VtGeometry::GeometryCollection(geometries) => geometries
+            .iter()
+            .all(|geometry| is_below_tolerance(geometry, line_tolerance, polygon_tolerance)),
+    }
+}
+
+/// Center of the bounds of `points`.
+fn center(points: &[VtPoint]) -> VtPoint {
+    let (mut min, mut max) = (points[0], points[0]);
+    for p in points {
+        (min.x, min.y) = (min.x.min(p.x), min.y.min(p.y));
+        (max.x, max.y) = (max.x.max(p.x), max.y.max(p.y));
+    }
+    VtPoint::from_xy((min.x + max.x) / 2., (min.y + max.y) / 2.)
+}
+
+/// Data quality issue found while converting a feature.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConvertWarning {
+    /// The points at `segment_index` and `segment_index + 1` of a line or ring are identical,
+    /// forming a zero-length segment.
+    DuplicateCoordinates {
+        feature_id: Option<Id>,
+        segment_index: usize,
+    },
+    /// Conversion stopped at `Options::feature_limit`, leaving part of the
+    /// `total_input_features` out of the index.
+    FeatureLimitReached { total_input_features: usize },
+    /// The features at `first_index` and `index` of the input were given the same hashed `id`.
+    IdCollision {
+        id: Id,
+        first_index: usize,
+        index: usize,
+    },
+    /// `dropped` features repeated an earlier one according to `Options::dedupe_features`.
+    DuplicateFeatures { dropped: usize },
+}
+
+pub(crate) struct Converter {
+    line_tolerance: f64,
+    polygon_tolerance: f64,
+    precision: Option<u8>,
+    remove_duplicates: bool,
+    /// Whether to trust the `bbox` of the input features rather than computing it.
+    respect_bbox: bool,
+    /// Whether to drop the lines and polygons below tolerance.
+    skip_below_tolerance: bool,
+    /// Whether to keep the lines below tolerance as points rather than dropping them.
+    collapse_lines: bool,
+    /// Whether to keep the polygons below tolerance as points rather than dropping them.
+    collapse_polygons: bool,
+    /// Whether to measure the perimeter of the rings, for `Options::ring_metrics`.
+    ring_metrics: bool,
+    /// Largest absolute longitude kept, for `Options::strict_coordinates`.
+    max_lng: Option<f64>,
+    feature_id: Option<Id>,
+    pub(crate) warnings: Vec<ConvertWarning>,
+    pub(crate) report: ConversionReport,
+}
+impl Converter {
+    pub fn new(
+        line_tolerance: f64,
+        polygon_tolerance: f64,
+        precision: Option<u8>,
+        remove_duplicates: bool,
+    ) -> Self {
+        Self {
+            line_tolerance,
+            polygon_tolerance,
+            precision,
+            remove_duplicates,
+            respect_bbox: false,
+            skip_below_tolerance: false,
+            collapse_lines: false,
+            collapse_polygons: false,
+            ring_metrics: false,
+            max_lng: None,
+            feature_id: None,
+            warnings: Vec::new(),
+            report: ConversionReport::default(),
+        }
+    }
+    /// Creates a converter for the input features with the settings of `options`, ids and
+    /// duplicates aside.
+    pub fn with_options(options: &ConvertOptions) -> Self {
+        let mut converter = Self::new(
+            options.line_tolerance,
+            options.polygon_tolerance,
+            options.precision,
+            options.remove_duplicates,
+        );
+        converter.respect_bbox = options.respect_feature_bbox;
+        converter.skip_below_tolerance = options.skip_below_tolerance;
+        converter.collapse_lines = options.collapse_lines;
+        converter.collapse_polygons = options.collapse_polygons;
+        converter.ring_metrics = options.ring_metrics;
+        converter.max_lng = options.max_lng;
+        converter
+    }
+    /// Converts the feature at `index` of the input as [`Converter::convert_feature`] does,
+    /// dropping it if its geometry collapsed or is below tolerance, unless it can be collapsed
+    /// to points, and recording it in the report if skipped or heavily simplified.
+    pub fn convert_input_feature(
+        &mut self,
+        index: usize,
+        feature: Feature,
+        id: Option<Id>,
+    ) -> Option<VtFeature> {
+        let input_id = feature.id.clone();
+        if let Some(max_lng) = self.max_lng
+            && let Some(geometry) = &feature.geometry
+            && let Some([lng, lat]) = out_of_range(&geometry.value, max_lng)
+        {
+            self.report.errors.push(ConvertError::CoordinateOutOfRange {
+                index,
+                id: input_id.clone(),
+                lng,
+                lat,
+            });
+            self.report
+                .skip(index, input_id, SkipReason::CoordinateOutOfRange);
+            return None;
+        }
+        let Some(vt_feature) = self.convert_feature(feature, id) else {
+            self.report.skip(index, input_id, SkipReason::EmptyGeometry);
+            return None;
+        };
+        if is_collapsed(&vt_feature.geometry) {
+            self.report.skip(index, input_id, SkipReason::Collapsed);
+            return None;
+        }
+        if self.skip_below_tolerance
+            && is_below_tolerance(
+                &vt_feature.geometry,
+                self.line_tolerance,
+                self.polygon_tolerance,
+            )
+        {
+            let Some(points) = self.collapsed_to_points(&vt_feature.geometry) else {
+                self.report
+                    .skip(index, input_id, SkipReason::BelowTolerance);
+                return None;
+            };
+            return Some(VtFeature::new(points, vt_feature.properties, vt_feature.id));
+        }
+        let mut simplified = SimplifiedFeature {
+            index,
+            id: input_id,
+            points: 0,
+            kept_points: 0,
+            rings: 0,
+            kept_rings: 0,
+        };
+        simplified.count(&vt_feature.geometry);
+        if simplified.is_heavy() {
+            self.report.simplified.push(simplified);
+        }
+        Some(vt_feature)
+    }
+    /// Replaces each line or polygon of a geometry below tolerance with the center of its
+    /// bounds, if enabled for its type.
+    fn collapsed_to_points(&self, geometry: &VtGeometry) -> Option<VtGeometry> {
+        match geometry {
+            VtGeometry::LineString(line) if self.collapse_lines => {
+                Some(VtGeometry::Point(center(&line.elements)))
+            }
+            VtGeometry::MultiLineString(lines) if self.collapse_lines => {
+                Some(VtGeometry::MultiPoint(
+                    lines
                         .iter()
-                        .map(|coords| convert_line_ring(coords, tolerance))
+                        .filter(|line| !line.elements.is_empty())
+                        .map(|line| center(&line.elements))
                         .collect(),
                 ))
             }
-        }
-        Value::MultiPolygon(coords) => {
-            if coords.is_empty() {
-                None
-            } else {
-                Some(VtGeometry::MultiPolygon(
-                    coords
+            VtGeometry::Polygon(rings) if self.collapse_polygons => {
+                Some(VtGeometry::Point(center(&rings[0].elements)))
+            }
+            VtGeometry::MultiPolygon(polygons) if self.collapse_polygons => {
+                Some(VtGeometry::MultiPoint(
+                    polygons
                         .iter()
-                        .map(|coords| {
-                            coords
-                                .iter()
-                                .map(|coords| convert_line_ring(coords, tolerance))
-                                .collect()
-                        })
+                        .filter_map(|rings| rings.first())
+                        .filter(|ring| !ring.elements.is_empty())
+                        .map(|ring| center(&ring.elements))
                         .collect(),
                 ))
             }
+            _ => None,
         }
+    }
+    pub fn convert_feature(&mut self, feature: Feature, id: Option<Id>) -> Option<VtFeature> {
+        let geometry = feature.geometry.as_ref()?;
+        self.feature_id = id.clone();
+        let vt_geometry = self.convert_geometry(geometry)?;
+        let properties = Arc::new(feature.properties);
+        let bbox = feature
+            .bbox
+            .as_deref()
+            .and_then(|bbox| self.convert_bbox(bbox));
+        match bbox {
+            Some(bbox) if self.respect_bbox => {
+                Some(VtFeature::with_bbox(vt_geometry, properties, id, bbox))
+            }
+            _ => Some(VtFeature::new(vt_geometry, properties, id)),
+        }
+    }
+    /// Projects a GeoJSON `bbox`, 2D or 3D, or returns `None` if it is malformed or crosses
+    /// the antimeridian.
+    fn convert_bbox(&self, bbox: &[f64]) -> Option<BBox> {
+        let (min_lng, min_lat, max_lng, max_lat) = match *bbox {
+            [min_lng, min_lat, max_lng, max_lat] => (min_lng, min_lat, max_lng, max_lat),
+            [min_lng, min_lat, _, max_lng, max_lat, _] => (min_lng, min_lat, max_lng, max_lat),
+            _ => return None,
+        };
+        if min_lng > max_lng || min_lat > max_lat {
+            return None;
+        }
+        // Mercator y grows southwards.
+        let min = self.convert_coords(&[min_lng, max_lat]);
+        let max = self.convert_coords(&[max_lng, min_lat]);
+        Some(BBox {
+            min_x: min.x,
+            min_y: min.y,
+            max_x: max.x,
+            max_y: max.y,
+        })
+    }
+    pub fn convert_geometry(&mut self, geometry: &Geometry) -> Option<VtGeometry> {
+        match &geometry.value {
+            Value::Point(coords) => {
+                if coords.is_empty() {
+                    None
+                } else {
+                    Some(VtGeometry::Point(self.convert_coords(coords)))
+                }
+            }
+            Value::MultiPoint(coords) => {
+                if coords.is_empty() {
+                    None
+                } else {
+                    Some(VtGeometry::MultiPoint(
+                        coords.iter().map(|p| self.convert_coords(p)).collect(),
+                    ))
+                }
+            }
+            Value::LineString(coords) => {
+                if coords.is_empty() {
+                    None
+                } else {
+                    Some(VtGeometry::LineString(self.convert_line_string(coords)))
+                }
+            }
+            Value::MultiLineString(coords) => {
+                if coords.is_empty() {
+                    None
+                } else {
+                    Some(VtGeometry::MultiLineString(
+                        coords
+                            .iter()
+                            .map(|coords| self.convert_line_string(coords))
+                            .collect(),
+                    ))
+                }
+            }
 
-        Value::GeometryCollection(geometries) => {
-            let geometries = geometries
-                .iter()
-                .filter_map(|geometry| convert_geometry(geometry, tolerance))
-                .collect::<Vec<_>>();
-            if geometries.is_empty() {
-                None
-            } else {
-                Some(VtGeometry::GeometryCollection(geometries))
+            Value::Polygon(coords) => {
+                if coords.is_empty() {
+                    None
+                } else {
+                    Some(VtGeometry::Polygon(
+                        coords
+                            .iter()
+                            .map(|coords| self.convert_line_ring(coords))
+                            .collect(),
+                    ))
+                }
+            }
+            Value::MultiPolygon(coords) => {
+                if coords.is_empty() {
+                    None
+                } else {
+                    Some(VtGeometry::MultiPolygon(
+                        coords
+                            .iter()
+                            .map(|coords| {
+                                coords
+                                    .iter()
+                                    .map(|coords| self.convert_line_ring(coords))
+                                    .collect()
+                            })
+                            .collect(),
+                    ))
+                }
+            }
+
+            Value::GeometryCollection(geometries) => {
+                let geometries = geometries
+                    .iter()
+                    .filter_map(|geometry| self.convert_geometry(geometry))
+                    .collect::<Vec<_>>();
+                if geometries.is_empty() {
+                    None
+                } else {
+                    Some(VtGeometry::GeometryCollection(geometries))
+                }
             }
         }
     }
-}
 
-fn convert_line_string(coords: &Vec<Vec<f64>>, tolerance: f64) -> VtLineString {
-    let mut dist = 0.;
-    let mut elements = coords
-        .iter()
-        .map(|coord| convert_coords(coord))
-        .collect::<Vec<_>>();
-    for w in elements.windows(2) {
-        let a = w[0];
-        let b = w[1];
-        dist += (b.x - a.x).hypot(b.y - a.y);
+    fn simplify_geometry(&self, geometry: &mut VtGeometry) {
+        let simplify_points = |points: &mut [VtPoint], tolerance: f64| {
+            points.iter_mut().for_each(|p| p.z = 0.);
+            simplify::simplify(points, tolerance);
+        };
+        match geometry {
+            VtGeometry::Point(_) | VtGeometry::MultiPoint(_) => (),
+            VtGeometry::LineString(line) => {
+                simplify_points(&mut line.elements, self.line_tolerance)
+            }
+            VtGeometry::MultiLineString(lines) => lines
+                .iter_mut()
+                .for_each(|line| simplify_points(&mut line.elements, self.line_tolerance)),
+            VtGeometry::Polygon(rings) => rings
+                .iter_mut()
+                .for_each(|ring| simplify_points(&mut ring.elements, self.polygon_tolerance)),
+            VtGeometry::MultiPolygon(polygons) => polygons
+                .iter_mut()
+                .flatten()
+                .for_each(|ring| simplify_points(&mut ring.elements, self.polygon_tolerance)),
+            VtGeometry::GeometryCollection(geometries) => geometries
+                .iter_mut()
+                .for_each(|geometry| self.simplify_geometry(geometry)),
+        }
     }
-    simplify::simplify(&mut elements, tolerance);
-    VtLineString {
-        elements,
-        dist,
-        seg_start: 0.,
-        seg_end: 0.,
+
+    fn convert_line_string(&mut self, coords: &[Vec<f64>]) -> VtLineString {
+        let points = self.convert_points(coords);
+        let mut line = VtLineString::new(points);
+        simplify::simplify(&mut line.elements, self.line_tolerance);
+        line
     }
-}
 
-fn convert_line_ring(coords: &Vec<Vec<f64>>, tolerance: f64) -> VtLinearRing {
-    let mut area = 0.;
-    let mut elements = coords
-        .iter()
-        .map(|coord| convert_coords(coord))
-        .collect::<Vec<_>>();
-    for w in elements.windows(2) {
-        let a = w[0];
-        let b = w[1];
-        area += a.x * b.y - b.x * a.y;
+    fn convert_line_ring(&mut self, coords: &[Vec<f64>]) -> VtLinearRing {
+        let points = self.convert_points(coords);
+        let mut ring = VtLinearRing::new(points);
+        simplify::simplify(&mut ring.elements, self.polygon_tolerance);
+        if self.ring_metrics {
+            ring.measure();
+        }
+        ring
     }
 
-    simplify::simplify(&mut elements, tolerance);
-    area = (area / 2.).abs();
-    VtLinearRing { elements, area }
-}
+    /// Projects the points of a line or ring, reporting (and optionally dropping) consecutive
+    /// duplicates.
+    fn convert_points(&mut self, coords: &[Vec<f64>]) -> Vec<VtPoint> {
+        let mut points: Vec<VtPoint> = Vec::with_capacity(coords.len());
+        for (i, coord) in coords.iter().enumerate() {
+            let point = self.convert_coords(coord);
+            if let Some(last) = points.last()
+                && last.x == point.x
+                && last.y == point.y
+            {
+                self.warnings.push(ConvertWarning::DuplicateCoordinates {
+                    feature_id: self.feature_id.clone(),
+                    segment_index: i - 1,
+                });
+                if self.remove_duplicates {
+                    continue;
+                }
+            }
+            points.push(point);
+        }
+        points
+    }
 
-fn convert_coords(coords: &[f64]) -> VtPoint {
-    let x = lng_to_mercator_x(coords[0]);
-    let y = lat_to_mercator_y(coords[1]);
-    VtPoint::from_xy(x, y)
+    fn convert_coords(&self, coords: &[f64]) -> VtPoint {
+        let mut x = lng_to_mercator_x(coords[0]);
+        let mut y = lat_to_mercator_y(coords[1]);
+        if let Some(digits) = self.precision {
+            x = round_significant(x, digits);
+            y = round_significant(y, digits);
+        }
+        VtPoint::from_xy(x, y)
+    }
 }
 
 #[inline]
-fn lng_to_mercator_x(lng: f64) -> f64 {
+pub(crate) fn lng_to_mercator_x(lng: f64) -> f64 {
     lng / 360. + 0.5
 }
 #[inline]
-fn lat_to_mercator_y(lat: f64) -> f64 {
+pub(crate) fn lat_to_mercator_y(lat: f64) -> f64 {
     let sin = lat.to_radians().sin();
     let y = 0.5 - 0.25 * ((1. + sin) / (1. - sin)).ln() / std::f64::consts::PI;
     y.clamp(0., 1.)
 }
+
+#[inline]
+pub(crate) fn mercator_x_to_lng(x: f64) -> f64 {
+    (x - 0.5) * 360.
+}
+#[inline]
+pub(crate) fn mercator_y_to_lat(y: f64) -> f64 {
+    let y2 = (1. - 2. * y) * std::f64::consts::PI;
+    y2.sinh().atan().to_degrees()
+}
+
+/// Rounds `v` to `digits` significant decimal digits.
+fn round_significant(v: f64, digits: u8) -> f64 {
+    if v == 0. || !v.is_finite() {
+        return v;
+    }
+    let magnitude = v.abs().log10().ceil() as i32;
+    let scale = 10f64.powi(digits as i32 - magnitude);
+    (v * scale).round() / scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inverse_projection() {
+        for lng in [-180., -77.03654, 0., 120.5, 180.] {
+            assert!((mercator_x_to_lng(lng_to_mercator_x(lng)) - lng).abs() < 1e-9);
+        }
+        for lat in [-85., -38.8951, 0., 47.1, 85.] {
+            assert!((mercator_y_to_lat(lat_to_mercator_y(lat)) - lat).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_flatten_geometry_collections() {
+        let geojson: geojson::GeoJson = r#"{"type":"Feature","id":"a","properties":{"k":1},
+            "geometry":{"type":"GeometryCollection","geometries":[
+                {"type":"Point","coordinates":[0,0]},
+                {"type":"GeometryCollection","geometries":[
+                    {"type":"LineString","coordinates":[[0,0],[10,10]]},
+                    {"type":"Polygon","coordinates":[[[0,0],[1,0],[1,1],[0,0]]]}
+                ]}
+            ]}}"#
+            .parse()
+            .unwrap();
+        let geojson::GeoJson::Feature(feature) = geojson else {
+            panic!("not a feature");
+        };
+        let fc = FeatureCollection::from_iter([feature]);
+
+        let nested = convert(fc.clone(), &ConvertOptions::default());
+        assert_eq!(nested.len(), 1);
+        assert!(matches!(
+            nested[0].geometry,
+            VtGeometry::GeometryCollection(_)
+        ));
+
+        let flat = convert(
+            fc,
+            &ConvertOptions {
+                flatten_collections: true,
+                ..ConvertOptions::default()
+            },
+        );
+        assert_eq!(flat.len(), 3);
+        assert!(matches!(flat[0].geometry, VtGeometry::Point(_)));
+        assert!(matches!(flat[1].geometry, VtGeometry::LineString(_)));
+        assert!(matches!(flat[2].geometry, VtGeometry::Polygon(_)));
+        for feature in &flat {
+            assert_eq!(feature.id, Some(Id::String("a".to_string())));
+            assert!(Arc::ptr_eq(&feature.properties, &flat[0].properties));
+        }
+        assert_eq!(flat[0].bbox.unwrap().max_x, 0.5);
+        assert_eq!(flat[1].point_count, 2);
+    }
+
+    #[test]
+    fn test_multi_point_indices() {
+        let feature = Feature::from(geojson::Geometry::new(geojson::Value::MultiPoint(vec![
+            vec![0., 0.],
+            vec![10., 10.],
+            vec![20., 20.],
+        ])));
+        let converted = convert(
+            FeatureCollection::from_iter([feature]),
+            &ConvertOptions::default(),
+        );
+        let VtGeometry::MultiPoint(points) = &converted[0].geometry else {
+            panic!("not a MultiPoint");
+        };
+        assert_eq!(points.indices, [0, 1, 2]);
+        assert!(points.elements.iter().all(VtPoint::is_original));
+        assert!(!points.elements.iter().any(VtPoint::is_synthetic));
+    }
+
+    #[test]
+    fn test_round_significant() {
+        assert_eq!(round_significant(0.123456789, 3), 0.123);
+        assert_eq!(round_significant(0.000123456, 2), 0.00012);
+        assert_eq!(round_significant(1.0, 7), 1.0);
+        assert_eq!(round_significant(0., 7), 0.);
+    }
+
+    #[test]
+    fn test_convert_with_precision() {
+        let converter = Converter::new(0., 0., Some(7), false);
+        let a = converter.convert_coords(&[-77.036540, 38.895110]);
+        let b = converter.convert_coords(&[-77.036540000001, 38.895110000001]);
+        assert_eq!(a, b);
+        assert_eq!(a.x, round_significant(lng_to_mercator_x(-77.03654), 7));
+
+        let full = Converter::new(0., 0., None, false).convert_coords(&[-77.03654, 38.89511]);
+        assert_eq!(full.x, lng_to_mercator_x(-77.03654));
+    }
+
+    #[test]
+    fn test_duplicate_coordinates() {
+        let geojson: geojson::GeoJson = r#"{"type":"Feature","id":7,"properties":null,
+            "geometry":{"type":"Polygon","coordinates":[
+                [[0,0],[10,0],[10,0],[10,10],[10,10],[10,10],[0,0]]
+            ]}}"#
+            .parse()
+            .unwrap();
+        let geojson::GeoJson::Feature(feature) = geojson else {
+            panic!("not a feature");
+        };
+        let fc = FeatureCollection::from_iter([feature]);
+        let expected: Vec<_> = [1, 3, 4]
+            .into_iter()
+            .map(|segment_index| ConvertWarning::DuplicateCoordinates {
+                feature_id: Some(Id::Number(7.into())),
+                segment_index,
+            })
+            .collect();
+
+        let (kept, warnings) = convert_with_warnings(fc.clone(), &ConvertOptions::default());
+        assert_eq!(warnings, expected);
+        let VtGeometry::Polygon(kept_rings) = &kept[0].geometry else {
+            panic!("not a polygon");
+        };
+        assert_eq!(kept_rings[0].elements.len(), 7);
+
+        let (removed, warnings) = convert_with_warnings(
+            fc,
+            &ConvertOptions {
+                remove_duplicates: true,
+                ..ConvertOptions::default()
+            },
+        );
+        assert_eq!(warnings, expected);
+        let VtGeometry::Polygon(rings) = &removed[0].geometry else {
+            panic!("not a polygon");
+        };
+        assert_eq!(rings[0].elements.len(), 4);
+        assert_eq!(rings[0].area, kept_rings[0].area);
+    }
+
+    #[test]
+    fn test_id_policy() {
+        let geojson: geojson::GeoJson = r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":7,"properties":{"code":"a"},
+             "geometry":{"type":"Point","coordinates":[0,0]}},
+            {"type":"Feature","properties":{"code":3},
+             "geometry":{"type":"Point","coordinates":[1,1]}},
+            {"type":"Feature","id":"x","properties":{"code":[1]},
+             "geometry":{"type":"Point","coordinates":[2,2]}},
+            {"type":"Feature","id":2,"properties":null,
+             "geometry":{"type":"Point","coordinates":[3,3]}},
+            {"type":"Feature","properties":null,
+             "geometry":{"type":"Point","coordinates":[4,4]}}
+        ]}"#
+        .parse()
+        .unwrap();
+        let geojson::GeoJson::FeatureCollection(fc) = geojson else {
+            panic!("not a feature collection");
+        };
+        let ids = |policy: IdPolicy| {
+            let (features, _) = convert_with_warnings(
+                fc.clone(),
+                &ConvertOptions {
+                    id_policy: policy.clone(),
+                    ..ConvertOptions::default()
+                },
+            );
+            features.into_iter().map(|f| f.id).collect::<Vec<_>>()
+        };
+        let number = |n: u64| Some(Id::Number(n.into()));
+        let string = |s: &str| Some(Id::String(s.to_string()));
+
+        let original = vec![number(7), None, string("x"), number(2), None];
+        assert_eq!(ids(IdPolicy::Keep), original);
+        assert_eq!(
+            ids(IdPolicy::GenerateAll),
+            (0..5).map(number).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            ids(IdPolicy::GenerateMissing),
+            vec![number(7), number(8), string("x"), number(2), number(9)]
+        );
+        assert_eq!(
+            ids(IdPolicy::Promote("code".to_string())),
+            vec![string("a"), number(3), None, None, None]
+        );
+        let generated = convert(
+            fc.clone(),
+            &ConvertOptions {
+                id_policy: IdPolicy::GenerateAll,
+                ..ConvertOptions::default()
+            },
+        );
+        assert_eq!(
+            generated.into_iter().map(|f| f.id).collect::<Vec<_>>(),
+            ids(IdPolicy::GenerateAll)
+        );
+    }
+
+    #[test]
+    fn test_hash_value() {
+        let hash = |value: Value| {
+            let mut hasher = Fnv1a::default();
+            hash_value(&value, &mut hasher);
+            hasher.0
+        };
+        let coords = vec![vec![0., 0.], vec![1., 1.]];
+        assert_eq!(
+            hash(Value::LineString(coords.clone())),
+            hash(Value::LineString(coords.clone()))
+        );
+        // The same coordinates in another shape or in another grouping hash differently.
+        assert_ne!(
+            hash(Value::LineString(coords.clone())),
+            hash(Value::MultiPoint(coords.clone()))
+        );
+        assert_ne!(
+            hash(Value::MultiLineString(vec![coords.clone()])),
+            hash(Value::MultiLineString(vec![
+                coords[..1].to_vec(),
+                coords[1..].to_vec()
+            ]))
+        );
+        // Coordinates are hashed by their bits, so -0 and 0 differ.
+        assert_ne!(
+            hash(Value::Point(vec![0., 0.])),
+            hash(Value::Point(vec![-0., 0.]))
+        );
+    }
+
+    #[test]
+    fn test_hashed_ids() {
+        let feature = |name: &str, x: f64| {
+            let mut feature = Feature::from(Geometry::new(Value::Point(vec![x, 0.])));
+            feature.set_property("name", name);
+            feature.set_property("rank", 1);
+            feature
+        };
+        let features = vec![feature("a", 0.), feature("b", 1.), feature("c", 2.)];
+        let mut shuffled = features.clone();
+        shuffled.rotate_left(1);
+        let ids_by_name = |features: &[Feature], policy: &IdPolicy| {
+            let fc = FeatureCollection::from_iter(features.iter().cloned());
+            let (converted, warnings) = convert_with_warnings(
+                fc,
+                &ConvertOptions {
+                    id_policy: policy.clone(),
+                    ..ConvertOptions::default()
+                },
+            );
+            assert!(warnings.is_empty());
+            let mut ids = converted
+                .into_iter()
+                .map(|f| (f.properties.as_ref().clone().unwrap()["name"].clone(), f.id))
+                .collect::<Vec<_>>();
+            ids.sort_by_key(|(name, _)| name.to_string());
+            ids
+        };
+
+        let keys = vec!["name".to_string(), "rank".to_string()];
+        for policy in [IdPolicy::HashProperties(keys), IdPolicy::HashGeometry] {
+            let ids = ids_by_name(&features, &policy);
+            assert_eq!(ids, ids_by_name(&shuffled, &policy));
+            assert!(ids.iter().all(|(_, id)| matches!(id, Some(Id::Number(_)))));
+        }
+        assert_ne!(
+            ids_by_name(&features, &IdPolicy::GenerateAll),
+            ids_by_name(&shuffled, &IdPolicy::GenerateAll)
+        );
+
+        let collision = vec![feature("a", 0.), feature("b", 1.), feature("a", 2.)];
+        let fc = FeatureCollection::from_iter(collision);
+        let policy = IdPolicy::HashProperties(vec!["name".to_string()]);
+        let (converted, warnings) = convert_with_warnings(
+            fc,
+            &ConvertOptions {
+                id_policy: policy.clone(),
+                ..ConvertOptions::default()
+            },
+        );
+        assert_eq!(converted[0].id, converted[2].id);
+        assert_eq!(
+            warnings,
+            vec![ConvertWarning::IdCollision {
+                id: converted[0].id.clone().unwrap(),
+                first_index: 0,
+                index: 2,
+            }]
+        );
+
+        let line = Geometry::new(Value::LineString(vec![
+            vec![0., 0.],
+            vec![1., 0.001],
+            vec![2., 0.],
+        ]));
+        let hashed_id = |tolerance: f64| {
+            let fc = FeatureCollection::from_iter([Feature::from(line.clone())]);
+            let (converted, _) = convert_with_warnings(
+                fc,
+                &ConvertOptions {
+                    line_tolerance: tolerance,
+                    polygon_tolerance: tolerance,
+                    id_policy: IdPolicy::HashGeometry,
+                    ..ConvertOptions::default()
+                },
+            );
+            converted[0].id.clone()
+        };
+        assert!(hashed_id(0.).is_some());
+        assert_eq!(hashed_id(0.), hashed_id(0.1));
+    }
+
+    #[test]
+    fn test_skip_below_tolerance() {
+        let line = Geometry::new(Value::LineString(vec![vec![0., 0.], vec![1., 0.001]]));
+        let fc = FeatureCollection::from_iter([Feature::from(line)]);
+        let converted = |skip_below_tolerance: bool| {
+            let options = ConvertOptions {
+                line_tolerance: 0.1,
+                skip_below_tolerance,
+                ..ConvertOptions::default()
+            };
+            convert_with_report(fc.clone(), &options)
+        };
+        let (kept, _, report) = converted(false);
+        assert_eq!(kept.len(), 1);
+        assert!(report.is_empty());
+        let (dropped, _, report) = converted(true);
+        assert!(dropped.is_empty());
+        assert_eq!(report.skipped[0].reason, SkipReason::BelowTolerance);
+        assert!(report.first_invalid().is_none());
+    }
+
+    #[test]
+    fn test_respect_feature_bbox() {
+        let line = Geometry::new(Value::LineString(vec![vec![-10., -5.], vec![20., 10.]]));
+        let feature = Feature::from(line);
+        let convert_bbox = |bbox: Option<Vec<f64>>, respect: bool| {
+            let mut feature = feature.clone();
+            feature.bbox = bbox;
+            let (features, _) = convert_with_warnings(
+                FeatureCollection::from_iter([feature]),
+                &ConvertOptions {
+                    respect_feature_bbox: respect,
+                    ..ConvertOptions::default()
+                },
+            );
+            let bbox = features[0].bbox.unwrap();
+            assert_eq!(features[0].point_count, 2);
+            (bbox.min_x, bbox.min_y, bbox.max_x, bbox.max_y)
+        };
+        let computed = convert_bbox(None, true);
+        let (x0, x1) = (lng_to_mercator_x(-10.), lng_to_mercator_x(20.));
+        assert_eq!(
+            computed,
+            (x0, lat_to_mercator_y(10.), x1, lat_to_mercator_y(-5.))
+        );
+
+        // A looser hint is taken as is, whether 2D or 3D.
+        let hint = (lng_to_mercator_x(-20.), lat_to_mercator_y(30.));
+        let hint = (hint.0, hint.1, x1, lat_to_mercator_y(-5.));
+        assert_eq!(convert_bbox(Some(vec![-20., -5., 20., 30.]), true), hint);
+        let bbox_3d = vec![-20., -5., 0., 20., 30., 100.];
+        assert_eq!(convert_bbox(Some(bbox_3d), true), hint);
+        assert_eq!(
+            convert_bbox(Some(vec![-20., -5., 20., 30.]), false),
+            computed
+        );
+
+        // Malformed hints and those crossing the antimeridian are ignored.
+        assert_eq!(convert_bbox(Some(vec![-20., -5., 20.]), true), computed);
+        assert_eq!(
+            convert_bbox(Some(vec![170., -5., -170., 30.]), true),
+            computed
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use std::{collections::HashSet, sync::Arc};
+
+use geojson::{Feature, Geometry};
+
+use crate::{
+    convert::convert_feature,
+    geojson_vt::{Options, split_features},
+    tile::TileCoord,
+    types::{VtFeature, features_bbox},
+    wrap::wrap,
+};
+
+/// Returns the tiles at zoom `z` that `geometry` touches, buffer included, sorted by row then
+/// column.
+///
+/// The geometry is projected and wrapped across the antimeridian as for indexing, then clipped
+/// down the quadtree, so a tile is only returned if some part of the geometry falls within it.
+/// `buffer` is expressed in units of a tile `extent` wide, as in [`Options`].
+#[must_use = "`tiles_covering` returns the tiles without side effects"]
+pub fn tiles_covering(geometry: &Geometry, z: u8, buffer: u32, extent: u32) -> Vec<TileCoord> {
+    let options = Options {
+        buffer,
+        extent,
+        ..Options::default()
+    };
+    let mut tiles = Vec::new();
+    let mut stack = vec![(0, 0, 0, project(geometry, &options))];
+    while let Some((tz, x, y, features)) = stack.pop() {
+        if features.is_empty() {
+            continue;
+        }
+        if tz == z {
+            tiles.push(TileCoord::new(x, y, z));
+            continue;
+        }
+        let bbox = features_bbox(&features);
+        let children = split_features(&features, tz, x, y, &bbox, &options);
+        let child_coords = [(0, 0), (1, 0), (0, 1), (1, 1)];
+        for ((dx, dy), features) in child_coords.into_iter().zip(children) {
+            stack.push((tz + 1, x * 2 + dx, y * 2 + dy, features));
+        }
+    }
+    tiles.sort_by_key(|tile| (tile.y(), tile.x()));
+    tiles
+}
+
+/// Same as [`tiles_covering`], only comparing tiles with the bounding boxes of the geometry.
+///
+/// Faster, but returns every tile overlapping the bounding boxes, including those the geometry
+/// itself misses.
+#[must_use = "`tiles_covering_bbox` returns the tiles without side effects"]
+pub fn tiles_covering_bbox(geometry: &Geometry, z: u8, buffer: u32, extent: u32) -> Vec<TileCoord> {
+    let options = Options {
+        buffer,
+        extent,
+        ..Options::default()
+    };
+    let z2 = (1u32 << z) as f64;
+    let b = buffer as f64 / extent as f64;
+    let range = |min: f64, max: f64| {
+        let first = (min * z2 - 1. - b).floor().max(0.) as u32;
+        let last = (max * z2 + b).ceil().min(z2 - 1.) as u32;
+        (first..=last)
+            .filter(move |&v| (v as f64 - b) / z2 <= max && (v as f64 + 1. + b) / z2 >= min)
+    };
+    let mut tiles = HashSet::new();
+    for feature in project(geometry, &options) {
+        let Some(bbox) = feature.bbox else {
+            continue;
+        };
+        for y in range(bbox.min_y, bbox.max_y) {
+            tiles.extend(range(bbox.min_x, bbox.max_x).map(|x| (y, x)));
+        }
+    }
+    let mut tiles = Vec::from_iter(tiles);
+    tiles.sort();
+    tiles
+        .into_iter()
+        .map(|(y, x)| TileCoord::new(x, y, z))
+        .collect()
+}
+
+/// Projects `geometry` and wraps it into the world as [`crate::GeoJSONVT`] does.
+fn project(geometry: &Geometry, options: &Options) -> Vec<Arc<VtFeature>> {
+    let feature = Feature::from(geometry.clone());
+    let Some(feature) = convert_feature(feature, 0., 0., None, None) else {
+        return Vec::new();
+    };
+    let buffer = options.buffer as f64 / options.extent as f64;
+    wrap(
+        &[Arc::new(feature)],
+        buffer,
+        false,
+        false,
+        options.point_boundary,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clip::{Boundary, clip};
+    use geojson::Value;
+
+    /// Checks every tile at `z`, row by row, by clipping the geometry to its buffered bounds.
+    fn brute_force(geometry: &Geometry, z: u8, buffer: u32, extent: u32) -> Vec<(u32, u32)> {
+        let options = Options {
+            buffer,
+            extent,
+            ..Options::default()
+        };
+        let features = project(geometry, &options);
+        let z2 = (1u32 << z) as f64;
+        let b = buffer as f64 / extent as f64;
+        let (min_all, max_all) = (f64::NEG_INFINITY, f64::INFINITY);
+        let mut tiles = Vec::new();
+        for y in 0..1u32 << z {
+            for x in 0..1u32 << z {
+                let (x0, x1) = ((x as f64 - b) / z2, (x as f64 + 1. + b) / z2);
+                let (y0, y1) = ((y as f64 - b) / z2, (y as f64 + 1. + b) / z2);
+                let both = Boundary::Both;
+                let clipped = clip::<0>(&features, x0, x1, min_all, max_all, false, false, both);
+                if !clip::<1>(&clipped, y0, y1, min_all, max_all, false, false, both).is_empty() {
+                    tiles.push((x, y));
+                }
+            }
+        }
+        tiles
+    }
+
+    fn coords(tiles: &[TileCoord]) -> Vec<(u32, u32)> {
+        tiles.iter().map(|tile| (tile.x(), tile.y())).collect()
+    }
+
+    #[test]
+    fn test_tiles_covering_matches_brute_force() {
+        // Linear congruential generator, enough to scatter test geometries.
+        let mut seed = 42u64;
+        let mut random = |min: f64, max: f64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+            min + (max - min) * (seed >> 11) as f64 / (1u64 << 53) as f64
+        };
+        for i in 0..60 {
+            let (lng, lat) = (random(-200., 200.), random(-80., 80.));
+            let mut points: Vec<Vec<f64>> = (0..5)
+                .map(|_| vec![lng + random(-40., 40.), lat + random(-20., 20.)])
+                .collect();
+            let value = match i % 3 {
+                0 => Value::MultiPoint(points),
+                1 => Value::LineString(points),
+                _ => {
+                    points.push(points[0].clone());
+                    Value::Polygon(vec![points])
+                }
+            };
+            let geometry = Geometry::new(value);
+            for z in 0..=4 {
+                for buffer in [0, 64, 512] {
+                    let found = coords(&tiles_covering(&geometry, z, buffer, 4096));
+                    let expected = brute_force(&geometry, z, buffer, 4096);
+                    assert_eq!(found, expected, "{:?} z{z} b{buffer}", geometry);
+
+                    let bbox = coords(&tiles_covering_bbox(&geometry, z, buffer, 4096));
+                    assert!(found.iter().all(|tile| bbox.contains(tile)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tiles_covering_antimeridian() {
+        let line = Geometry::new(Value::LineString(vec![vec![170., 10.], vec![190., 10.]]));
+        assert_eq!(coords(&tiles_covering(&line, 1, 0, 4096)), [(0, 0), (1, 0)]);
+        let tiles = coords(&tiles_covering(&line, 4, 0, 4096));
+        assert_eq!(tiles, [(0, 7), (15, 7)]);
+        assert_eq!(coords(&tiles_covering_bbox(&line, 4, 0, 4096)), tiles);
+    }
+}
@@ -0,0 +1,115 @@
+use ::geo_types::{
+    Coord, Geometry, LineString, MultiLineString, MultiPoint, MultiPolygon, Point, Polygon,
+};
+
+use crate::{
+    convert::{lat_to_mercator_y, lng_to_mercator_x, mercator_x_to_lng, mercator_y_to_lat},
+    types::{VtGeometry, VtLineString, VtLinearRing, VtPoint, VtPolygon},
+};
+
+impl From<Coord<f64>> for VtPoint {
+    /// Takes the coordinate as is, in the projected 0..1 space, without simplification
+    /// importance.
+    fn from(coord: Coord<f64>) -> Self {
+        VtPoint::from_xy(coord.x, coord.y)
+    }
+}
+
+impl From<VtPoint> for Coord<f64> {
+    fn from(point: VtPoint) -> Self {
+        Coord {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+impl From<&VtGeometry> for Geometry<f64> {
+    /// Keeps the coordinates in the projected 0..1 space, see [`VtGeometry::to_geo_lnglat`] for
+    /// longitudes and latitudes.
+    fn from(geometry: &VtGeometry) -> Self {
+        to_geo(geometry, &|point| Coord::from(*point))
+    }
+}
+
+impl From<&Geometry<f64>> for VtGeometry {
+    /// Takes the coordinates as is, in the projected 0..1 space, computing line lengths and
+    /// ring areas. `Line`s become line strings, `Rect`s and `Triangle`s polygons.
+    fn from(geometry: &Geometry<f64>) -> Self {
+        from_geo(geometry, &VtPoint::from)
+    }
+}
+
+impl VtGeometry {
+    /// Converts the geometry to `geo-types`, inverse-projecting its points to longitude and
+    /// latitude.
+    #[must_use = "`to_geo_lnglat` returns the converted geometry"]
+    pub fn to_geo_lnglat(&self) -> Geometry<f64> {
+        to_geo(self, &|point| Coord {
+            x: mercator_x_to_lng(point.x),
+            y: mercator_y_to_lat(point.y),
+        })
+    }
+    /// Creates a geometry from a `geo-types` one in longitude and latitude, projecting its
+    /// points as [`crate::convert()`] does without simplifying them.
+    #[must_use = "`from_geo_lnglat` returns a new geometry"]
+    pub fn from_geo_lnglat(geometry: &Geometry<f64>) -> VtGeometry {
+        from_geo(geometry, &|coord| {
+            VtPoint::from_xy(lng_to_mercator_x(coord.x), lat_to_mercator_y(coord.y))
+        })
+    }
+}
+
+fn to_geo(geometry: &VtGeometry, coord: &dyn Fn(&VtPoint) -> Coord<f64>) -> Geometry<f64> {
+    let line = |points: &[VtPoint]| LineString::from_iter(points.iter().map(coord));
+    let polygon = |rings: &VtPolygon| {
+        let mut rings = rings.iter().map(|ring| line(&ring.elements));
+        let exterior = rings.next().unwrap_or_else(|| LineString::new(vec![]));
+        Polygon::new(exterior, rings.collect())
+    };
+    match geometry {
+        VtGeometry::Point(p) => Point::from(coord(p)).into(),
+        VtGeometry::MultiPoint(ps) => MultiPoint::from_iter(ps.elements.iter().map(coord)).into(),
+        VtGeometry::LineString(ls) => line(&ls.elements).into(),
+        VtGeometry::MultiLineString(mls) => {
+            MultiLineString::from_iter(mls.iter().map(|ls| line(&ls.elements))).into()
+        }
+        VtGeometry::Polygon(poly) => polygon(poly).into(),
+        VtGeometry::MultiPolygon(mpoly) => {
+            MultiPolygon::from_iter(mpoly.iter().map(polygon)).into()
+        }
+        VtGeometry::GeometryCollection(gc) => {
+            Geometry::GeometryCollection(gc.iter().map(|g| to_geo(g, coord)).collect())
+        }
+    }
+}
+
+fn from_geo(geometry: &Geometry<f64>, point: &dyn Fn(Coord<f64>) -> VtPoint) -> VtGeometry {
+    let line = |ls: &LineString<f64>| VtLineString::new(ls.coords().map(|c| point(*c)).collect());
+    let ring = |ls: &LineString<f64>| VtLinearRing::new(ls.coords().map(|c| point(*c)).collect());
+    let polygon = |poly: &Polygon<f64>| {
+        let exterior = std::iter::once(poly.exterior());
+        exterior
+            .chain(poly.interiors())
+            .map(ring)
+            .collect::<VtPolygon>()
+    };
+    match geometry {
+        Geometry::Point(p) => VtGeometry::Point(point(p.0)),
+        Geometry::Line(l) => VtGeometry::LineString(line(&LineString::from(*l))),
+        Geometry::LineString(ls) => VtGeometry::LineString(line(ls)),
+        Geometry::Polygon(poly) => VtGeometry::Polygon(polygon(poly)),
+        Geometry::MultiPoint(mp) => VtGeometry::MultiPoint(mp.iter().map(|p| point(p.0)).collect()),
+        Geometry::MultiLineString(mls) => {
+            VtGeometry::MultiLineString(mls.iter().map(line).collect())
+        }
+        Geometry::MultiPolygon(mpoly) => {
+            VtGeometry::MultiPolygon(mpoly.iter().map(polygon).collect())
+        }
+        Geometry::GeometryCollection(gc) => {
+            VtGeometry::GeometryCollection(gc.iter().map(|g| from_geo(g, point)).collect())
+        }
+        Geometry::Rect(rect) => VtGeometry::Polygon(polygon(&rect.to_polygon())),
+        Geometry::Triangle(triangle) => VtGeometry::Polygon(polygon(&triangle.to_polygon())),
+    }
+}
@@ -1,102 +1,1621 @@
 use core::panic;
-use geojson::{Feature, FeatureCollection, GeoJson};
+use geojson::{
+    Feature, FeatureCollection, GeoJson, Geometry, JsonObject, Position, Value, feature::Id,
+};
 
+use lru::LruCache;
 use std::{
-    collections::{HashMap, hash_map::Entry},
-    rc::Rc,
+    collections::{BTreeSet, HashMap, HashSet, hash_map::Entry},
+    fmt,
+    num::NonZeroUsize,
+    sync::{
+        Arc, Mutex, PoisonError,
+        mpsc::{self, Receiver, Sender},
+    },
+    time::{Duration, Instant},
 };
 
 use crate::{
-    clip::clip,
-    convert::convert,
-    tile::{EMPTY_TILE, InternalTile, Tile, TileCoord},
-    types::VtFeature,
+    clip::{Boundary, clip},
+    compact::CompactTile,
+    convert::{
+        ConversionReport, ConvertError, ConvertOptions, ConvertWarning, DedupePolicy, IdGenerator,
+        IdPolicy, SkippedFeature, convert_with_ids, id_key, lat_to_mercator_y, lng_to_mercator_x,
+        mercator_x_to_lng, mercator_y_to_lat, resimplify,
+    },
+    mask::Mask,
+    tile::{
+        EMPTY_TILE, FeatureOrder, InternalTile, NumberStyle, Tile, TileCoord, TileMetrics,
+        TileOptions, TileOrigin, TilePropertyHook, YAxis,
+    },
+    types::{
+        BBox, VtFeature, VtGeometry, VtLineString, VtLinearRing, VtPoint,
+        WEB_MERCATOR_CIRCUMFERENCE, features_bbox,
+    },
     wrap::wrap,
 };
 
-#[derive(Debug, Copy, Clone)]
+/// Unit in which [`Options::tolerance`] and its per-geometry overrides are expressed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum ToleranceUnit {
+    /// Tile extent units; simplification changes when `extent` changes.
+    #[default]
+    ExtentUnits,
+    /// Screen pixels of a tile rendered at `tile_size` pixels wide.
+    Pixels { tile_size: u16 },
+}
+
+/// Unit in which [`Options::buffer`] is expressed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum BufferUnit {
+    /// Tile extent units; the margin shrinks on screen when `extent` grows.
+    #[default]
+    ExtentUnits,
+    /// Screen pixels of a tile rendered at `tile_size` pixels wide.
+    Pixels { tile_size: u16 },
+}
+
+/// Invalid combination of [`Options`], see [`Options::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptionsError {
+    /// The buffer, in extent units, is at least half the extent, making the clip ranges of
+    /// sibling tiles overlap entirely.
+    BufferTooLarge { buffer: u32, extent: u32 },
+    /// The extent is above 2^30, past which the tile coordinates, buffer included, no longer
+    /// fit the signed 32-bit integers of vector tile geometries.
+    ExtentTooLarge { extent: u32 },
+    /// The `tile_size` of [`BufferUnit::Pixels`] or [`ToleranceUnit::Pixels`] is 0.
+    ZeroTileSize,
+    /// [`Options::mask`] is not a non-empty `Polygon` or `MultiPolygon`.
+    MaskNotPolygon,
+    /// An outer ring of [`Options::mask`] is not convex, which clipping against the
+    /// half-planes of its edges requires.
+    NonConvexMask,
+    /// [`Options::tile_cache_capacity`] is `Some(0)`.
+    ZeroTileCacheCapacity,
+    /// `max_zoom` is outside `1..=24`.
+    MaxZoomOutOfRange { max_zoom: u8 },
+    /// `index_max_zoom` is above `max_zoom`.
+    IndexMaxZoomAboveMaxZoom { index_max_zoom: u8, max_zoom: u8 },
+    /// `min_zoom` is above `index_max_zoom`, leaving no zoom to build the index at.
+    MinZoomAboveIndexMaxZoom { min_zoom: u8, index_max_zoom: u8 },
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionsError::BufferTooLarge { buffer, extent } => write!(
+                f,
+                "buffer of {buffer} extent units must be less than half the extent of {extent}"
+            ),
+            OptionsError::ExtentTooLarge { extent } => {
+                write!(f, "extent of {extent} must be at most {MAX_EXTENT}")
+            }
+            OptionsError::ZeroTileSize => write!(f, "pixel tile size must not be 0"),
+            OptionsError::MaskNotPolygon => write!(f, "mask must be a Polygon or MultiPolygon"),
+            OptionsError::NonConvexMask => write!(f, "mask polygons must be convex"),
+            OptionsError::ZeroTileCacheCapacity => write!(f, "tile cache capacity must not be 0"),
+            OptionsError::MaxZoomOutOfRange { max_zoom } => {
+                write!(f, "max zoom of {max_zoom} must be between 1 and {MAX_ZOOM}")
+            }
+            OptionsError::IndexMaxZoomAboveMaxZoom {
+                index_max_zoom,
+                max_zoom,
+            } => write!(
+                f,
+                "index max zoom of {index_max_zoom} must be at most the max zoom of {max_zoom}"
+            ),
+            OptionsError::MinZoomAboveIndexMaxZoom {
+                min_zoom,
+                index_max_zoom,
+            } => write!(
+                f,
+                "min zoom of {min_zoom} must be at most the index max zoom of {index_max_zoom}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OptionsError {}
+
+/// Error building an index, see [`GeoJSONVT::try_new`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    InvalidOptions(OptionsError),
+    /// A feature was skipped at conversion with [`Options::strict`].
+    SkippedFeature(SkippedFeature),
+}
+
+impl From<OptionsError> for BuildError {
+    fn from(error: OptionsError) -> Self {
+        BuildError::InvalidOptions(error)
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::InvalidOptions(error) => error.fmt(f),
+            BuildError::SkippedFeature(skipped) => write!(f, "strict mode: {skipped}"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BuildError::InvalidOptions(error) => Some(error),
+            BuildError::SkippedFeature(_) => None,
+        }
+    }
+}
+
+/// Invalid tile request, see [`GeoJSONVT::try_tile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileError {
+    /// The zoom is above `Options::max_zoom`.
+    ZoomTooHigh { z: u8, max_zoom: u8 },
+    /// The row is outside the tile grid at the zoom of the request.
+    RowOutOfRange { z: u8, y: u32 },
+}
+
+impl fmt::Display for TileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TileError::ZoomTooHigh { z, max_zoom } => {
+                write!(f, "zoom {z} is higher than the max zoom of {max_zoom}")
+            }
+            TileError::RowOutOfRange { z, y } => {
+                write!(f, "row {y} is outside the tile grid at zoom {z}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TileError {}
+
+/// Failed use of the source features, see [`GeoJSONVT::query_bbox`],
+/// [`GeoJSONVT::query_radius`], [`GeoJSONVT::retile`] and [`crate::validate::check_coverage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryError {
+    /// The index was built without [`Options::retain_sources`], or its sources were dropped
+    /// with [`GeoJSONVT::drop_sources`].
+    NoSources,
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::NoSources => f.write_str("the index does not keep its source features"),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+/// Pixel size of the tiles requested from the index.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum TileSize {
+    #[default]
+    Px256,
+    /// Tiles are requested one zoom level higher than the content they carry,
+    /// as MapLibre does for sources with `tileSize: 512`: the tile at `z/x/y` is the quadrant
+    /// of the content at `z - 1` over twice the extent, see [`GeoJSONVT::tile`].
+    Px512,
+}
+impl TileSize {
+    /// Difference between the requested zoom and the zoom the content is generated at.
+    pub fn zoom_offset(&self) -> u8 {
+        match self {
+            TileSize::Px256 => 0,
+            TileSize::Px512 => 1,
+        }
+    }
+    /// Width of a requested tile on screen, in pixels.
+    pub fn pixels(&self) -> u32 {
+        match self {
+            TileSize::Px256 => 256,
+            TileSize::Px512 => 512,
+        }
+    }
+}
+
+/// Settings of a [`GeoJSONVT`] index.
+///
+/// `Options` is `Clone` but not `Copy`, as it owns values such as the [`Options::mask`]
+/// geometry: pass it by reference, or clone it to build another index.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
 pub struct Options {
+    /// Tiles below this zoom are only used to route features and render empty.
+    pub min_zoom: u8,
     pub max_zoom: u8,
     pub index_max_zoom: u8,
     pub index_max_points: u32,
+    /// Splits every tile of the initial index down to `index_max_zoom`, whatever
+    /// `index_max_points`, so the first requests at those zooms find their tile already built.
+    /// Otherwise the tiles where the split stops above `index_max_zoom`, holding at most
+    /// `index_max_points` points, only keep their source features until first requested.
+    pub eager: bool,
     pub tolerance: f64,
-    pub extent: u16,
-    pub buffer: u16,
+    /// Overrides `tolerance` for line geometries.
+    pub tolerance_lines: Option<f64>,
+    /// Overrides `tolerance` for polygon rings.
+    pub tolerance_polygons: Option<f64>,
+    pub tolerance_unit: ToleranceUnit,
+    /// Tolerance of the tiles at each zoom, indexed by zoom and in `tolerance_unit`, replacing
+    /// `tolerance` and its per-geometry overrides for the tiles, `max_zoom` included. The last
+    /// entry is repeated for the zooms past the end of the schedule. The initial simplification
+    /// still uses `tolerance`, so the tiles cannot keep points it dropped.
+    pub tolerance_schedule: Option<Vec<f64>>,
+    /// Significant digits kept in projected Mercator coordinates, `None` for full precision.
+    pub mercator_precision: Option<u8>,
+    /// Size of a tile in output coordinates, up to 2^30. Projected coordinates hold 53 bits
+    /// across the world, so the rounded tile coordinates stay exact while `extent * 2^z` is
+    /// below 2^53, as at every zoom with an extent of 2^20.
+    pub extent: u32,
+    pub buffer: u32,
+    /// Converted to extent units when building the index, whose [`GeoJSONVT::options`] then
+    /// report `buffer` in [`BufferUnit::ExtentUnits`].
+    pub buffer_unit: BufferUnit,
+    /// Clips the output geometries of each tile to `[0, extent]`, leaving out its buffer. The
+    /// buffered geometries are still used to generate the tiles below.
+    pub clip_output_to_extent: bool,
     pub line_metrics: bool,
+    /// JSON number type of the generated `mapbox_clip_start` and `mapbox_clip_end` values.
+    /// Always floats by default, [`NumberStyle::Mixed`] writes 0 and 1 as integers.
+    pub numeric_property_style: NumberStyle,
+    /// With `line_metrics`, also gives the clipped polygons `mapbox_clip_start` and
+    /// `mapbox_clip_end` along the perimeter of their outer ring, splitting multipolygons into
+    /// one feature per polygon. Off by default, as geojson-vt only measures lines.
+    pub ring_metrics: bool,
+    /// Shorthand for [`IdPolicy::GenerateAll`], taking precedence over `id_policy` when set.
     pub generate_id: bool,
+    pub id_policy: IdPolicy,
+    /// Splits `GeometryCollection`s into one feature per member geometry.
+    pub flatten_geometry_collections: bool,
+    pub tile_size: TileSize,
+    /// Hard-clips features to this `[min_lng, min_lat, max_lng, max_lat]` box before tiling.
+    pub clip_to_bbox: Option<[f64; 4]>,
+    /// Drops consecutive duplicate coordinates of lines and rings, see
+    /// [`ConvertWarning::DuplicateCoordinates`].
+    pub remove_duplicate_coordinates: bool,
+    /// Clips every feature to this `Polygon` or `MultiPolygon` before tiling. Points are kept if
+    /// they lie inside it.
+    ///
+    /// Each polygon of the mask must be convex, as checked by [`Options::validate`], and its
+    /// holes are ignored.
+    pub mask: Option<Geometry>,
+    /// Drops the features repeating an earlier one at conversion, see
+    /// [`ConvertWarning::DuplicateFeatures`].
+    pub dedupe_features: DedupePolicy,
+    /// Takes the `bbox` of input features, when valid, as their bounds instead of computing
+    /// them from every point. The boxes must contain their geometry.
+    pub respect_feature_bbox: bool,
+    /// Keeps the lines too short to show at `max_zoom`, otherwise dropped at conversion as
+    /// [`crate::SkipReason::BelowTolerance`], as a point at their center, e.g. to draw tiny
+    /// streams as dots.
+    pub collapse_small_lines_to_points: bool,
+    /// Keeps the polygons too small to show at `max_zoom`, otherwise dropped at conversion as
+    /// [`crate::SkipReason::BelowTolerance`], as a point at their center to place a label.
+    pub collapse_small_polygons_to_points: bool,
+    /// Fails building the index on the first feature skipped at conversion instead of only
+    /// listing it in [`GeoJSONVT::conversion_report`], except for features too small to show,
+    /// see [`crate::SkipReason::BelowTolerance`]. [`GeoJSONVT::try_new`] then returns
+    /// [`BuildError::SkippedFeature`] and `from_geozero` a `GeozeroError::Feature`, the other
+    /// constructors panic.
+    pub strict: bool,
+    /// Skips the features with a latitude outside `-90..=90` or a longitude outside
+    /// `±180 * lng_wrap_allowance`, listing them in [`GeoJSONVT::conversion_errors`] and as
+    /// [`crate::SkipReason::CoordinateOutOfRange`]. Otherwise latitudes are clamped to the
+    /// poles and any longitude is wrapped.
+    pub strict_coordinates: bool,
+    /// Number of half world widths either side of the prime meridian accepted with
+    /// `strict_coordinates`, 5 by default for longitudes up to ±900.
+    pub lng_wrap_allowance: u32,
+    /// Keeps a map from feature ids to their source features, see
+    /// [`GeoJSONVT::feature_by_id`].
+    pub index_feature_ids: bool,
+    /// Keeps the projected features the index was built from for the lifetime of the index,
+    /// as needed by [`GeoJSONVT::retile`], [`GeoJSONVT::query_bbox`],
+    /// [`GeoJSONVT::query_radius`] and [`crate::validate::check_coverage`]. Off by default, as
+    /// they hold the whole dataset on top of the tiles.
+    pub retain_sources: bool,
+    /// Stops converting the input after this many features, see
+    /// [`ConvertWarning::FeatureLimitReached`].
+    pub feature_limit: Option<usize>,
+    /// Keeps at most this many tiles generated by [`GeoJSONVT::tile`] beyond the initial index,
+    /// evicting the least recently used ones. Unbounded when `None`, and must not be
+    /// `Some(0)`.
+    pub tile_cache_capacity: Option<usize>,
+    /// Drops line and ring points that are collinear with their neighbours once rounded to the
+    /// tile extent, such as the chains of points clipping leaves along tile borders. Points
+    /// where the line turns back on itself are kept. Off by default, as geojson-vt keeps them.
+    pub remove_collinear: bool,
+    /// Drops the polygon rings left without area once rounded to the tile extent, such as the
+    /// slivers clipping leaves near tile corners, and the repeated points of the other rings.
+    /// Rings are also only kept if the area of their part within the tile is above the
+    /// tolerance, rather than the area of the whole source ring. Off by default, as geojson-vt
+    /// keeps such slivers.
+    pub remove_slivers: bool,
+    /// Outputs each point of a `MultiPoint` left in a tile as its own `Point` feature, so each
+    /// can be told apart when hit-testing. Their ids are the string id of the source feature
+    /// suffixed with `-{index}`, the position of the point within the source `MultiPoint`, so
+    /// a point keeps its id in every tile. Numeric ids are only suffixed when generated by
+    /// [`Options::generate_id`], and dropped otherwise.
+    pub explode_multipoints: bool,
+    /// Where the output coordinates of each tile are measured from. Clipping and the buffer are
+    /// unaffected, [`TileOrigin::Center`] only shifts the output by `extent / 2`.
+    pub origin: TileOrigin,
+    /// Direction of the y axis of the output coordinates, applied after `origin`, so with
+    /// [`YAxis::CartesianCoords`] y runs from `extent` at the top of the tile to 0 at the bottom.
+    pub y_axis: YAxis,
+    /// Which tile keeps a point lying exactly on the edge between two tiles. With the default
+    /// [`Boundary::Both`] and a `buffer` of 0 such points appear in both tiles, a half-open
+    /// boundary puts each point in exactly one.
+    pub point_boundary: Boundary,
+    /// Order of the features within each tile.
+    pub feature_order: FeatureOrder,
+    /// Adds the features to each tile in the order of their ids, see [`VtFeature::cmp_by_id`],
+    /// before applying `feature_order`.
+    pub sort_features: bool,
+    /// Called with the metrics of every tile generated, see [`GeoJSONVT::slowest_tiles`]. While
+    /// it is set, the output of every tile of the initial index is generated when indexing,
+    /// see [`Options::eager`], so that all of them are reported as the index is built.
+    #[cfg_attr(feature = "persist", serde(skip))]
+    pub on_tile_created: Option<OnTileCreated>,
+    /// Called with `z`, `x`, `y` and the properties of every feature output to a tile, after
+    /// `line_metrics` added its keys and before `feature_order` is applied. Properties it
+    /// returns replace those of the feature in that tile only, the source features keep theirs.
+    /// Features without properties are passed an empty object.
+    #[cfg_attr(feature = "persist", serde(skip))]
+    pub tile_property_hook: Option<TilePropertyHook>,
+    /// Groups the features of each tile into layers named by the value of this property,
+    /// `None` when missing, see [`Tile::layers`]. Each layer is ordered by `feature_order` on
+    /// its own. Layers are named before `tile_property_hook` runs, which can strip the property
+    /// from the output.
+    pub layer_by_property: Option<String>,
 }
 impl Default for Options {
     fn default() -> Self {
         Self {
+            min_zoom: 0,
             max_zoom: 18,
             index_max_zoom: 5,
             index_max_points: 100000,
+            eager: false,
             tolerance: 3.,
+            tolerance_lines: None,
+            tolerance_polygons: None,
+            tolerance_unit: ToleranceUnit::ExtentUnits,
+            tolerance_schedule: None,
+            mercator_precision: None,
             extent: 4096,
             buffer: 64,
+            buffer_unit: BufferUnit::ExtentUnits,
+            clip_output_to_extent: false,
             line_metrics: false,
+            numeric_property_style: NumberStyle::AlwaysFloat,
+            ring_metrics: false,
             generate_id: false,
+            id_policy: IdPolicy::Keep,
+            flatten_geometry_collections: false,
+            tile_size: TileSize::Px256,
+            clip_to_bbox: None,
+            remove_duplicate_coordinates: false,
+            mask: None,
+            dedupe_features: DedupePolicy::Off,
+            respect_feature_bbox: false,
+            collapse_small_lines_to_points: false,
+            collapse_small_polygons_to_points: false,
+            strict: false,
+            strict_coordinates: false,
+            lng_wrap_allowance: 5,
+            index_feature_ids: false,
+            retain_sources: false,
+            feature_limit: None,
+            tile_cache_capacity: None,
+            remove_collinear: false,
+            remove_slivers: false,
+            explode_multipoints: false,
+            origin: TileOrigin::TopLeft,
+            y_axis: YAxis::ScreenCoords,
+            point_boundary: Boundary::Both,
+            feature_order: FeatureOrder::InputOrder,
+            sort_features: false,
+            on_tile_created: None,
+            tile_property_hook: None,
+            layer_by_property: None,
         }
     }
 }
+impl Options {
+    /// Simplification tolerance applied to line geometries, in extent units.
+    pub fn line_tolerance(&self) -> f64 {
+        self.tolerance_lines.unwrap_or(self.tolerance) * self.tolerance_scale()
+    }
+    /// Simplification tolerance applied to polygon rings, in extent units.
+    pub fn polygon_tolerance(&self) -> f64 {
+        self.tolerance_polygons.unwrap_or(self.tolerance) * self.tolerance_scale()
+    }
+    /// Buffer around each tile, in extent units.
+    pub fn extent_buffer(&self) -> u32 {
+        match self.buffer_unit {
+            BufferUnit::ExtentUnits => self.buffer,
+            BufferUnit::Pixels { tile_size } => {
+                let buffer = self.buffer as f64 * self.extent as f64 / tile_size as f64;
+                buffer.round().min(u32::MAX as f64) as u32
+            }
+        }
+    }
+    /// Checks the options for combinations the index cannot be built with.
+    pub fn validate(&self) -> Result<(), OptionsError> {
+        if !(1..=MAX_ZOOM).contains(&self.max_zoom) {
+            return Err(OptionsError::MaxZoomOutOfRange {
+                max_zoom: self.max_zoom,
+            });
+        }
+        if self.index_max_zoom > self.max_zoom {
+            return Err(OptionsError::IndexMaxZoomAboveMaxZoom {
+                index_max_zoom: self.index_max_zoom,
+                max_zoom: self.max_zoom,
+            });
+        }
+        if self.min_zoom > self.index_max_zoom {
+            return Err(OptionsError::MinZoomAboveIndexMaxZoom {
+                min_zoom: self.min_zoom,
+                index_max_zoom: self.index_max_zoom,
+            });
+        }
+        if self.extent > MAX_EXTENT {
+            return Err(OptionsError::ExtentTooLarge {
+                extent: self.extent,
+            });
+        }
+        if matches!(self.buffer_unit, BufferUnit::Pixels { tile_size: 0 })
+            || matches!(self.tolerance_unit, ToleranceUnit::Pixels { tile_size: 0 })
+        {
+            return Err(OptionsError::ZeroTileSize);
+        }
+        let buffer = self.extent_buffer();
+        if buffer as u64 * 2 >= self.extent as u64 {
+            return Err(OptionsError::BufferTooLarge {
+                buffer,
+                extent: self.extent,
+            });
+        }
+        if self.tile_cache_capacity == Some(0) {
+            return Err(OptionsError::ZeroTileCacheCapacity);
+        }
+        if let Some(mask) = &self.mask {
+            Mask::new(mask)?;
+        }
+        Ok(())
+    }
+    /// Line and polygon tolerances of the tiles at zoom `z`, in the projected 0..1 space.
+    ///
+    /// With [`TileSize::Px512`] these are the tolerances of the content at `z - 1` over twice
+    /// the extent, see [`GeoJSONVT::tile`].
+    pub(crate) fn tile_tolerances(&self, z: u8) -> (f64, f64) {
+        let offset = self.tile_size.zoom_offset();
+        let z = self.content_zoom(z);
+        let scale = (1u32 << z) as f64 * (self.extent << offset) as f64;
+        let schedule = self.tolerance_schedule.as_deref().unwrap_or_default();
+        if let Some(&tolerance) = schedule.get(z as usize).or(schedule.last()) {
+            let tolerance = tolerance * self.tolerance_scale() / scale;
+            return (tolerance, tolerance);
+        }
+        if z == self.max_zoom {
+            return (0., 0.);
+        }
+        (
+            self.line_tolerance() / scale,
+            self.polygon_tolerance() / scale,
+        )
+    }
+    /// Tolerances used for the initial simplification, in the projected 0..1 space.
+    pub(crate) fn projected_tolerances(&self) -> (f64, f64) {
+        let scale = self.extent as f64 * (1u32 << self.tile_max_zoom() as u32) as f64;
+        (
+            self.line_tolerance() / scale,
+            self.polygon_tolerance() / scale,
+        )
+    }
+    /// Projects and simplifies `features` according to the projection-time settings, failing
+    /// on the first skipped feature in `strict` mode.
+    pub(crate) fn convert_features(
+        &self,
+        features: FeatureCollection,
+    ) -> Result<(Vec<VtFeature>, Vec<ConvertWarning>, ConversionReport), BuildError> {
+        let mut ids = IdGenerator::new(self.resolved_id_policy(), &features.features);
+        self.convert_features_with_ids(features, &mut ids)
+    }
+    /// Same as [`Options::convert_features`], assigning ids with `ids`.
+    pub(crate) fn convert_features_with_ids(
+        &self,
+        features: FeatureCollection,
+        ids: &mut IdGenerator,
+    ) -> Result<(Vec<VtFeature>, Vec<ConvertWarning>, ConversionReport), BuildError> {
+        let converted = convert_with_ids(features, &self.convert_options(), ids);
+        if self.strict
+            && let Some(skipped) = converted.2.first_invalid()
+        {
+            return Err(BuildError::SkippedFeature(skipped.clone()));
+        }
+        Ok(converted)
+    }
+    /// Projection-time settings of the options, see [`GeoJSONVT::retile`].
+    pub(crate) fn convert_options(&self) -> ConvertOptions {
+        let (line_tolerance, polygon_tolerance) = self.projected_tolerances();
+        ConvertOptions {
+            line_tolerance,
+            polygon_tolerance,
+            precision: self.mercator_precision,
+            id_policy: self.resolved_id_policy(),
+            flatten_collections: self.flatten_geometry_collections,
+            remove_duplicates: self.remove_duplicate_coordinates,
+            feature_limit: self.feature_limit,
+            dedupe: self.dedupe_features,
+            respect_feature_bbox: self.respect_feature_bbox,
+            skip_below_tolerance: true,
+            collapse_lines: self.collapse_small_lines_to_points,
+            collapse_polygons: self.collapse_small_polygons_to_points,
+            ring_metrics: self.ring_metrics,
+            max_lng: self
+                .strict_coordinates
+                .then_some(180. * self.lng_wrap_allowance as f64),
+        }
+    }
+    /// Tile generation settings of the options, shared by every tile of the index.
+    pub(crate) fn tile_options(&self) -> TileOptions {
+        TileOptions {
+            extent: self.extent,
+            tolerances: (0..=self.tile_max_zoom())
+                .map(|z| self.tile_tolerances(z))
+                .collect(),
+            line_metrics: self.line_metrics,
+            remove_collinear: self.remove_collinear,
+            remove_slivers: self.remove_slivers,
+            explode_multipoints: self.explode_multipoints,
+            suffix_numeric_ids: matches!(self.resolved_id_policy(), IdPolicy::GenerateAll),
+            origin: self.origin,
+            y_axis: self.y_axis,
+            feature_order: self.feature_order.clone(),
+            clip_to_extent: self.clip_output_to_extent,
+            sort_features: self.sort_features,
+            property_hook: self.tile_property_hook.clone(),
+            number_style: self.numeric_property_style,
+            layer_property: self.layer_by_property.clone(),
+        }
+    }
+    /// Highest zoom tiles can be requested at, one above `max_zoom` for [`TileSize::Px512`].
+    pub(crate) fn tile_max_zoom(&self) -> u8 {
+        self.max_zoom + self.tile_size.zoom_offset()
+    }
+    /// Zoom down to which the tiles requested at are built with the index, see
+    /// [`Options::index_max_zoom`].
+    pub(crate) fn tile_index_max_zoom(&self) -> u8 {
+        self.index_max_zoom + self.tile_size.zoom_offset()
+    }
+    /// Zoom the content of the tiles requested at `z` is generated at, see [`GeoJSONVT::tile`].
+    pub(crate) fn content_zoom(&self, z: u8) -> u8 {
+        z.saturating_sub(self.tile_size.zoom_offset())
+    }
+    /// Id policy applied during conversion, accounting for `generate_id`.
+    pub(crate) fn resolved_id_policy(&self) -> IdPolicy {
+        if self.generate_id {
+            IdPolicy::GenerateAll
+        } else {
+            self.id_policy.clone()
+        }
+    }
+    fn tolerance_scale(&self) -> f64 {
+        match self.tolerance_unit {
+            ToleranceUnit::ExtentUnits => 1.,
+            ToleranceUnit::Pixels { tile_size } => self.extent as f64 / tile_size as f64,
+        }
+    }
+}
+/// Callback of [`Options::on_tile_created`], shared by the clones of the options it is set on.
+#[derive(Clone)]
+pub struct OnTileCreated(Arc<Mutex<TileCallback>>);
+
+type TileCallback = dyn FnMut(&TileCoord, &TileMetrics) + Send;
+
+impl OnTileCreated {
+    pub fn new(f: impl FnMut(&TileCoord, &TileMetrics) + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(f)))
+    }
+}
+
+impl fmt::Debug for OnTileCreated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OnTileCreated")
+    }
+}
+
+/// Number of tiles kept by [`GeoJSONVT::slowest_tiles`].
+const SLOWEST_TILES: usize = 10;
+/// Largest [`Options::extent`], see [`OptionsError::ExtentTooLarge`].
+const MAX_EXTENT: u32 = 1 << 30;
+/// Largest [`Options::max_zoom`], see [`OptionsError::MaxZoomOutOfRange`].
+const MAX_ZOOM: u8 = 24;
+
 #[derive(Debug)]
 pub struct GeoJSONVT {
     options: Options,
-    tiles: HashMap<u64, InternalTile>,
-    tile_coords: Vec<TileCoord>,
-    total: u32,
-    stats: HashMap<u8, u32>,
+    pub(crate) tile_options: Arc<TileOptions>,
+    pub(crate) source: Vec<Arc<VtFeature>>,
+    /// Positions in `source` of the features by the id of the smallest tile containing their
+    /// bounds, down to `Options::index_max_zoom`. The ancestors of those tiles are present too,
+    /// so that queries walk down from the root.
+    pub(crate) source_tiles: HashMap<u64, Vec<usize>>,
+    pub(crate) tiles: HashMap<u64, InternalTile>,
+    pub(crate) tile_coords: Vec<TileCoord>,
+    pub(crate) total: u32,
+    pub(crate) stats: HashMap<u8, u32>,
+    /// Tiles of the index with at least one feature per zoom, counted as they are generated
+    /// and uncounted as they lose their output.
+    pub(crate) non_empty_stats: HashMap<u8, u32>,
+    /// Tiles held by `cache` per zoom, and those of them with at least one feature.
+    cache_stats: HashMap<u8, u32>,
+    cache_non_empty_stats: HashMap<u8, u32>,
+    pub(crate) read_only: bool,
+    pub(crate) warnings: Vec<ConvertWarning>,
+    pub(crate) conversion_report: ConversionReport,
+    /// Source features by [`id_key`], with [`Options::index_feature_ids`].
+    pub(crate) feature_ids: HashMap<String, Vec<Arc<VtFeature>>>,
+    pub(crate) cache: Option<LruCache<u64, InternalTile>>,
+    slowest: Vec<(TileCoord, TileMetrics)>,
+    /// Tiles whose output was dropped by [`GeoJSONVT::evict_tile`], rebuilt on next request.
+    pub(crate) evicted: HashSet<u64>,
+    /// Tiles ending the initial split whose output is not generated yet, built on first
+    /// request, see [`Options::eager`].
+    pub(crate) deferred: HashSet<u64>,
+    watchers: HashMap<u64, Vec<Sender<()>>>,
+    pub(crate) input_features: usize,
+    /// Size of the smallest line or polygon of the sources, see
+    /// [`GeoJSONVT::zoom_for_feature_size`].
+    pub(crate) min_feature_size: Option<f64>,
+    pub(crate) build_duration: Option<Duration>,
+    #[cfg(feature = "compress")]
+    pub(crate) encoded_payloads: usize,
+}
+
+/// Clones share the source features and the output of the tiles of the index, copying only the
+/// maps holding them, so that a snapshot can be taken cheaply while another one keeps serving.
+/// The index is `Send`, so a snapshot can be built on one thread and served from another.
+///
+/// Clones are independent once taken: tiles generated, evicted or dropped in one are not seen by
+/// the other. A tile changed by one clone, such as an ancestor handing its source features to
+/// its children, gets new output or source features in that clone only, without copying the
+/// shared ones. The tile cache bounded by [`Options::tile_cache_capacity`] starts empty, the
+/// receivers of [`GeoJSONVT::watch_tile`] are not signalled for the clone, and
+/// [`Options::on_tile_created`] is shared.
+impl Clone for GeoJSONVT {
+    fn clone(&self) -> Self {
+        Self {
+            options: self.options.clone(),
+            tile_options: self.tile_options.clone(),
+            source: self.source.clone(),
+            source_tiles: self.source_tiles.clone(),
+            tiles: self.tiles.clone(),
+            tile_coords: self.tile_coords.clone(),
+            total: self.total,
+            stats: self.stats.clone(),
+            non_empty_stats: self.non_empty_stats.clone(),
+            cache_stats: HashMap::new(),
+            cache_non_empty_stats: HashMap::new(),
+            read_only: self.read_only,
+            warnings: self.warnings.clone(),
+            conversion_report: self.conversion_report.clone(),
+            feature_ids: self.feature_ids.clone(),
+            cache: self.cache.as_ref().map(|cache| LruCache::new(cache.cap())),
+            slowest: self.slowest.clone(),
+            evicted: self.evicted.clone(),
+            deferred: self.deferred.clone(),
+            watchers: HashMap::new(),
+            input_features: self.input_features,
+            min_feature_size: self.min_feature_size,
+            build_duration: self.build_duration,
+            #[cfg(feature = "compress")]
+            encoded_payloads: self.encoded_payloads,
+        }
+    }
+}
+
+/// Summary of an index, see [`GeoJSONVT::index_stats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexStats {
+    /// Tiles generated into the index or held by the cache bounded by
+    /// `Options::tile_cache_capacity`, including those left without features, such as the
+    /// tiles below `Options::min_zoom` or whose features were all dropped by simplification.
+    pub total_tiles: u32,
+    pub tiles_per_zoom: HashMap<u8, u32>,
+    /// Tiles of the index or the cache with at least one feature, counted as they are
+    /// generated. Tiles whose output is deferred until first requested, see
+    /// [`Options::eager`], or was evicted, see [`GeoJSONVT::evict_tile`], are counted once
+    /// generated again.
+    pub non_empty_tiles: u32,
+    pub non_empty_tiles_per_zoom: HashMap<u8, u32>,
+    /// Number of features the index was built from, before wrapping across the antimeridian.
+    pub total_input_features: usize,
+    /// Points kept after simplification in the generated tiles.
+    pub total_simplified_points: u32,
+    /// Points clipped to the generated tiles before simplification.
+    pub total_original_points: u32,
+    /// Time taken to build the initial tiles, down to `Options::index_max_zoom`.
+    pub build_duration: Option<Duration>,
 }
 
 impl GeoJSONVT {
+    /// Returns a builder that accumulates features before building the index.
+    pub fn builder() -> GeoJSONVTBuilder {
+        GeoJSONVTBuilder::default()
+    }
+    /// Builds the index from any GeoJSON object, a geometry or a feature being indexed alone.
+    ///
+    /// # Panics
+    ///
+    /// When [`Options::validate`] fails, such as with a `buffer` of half the extent or more, or
+    /// when a feature is skipped with [`Options::strict`]. See [`GeoJSONVT::try_new`] to get an
+    /// error instead.
     pub fn from_geojson(geojson: &GeoJson, options: &Options) -> Self {
         let collection = geojson_to_feature_collection(geojson);
-        Self::new(collection, *options)
+        Self::new(collection, options.clone())
+    }
+    /// Parses `s` as GeoJSON and builds the index from it.
+    ///
+    /// # Panics
+    ///
+    /// When [`Options::validate`] fails, such as with a `buffer` of half the extent or more, or
+    /// when a feature is skipped with [`Options::strict`]. See [`GeoJSONVT::try_new`] to get an
+    /// error instead.
+    #[allow(clippy::result_large_err)]
+    pub fn from_geojson_str(s: &str, options: &Options) -> Result<Self, geojson::Error> {
+        Ok(Self::from_geojson(&s.parse()?, options))
     }
+    /// Parses `bytes` as UTF-8 GeoJSON and builds the index from it, without first copying them
+    /// into a string.
+    ///
+    /// # Panics
+    ///
+    /// When [`Options::validate`] fails, such as with a `buffer` of half the extent or more, or
+    /// when a feature is skipped with [`Options::strict`]. See [`GeoJSONVT::try_new`] to get an
+    /// error instead.
+    #[allow(clippy::result_large_err)]
+    pub fn from_geojson_bytes(bytes: &[u8], options: &Options) -> Result<Self, geojson::Error> {
+        Ok(Self::from_geojson(&GeoJson::from_reader(bytes)?, options))
+    }
+    /// Same as [`GeoJSONVT::new`], returning an error instead of panicking when
+    /// [`Options::validate`] fails or, with [`Options::strict`], a feature is skipped.
+    pub fn try_new(features: FeatureCollection, options: Options) -> Result<Self, BuildError> {
+        options.validate()?;
+        let (vt_features, warnings, report) = options.convert_features(features)?;
+        let source = vt_features.into_iter().map(Arc::new).collect();
+        let mut geojsonvt = Self::try_from_source(source, options)?;
+        geojsonvt.warnings = warnings;
+        geojsonvt.conversion_report = report;
+        Ok(geojsonvt)
+    }
+    /// Builds the index from `features`.
+    ///
+    /// # Panics
+    ///
+    /// When [`Options::validate`] fails, such as with a `buffer` of half the extent or more, or
+    /// when a feature is skipped with [`Options::strict`]. See [`GeoJSONVT::try_new`] to get an
+    /// error instead.
     pub fn new(features: FeatureCollection, options: Options) -> Self {
-        assert!(options.max_zoom > 0 && options.max_zoom <= 24);
+        Self::try_new(features, options).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Builds a single index from several inputs, each converted with its own options, e.g.
+    /// different simplification tolerances for a road and a boundary layer.
+    ///
+    /// The projected features of all inputs are then tiled together with `global_options`,
+    /// whose projection-time settings are ignored, except for ids: they are assigned across all
+    /// inputs with the id policy of `global_options`, so that generated ids are unique within
+    /// the index. Warnings and conversion reports are gathered in input order, the indices of
+    /// the report referring to the position within each input.
+    ///
+    /// The tolerance of an input only drops points at the `max_zoom` of its options. Tiles at
+    /// lower zooms are simplified with the tolerances of `global_options`, which act as a
+    /// floor: an input with a lower tolerance keeps more detail in the deepest tiles only.
+    ///
+    /// # Panics
+    ///
+    /// When [`Options::validate`] fails for `global_options`, or a feature of an input is
+    /// skipped with [`Options::strict`] set on its options. Validate the options first and
+    /// leave `strict` unset to avoid panicking, or see [`GeoJSONVT::try_new`] for one input.
+    pub fn from_multiple_geojson(
+        inputs: &[(&GeoJson, &Options)],
+        global_options: &Options,
+    ) -> Self {
+        let mut vt_features = Vec::new();
+        let mut warnings = Vec::new();
+        let mut report = ConversionReport::default();
+        let collections: Vec<_> = inputs
+            .iter()
+            .map(|(geojson, _)| geojson_to_feature_collection(geojson))
+            .collect();
+        let features = collections
+            .iter()
+            .flat_map(|collection| &collection.features);
+        let mut ids = IdGenerator::new(global_options.resolved_id_policy(), features);
+        for (collection, (_, options)) in collections.into_iter().zip(inputs) {
+            let (features, input_warnings, input_report) = options
+                .convert_features_with_ids(collection, &mut ids)
+                .unwrap_or_else(|error| panic!("{error}"));
+            vt_features.extend(features);
+            warnings.extend(input_warnings);
+            report.extend(input_report);
+        }
+        let mut geojsonvt = Self::from_vt_features(vt_features, global_options.clone());
+        geojsonvt.warnings = warnings;
+        geojsonvt.conversion_report = report;
+        geojsonvt
+    }
+    /// Builds the index from already projected features, see [`crate::convert::convert`].
+    ///
+    /// Coordinates are expected in Web Mercator space normalized to 0..1, with `z` holding the
+    /// simplification importance of each point as computed by [`crate::convert::convert`].
+    ///
+    /// # Panics
+    ///
+    /// When [`Options::validate`] fails, call it first to get an error instead.
+    pub fn from_vt_features(vt_features: Vec<VtFeature>, options: Options) -> Self {
+        Self::from_source(vt_features.into_iter().map(Arc::new).collect(), options)
+    }
+    /// Rebuilds the tile pyramid with `options`, reusing the already projected features.
+    ///
+    /// Points are re-simplified only when the options change the initial simplification
+    /// tolerance (e.g. `tolerance`, `extent` or `max_zoom`). Projection-time settings such as
+    /// `mercator_precision`, `id_policy`, `flatten_geometry_collections`,
+    /// `remove_duplicate_coordinates`, `respect_feature_bbox`, `collapse_small_lines_to_points`,
+    /// `collapse_small_polygons_to_points` and `feature_limit` keep the values the index was built
+    /// with, as do [`GeoJSONVT::warnings`].
+    ///
+    /// Fails with [`QueryError::NoSources`] if the index does not keep its sources, see
+    /// [`Options::retain_sources`].
+    ///
+    /// # Panics
+    ///
+    /// When [`Options::validate`] fails for `options`, call it first to get an error instead.
+    #[must_use = "`retile` returns a new index, leaving this one unchanged"]
+    pub fn retile(&self, options: Options) -> Result<GeoJSONVT, QueryError> {
+        if !self.has_sources() {
+            return Err(QueryError::NoSources);
+        }
+        let (line_tolerance, polygon_tolerance) = options.projected_tolerances();
+        let source = if (line_tolerance, polygon_tolerance) == self.options.projected_tolerances() {
+            self.source.clone()
+        } else {
+            self.source
+                .iter()
+                .map(|f| Arc::new(resimplify(f, line_tolerance, polygon_tolerance)))
+                .collect()
+        };
+        let mut geojsonvt = Self::from_source(source, options);
+        geojsonvt.warnings = self.warnings.clone();
+        geojsonvt.conversion_report = self.conversion_report.clone();
+        Ok(geojsonvt)
+    }
+    pub(crate) fn from_source(source: Vec<Arc<VtFeature>>, options: Options) -> Self {
+        Self::try_from_source(source, options).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Builds the index from projected `source` features, failing when [`Options::validate`]
+    /// does.
+    pub(crate) fn try_from_source(
+        source: Vec<Arc<VtFeature>>,
+        mut options: Options,
+    ) -> Result<Self, OptionsError> {
+        options.validate()?;
+        options.buffer = options.extent_buffer();
+        options.buffer_unit = BufferUnit::ExtentUnits;
         let buffer = options.buffer as f64 / options.extent as f64;
-        let tolerance =
-            (options.tolerance / options.extent as f64) / (1u32 << options.max_zoom as u32) as f64;
-        let vt_features = convert(features, tolerance, options.generate_id);
-        let vt_features = wrap(vt_features, buffer, options.line_metrics);
+        let mut clipped = None;
+        if let Some(bbox) = options.clip_to_bbox {
+            let (line_metrics, clipped_area) = (options.line_metrics, options.remove_slivers);
+            clipped = Some(clip_to_bbox(&source, bbox, line_metrics, clipped_area));
+        }
+        if let Some(mask) = &options.mask {
+            let features = clipped.as_deref().unwrap_or(&source);
+            let mask = Mask::new(mask)?;
+            clipped = Some(mask.clip(features, options.line_metrics));
+        }
+        let vt_features = wrap(
+            clipped.as_deref().unwrap_or(&source),
+            buffer,
+            options.line_metrics,
+            options.remove_slivers,
+            options.point_boundary,
+        );
 
+        let cache = options
+            .tile_cache_capacity
+            .and_then(NonZeroUsize::new)
+            .map(LruCache::new);
+        let input_features = source.len();
+        let min_feature_size = source
+            .iter()
+            .filter_map(|feature| geometry_size(&feature.geometry))
+            .reduce(f64::min);
+        let tile_options = Arc::new(options.tile_options());
         let mut geojsonvt: Self = Self {
             options,
+            tile_options,
+            source,
+            source_tiles: HashMap::new(),
             tiles: HashMap::new(),
             tile_coords: Vec::new(),
             total: 0,
             stats: HashMap::new(),
+            non_empty_stats: HashMap::new(),
+            cache_stats: HashMap::new(),
+            cache_non_empty_stats: HashMap::new(),
+            read_only: false,
+            warnings: Vec::new(),
+            conversion_report: ConversionReport::default(),
+            feature_ids: HashMap::new(),
+            cache,
+            slowest: Vec::new(),
+            evicted: HashSet::new(),
+            deferred: HashSet::new(),
+            watchers: HashMap::new(),
+            input_features,
+            min_feature_size,
+            build_duration: None,
+            #[cfg(feature = "compress")]
+            encoded_payloads: 0,
         };
-        geojsonvt.split_tile(&vt_features, 0, 0, 0, 0, 0, 0);
-        geojsonvt
+        let start = Instant::now();
+        geojsonvt.split_tile(&vt_features, 0, 0, 0, 0, 0, 0, Duration::ZERO);
+        geojsonvt.build_duration = Some(start.elapsed());
+        if geojsonvt.options.index_feature_ids {
+            for feature in &geojsonvt.source {
+                if let Some(id) = &feature.id {
+                    let features = geojsonvt.feature_ids.entry(id_key(id)).or_default();
+                    features.push(feature.clone());
+                }
+            }
+        }
+        if geojsonvt.options.retain_sources {
+            geojsonvt.index_sources();
+        } else {
+            geojsonvt.source = Vec::new();
+        }
+        Ok(geojsonvt)
     }
+    /// Returns the tile at `z/x/y`.
+    ///
+    /// With [`TileSize::Px512`] the content of a tile requested at `z` is generated at `z - 1`
+    /// over twice the extent, and the tile is the quadrant of the content tile
+    /// `z - 1/(x >> 1)/(y >> 1)` it covers, at the extent: its output is the same as that of
+    /// the quadrant of a 256px tile at `z - 1` with twice the extent, shifted to the origin.
+    /// Tiles can then be requested up to `Options::max_zoom + 1`, and the tile at zoom 0 holds
+    /// the whole world, simplified as the content of zoom 0.
+    ///
+    /// `x` wraps around the world, so `x + 2^z` returns the same tile as `x`. Without content
+    /// at `z/x/y` the shared empty tile is returned, see [`Tile::is_placeholder`], as it is for
+    /// rows outside `0..2^z`.
+    ///
+    /// # Panics
+    ///
+    /// Above `Options::max_zoom`, see [`GeoJSONVT::try_tile`] to get an error instead.
     pub fn tile(&mut self, z: u8, x: u32, y: u32) -> &Tile {
-        if z > self.options.max_zoom {
+        if z > self.options.tile_max_zoom() {
             panic!("Requested zoom higher than maxZoom: {}", z);
         }
-        let z2 = 1u32 << z;
-        let x = ((x % z2) + z2) % z2;
+        if self.options.content_zoom(z) < self.options.min_zoom || y >= 1u32 << z {
+            return &EMPTY_TILE;
+        }
+        let x = x % (1u32 << z);
         let id = to_id(z, x, y);
+        self.generate_pending(id);
         if self.tiles.contains_key(&id) {
             return &self.tiles[&id].tile;
         }
+        if self.cache.is_some() {
+            return self.cached_tile(z, x, y);
+        }
+        if self.read_only {
+            return &EMPTY_TILE;
+        }
         let parent = self.find_parent(z, x, y).unwrap();
-        self.split_tile(
-            &parent.source_feature.clone(),
-            parent.z,
-            parent.x,
-            parent.y,
-            z,
-            x,
-            y,
-        );
+        let (source, pz, px, py) = (parent.source_feature.clone(), parent.z, parent.x, parent.y);
+        self.split_tile(&source, pz, px, py, z, x, y, Duration::ZERO);
         if self.tiles.contains_key(&id) {
             return &self.tiles[&id].tile;
         }
         &EMPTY_TILE
     }
+    /// Returns the tile at `z/x/y` like [`GeoJSONVT::tile`], with `x` wrapped around the world
+    /// in both directions, so `-1` is the last column and `2^z` the first.
+    ///
+    /// Fails for zooms above `Options::max_zoom` and rows outside `0..2^z`, instead of
+    /// panicking or returning an empty tile.
+    pub fn try_tile(&mut self, z: u8, x: i64, y: u32) -> Result<&Tile, TileError> {
+        let max_zoom = self.options.tile_max_zoom();
+        if z > max_zoom {
+            return Err(TileError::ZoomTooHigh { z, max_zoom });
+        }
+        let z2 = 1i64 << z;
+        if y as i64 >= z2 {
+            return Err(TileError::RowOutOfRange { z, y });
+        }
+        Ok(self.tile(z, x.rem_euclid(z2) as u32, y))
+    }
+    /// Returns a copy of the tile at `z/x/y` if it is already generated, without generating
+    /// it, see [`GeoJSONVT::tile`].
+    ///
+    /// Takes `&self` so the index can be shared for reading. `None` when the tile has not been
+    /// generated yet or was evicted, and for zooms above `max_zoom`. Tiles of the cache bounded
+    /// by [`Options::tile_cache_capacity`] are returned without updating their recency.
+    #[must_use = "`get_tile` returns a copy of the tile without side effects"]
+    pub fn get_tile(&self, z: u8, x: u32, y: u32) -> Option<Tile> {
+        if z > self.options.tile_max_zoom() {
+            return None;
+        }
+        if self.options.content_zoom(z) < self.options.min_zoom {
+            return Some(EMPTY_TILE.clone());
+        }
+        let id = self.content_id(z, x, y);
+        if self.evicted.contains(&id) || self.deferred.contains(&id) {
+            return None;
+        }
+        match self.tiles.get(&id) {
+            Some(tile) => Some(Tile::clone(&tile.tile)),
+            None => self
+                .cache
+                .as_ref()?
+                .peek(&id)
+                .map(|tile| Tile::clone(&tile.tile)),
+        }
+    }
+
+    /// Returns the tile at `z/x/y` like [`GeoJSONVT::tile`], or why it has no content.
+    pub fn tile_status(&mut self, z: u8, x: u32, y: u32) -> TileStatus<'_> {
+        if z > self.options.tile_max_zoom() || y >= 1u32 << z {
+            return TileStatus::OutOfRange;
+        }
+        if self.options.content_zoom(z) < self.options.min_zoom {
+            return TileStatus::Empty {
+                reason: EmptyReason::BelowMinZoom,
+            };
+        }
+        let id = self.content_id(z, x, y);
+        if self.read_only && !self.tiles.contains_key(&id) {
+            // The nearest indexed ancestor bounds every feature the tile could have had.
+            let x = x % (1u32 << z);
+            let buffer =
+                self.options.buffer as f64 / self.options.extent as f64 / (1u32 << z) as f64;
+            let tile = tile_bbox(z, x, y);
+            let bbox = BBox {
+                min_x: tile.min_x - buffer,
+                min_y: tile.min_y - buffer,
+                max_x: tile.max_x + buffer,
+                max_y: tile.max_y + buffer,
+            };
+            let reason = match self.find_parent(z, x, y) {
+                Some(parent) if !parent.bbox.intersects(&bbox) => EmptyReason::OutsideData,
+                _ => EmptyReason::NoSourceFeatures,
+            };
+            return TileStatus::Empty { reason };
+        }
+        self.tile(z, x, y);
+        let tile = match self.tiles.get(&id) {
+            Some(tile) => Some(tile),
+            None => self.cache.as_ref().and_then(|cache| cache.peek(&id)),
+        };
+        match tile {
+            Some(tile) if tile.feature_count() > 0 => TileStatus::Generated(&tile.tile),
+            // Tiles no feature reaches are not generated.
+            _ => TileStatus::Empty {
+                reason: tile
+                    .and_then(|tile| tile.empty_reason)
+                    .unwrap_or(EmptyReason::OutsideData),
+            },
+        }
+    }
+    /// Returns the tile at `z/x/y` packed into a [`CompactTile`], see [`GeoJSONVT::tile`].
+    pub fn compact_tile(&mut self, z: u8, x: u32, y: u32) -> CompactTile {
+        self.tile(z, x, y).to_compact()
+    }
+
+    /// Generates every non-empty tile down to `max_zoom`, clamped to the highest zoom tiles can
+    /// be requested at, see [`GeoJSONVT::tile`].
+    pub fn generate_all(&mut self, max_zoom: u8) {
+        let max_zoom = max_zoom.min(self.options.tile_max_zoom());
+        for z in 0..max_zoom {
+            let parents = self
+                .tiles
+                .values()
+                .filter(|tile| tile.z == z && !tile.source_feature.is_empty())
+                .map(|tile| (tile.x, tile.y))
+                .collect::<Vec<_>>();
+            for (x, y) in parents {
+                let source = self.tiles[&to_id(z, x, y)].source_feature.clone();
+                self.split_tile(&source, z, x, y, z + 1, x * 2, y * 2, Duration::ZERO);
+            }
+        }
+    }
+    /// Returns the WGS-84 outline of every cached tile at `zoom` as a polygon, with the tile's
+    /// `z`, `x`, `y`, `point_count` and `simplified_count` as properties, for debugging the
+    /// tile grid in GIS tools.
+    #[must_use = "`tile_grid_as_geojson` returns a new feature collection"]
+    pub fn tile_grid_as_geojson(&self, zoom: u8) -> FeatureCollection {
+        let features = self
+            .tiles
+            .values()
+            .filter(|tile| {
+                tile.z == zoom && self.options.content_zoom(zoom) >= self.options.min_zoom
+            })
+            .filter_map(|tile| {
+                let (west, south, east, north) = Self::tile_extent_wgs84(tile.z, tile.x, tile.y)?;
+                let ring = vec![
+                    vec![west, south],
+                    vec![east, south],
+                    vec![east, north],
+                    vec![west, north],
+                    vec![west, south],
+                ];
+                let mut properties = JsonObject::new();
+                properties.insert("z".to_string(), tile.z.into());
+                properties.insert("x".to_string(), tile.x.into());
+                properties.insert("y".to_string(), tile.y.into());
+                properties.insert("point_count".to_string(), tile.tile.point_count.into());
+                properties.insert(
+                    "simplified_count".to_string(),
+                    tile.tile.simplified_count.into(),
+                );
+                Some(Feature {
+                    bbox: None,
+                    geometry: Some(Geometry::new(Value::Polygon(vec![ring]))),
+                    id: None,
+                    properties: Some(properties),
+                    foreign_members: None,
+                })
+            });
+        FeatureCollection::from_iter(features)
+    }
+    /// Returns the `(west, south, east, north)` bounds of tile `z/x/y` in WGS-84 degrees.
+    ///
+    /// Returns `None` unless `z` is at most 32 and `x` and `y` are below `2^z`.
+    pub fn tile_extent_wgs84(z: u8, x: u32, y: u32) -> Option<(f64, f64, f64, f64)> {
+        if z > 32 || u64::from(x.max(y)) >= 1u64 << z {
+            return None;
+        }
+        let z2 = (1u64 << z) as f64;
+        let (x, y) = (x as f64, y as f64);
+        Some((
+            mercator_x_to_lng(x / z2),
+            mercator_y_to_lat((y + 1.) / z2),
+            mercator_x_to_lng((x + 1.) / z2),
+            mercator_y_to_lat(y / z2),
+        ))
+    }
+    /// Returns the source features intersecting the `[min_lng, min_lat, max_lng, max_lat]` box,
+    /// each once and with its whole geometry, in WGS-84 coordinates, in input order.
+    ///
+    /// Features are pruned by the tiles of the box, then by their bounds, then clipped to the
+    /// box to test their geometry. A box with `min_lng > max_lng` crosses the antimeridian.
+    ///
+    /// Fails with [`QueryError::NoSources`] if the index does not keep its sources, see
+    /// [`Options::retain_sources`].
+    pub fn query_bbox(&self, bbox: [f64; 4]) -> Result<Vec<Feature>, QueryError> {
+        if !self.has_sources() {
+            return Err(QueryError::NoSources);
+        }
+        let [min_lng, min_lat, max_lng, max_lat] = bbox;
+        let ranges = if min_lng > max_lng {
+            vec![(min_lng, 180.), (-180., max_lng)]
+        } else {
+            vec![(min_lng, max_lng)]
+        };
+        let (min_y, max_y) = (lat_to_mercator_y(max_lat), lat_to_mercator_y(min_lat));
+        let areas: Vec<BBox> = ranges
+            .into_iter()
+            .map(|(west, east)| BBox {
+                min_x: lng_to_mercator_x(west),
+                min_y,
+                max_x: lng_to_mercator_x(east),
+                max_y,
+            })
+            .collect();
+        let max_zoom = self.options.index_max_zoom;
+        let candidates: BTreeSet<usize> = areas
+            .iter()
+            .flat_map(|a| self.sources_in(a, max_zoom))
+            .collect();
+        // Source features crossing the antimeridian reach beyond 0..1, as do their copies.
+        let boxes: Vec<BBox> = areas
+            .iter()
+            .flat_map(|area| {
+                [-1., 0., 1.].map(|shift| BBox {
+                    min_x: area.min_x + shift,
+                    max_x: area.max_x + shift,
+                    ..*area
+                })
+            })
+            .collect();
+        let features = candidates
+            .into_iter()
+            .map(|i| &self.source[i])
+            .filter(|feature| {
+                let Some(bounds) = &feature.bbox else {
+                    return false;
+                };
+                boxes.iter().filter(|b| b.intersects(bounds)).any(|b| {
+                    let features = std::slice::from_ref(*feature);
+                    let both = Boundary::Both;
+                    let (x0, x1, y0, y1) = (b.min_x, b.max_x, b.min_y, b.max_y);
+                    let (min_all, max_all) = (f64::NEG_INFINITY, f64::INFINITY);
+                    let clipped = clip::<0>(features, x0, x1, min_all, max_all, false, false, both);
+                    !clip::<1>(&clipped, y0, y1, min_all, max_all, false, false, both).is_empty()
+                })
+            })
+            .map(|feature| lnglat_feature(feature))
+            .collect();
+        Ok(features)
+    }
+    /// Returns the source features within `radius_meters` of the WGS-84 position `(lng, lat)`,
+    /// each once and with its whole geometry, in WGS-84 coordinates, in input order.
+    ///
+    /// The radius is in EPSG:3857 meters, which match ground meters at the equator only.
+    /// Features are pruned by the tiles of the box around the circle down to `zoom`, the zoom of
+    /// the map the position was picked on, then by their bounds, and their geometry is tested as
+    /// in [`crate::Tile::query_point`]. Indexed tiles stop at [`Options::index_max_zoom`], which
+    /// bounds `zoom`, and a lower `zoom` prunes less but finds the same features.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`QueryError::NoSources`] if the index does not keep its sources: it must be
+    /// built with [`Options::retain_sources`] and not have had [`GeoJSONVT::drop_sources`]
+    /// called.
+    pub fn query_radius(
+        &self,
+        lng: f64,
+        lat: f64,
+        radius_meters: f64,
+        zoom: u8,
+    ) -> Result<Vec<Feature>, QueryError> {
+        if !self.has_sources() {
+            return Err(QueryError::NoSources);
+        }
+        let (x, y) = (lng_to_mercator_x(lng), lat_to_mercator_y(lat));
+        let radius = radius_meters / WEB_MERCATOR_CIRCUMFERENCE;
+        // Source features crossing the antimeridian reach beyond 0..1, as do their copies, and
+        // so may the circle.
+        let circles = [-1., 0., 1.].map(|shift| {
+            let bounds = BBox {
+                min_x: x + shift - radius,
+                min_y: y - radius,
+                max_x: x + shift + radius,
+                max_y: y + radius,
+            };
+            (x + shift, bounds)
+        });
+        let candidates: BTreeSet<usize> = circles
+            .iter()
+            .flat_map(|(_, bounds)| self.sources_in(bounds, zoom))
+            .collect();
+        let features = candidates
+            .into_iter()
+            .map(|i| &self.source[i])
+            .filter(|feature| {
+                let Some(bounds) = &feature.bbox else {
+                    return false;
+                };
+                let mut circles = circles.iter().filter(|(_, c)| c.intersects(bounds));
+                circles.any(|&(cx, _)| feature.geometry.is_within_distance(cx, y, radius))
+            })
+            .map(|feature| lnglat_feature(feature))
+            .collect();
+        Ok(features)
+    }
+    /// Returns the source feature with `id`, with all its properties and its whole geometry in
+    /// WGS-84 coordinates. The first one is returned if several share the id, see
+    /// [`GeoJSONVT::features_by_id`].
+    ///
+    /// Requires [`Options::index_feature_ids`], `None` is returned otherwise. The map of ids
+    /// holds on to the source features after [`GeoJSONVT::drop_sources`].
+    #[must_use = "`feature_by_id` returns the feature"]
+    pub fn feature_by_id(&self, id: &Id) -> Option<Feature> {
+        let features = self.feature_ids.get(&id_key(id))?;
+        features.first().map(|feature| lnglat_feature(feature))
+    }
+    /// Same as [`GeoJSONVT::feature_by_id`], returning every source feature with `id` in input
+    /// order, such as duplicated ids or the members of a flattened geometry collection.
+    #[must_use = "`features_by_id` returns the features"]
+    pub fn features_by_id(&self, id: &Id) -> Vec<Feature> {
+        let features = self.feature_ids.get(&id_key(id));
+        features
+            .into_iter()
+            .flatten()
+            .map(|feature| lnglat_feature(feature))
+            .collect()
+    }
+    /// Returns the features of the tile at zoom `z` under the WGS-84 position `(lng, lat)`,
+    /// within `radius_px` screen pixels, see [`Tile::query_point`].
+    pub fn query_lnglat(&mut self, lng: f64, lat: f64, z: u8, radius_px: f64) -> Vec<&Feature> {
+        let z2 = (1u32 << z) as f64;
+        let (x, y) = (lng_to_mercator_x(lng) * z2, lat_to_mercator_y(lat) * z2);
+        let (tx, ty) = (x.floor().clamp(0., z2 - 1.), y.floor().clamp(0., z2 - 1.));
+        let extent = self.options.extent as f64;
+        let radius = radius_px * extent / self.options.tile_size.pixels() as f64;
+        let tile = self.tile(z, tx as u32, ty as u32);
+        match tile.lnglat_to_pixel(lng, lat) {
+            Some((px, py)) => tile.query_point(px, py, radius),
+            None => Vec::new(),
+        }
+    }
+    /// Whether a single polygon of the tile at `z/x/y` covers it entirely, so that it can be
+    /// drawn as a solid fill, see [`GeoJSONVT::tile`].
+    ///
+    /// The four corners of the tile must lie inside the polygon, edges included, and outside its
+    /// holes, and none of its points may lie strictly within the tile. The check runs on the
+    /// simplified geometries of the tile.
+    pub fn tile_is_fully_covered(&mut self, z: u8, x: u32, y: u32) -> bool {
+        let min = -self.options.origin.offset(self.options.extent);
+        let max = min + self.options.extent as f64;
+        let corners = [(min, min), (max, min), (min, max), (max, max)];
+        let covers = |rings: &[Vec<Position>]| {
+            let rings: Vec<_> = rings
+                .iter()
+                .map(|ring| {
+                    VtLinearRing::new(ring.iter().map(|p| VtPoint::from_xy(p[0], p[1])).collect())
+                })
+                .collect();
+            let within = |p: &VtPoint| p.x > min && p.x < max && p.y > min && p.y < max;
+            let (exterior, holes) = match rings.split_first() {
+                Some(split) => split,
+                None => return false,
+            };
+            corners.iter().all(|&(cx, cy)| {
+                exterior.contains_point(cx, cy)
+                    && !holes.iter().any(|hole| hole.contains_point(cx, cy))
+            }) && !rings.iter().flat_map(|ring| &ring.elements).any(within)
+        };
+        let tile = self.tile(z, x, y);
+        tile.features().any(|feature| {
+            match feature.geometry.as_ref().map(|geometry| &geometry.value) {
+                Some(Value::Polygon(rings)) => covers(rings),
+                Some(Value::MultiPolygon(polygons)) => polygons.iter().any(|rings| covers(rings)),
+                _ => false,
+            }
+        })
+    }
+    /// Returns the projected source features of the tile at `z/x/y`, generating it if needed,
+    /// see [`GeoJSONVT::tile`].
+    ///
+    /// The features are clipped to the tile and its buffer, in the projected 0..1 space, with
+    /// the simplification importance computed for `max_zoom`. Tiles of the index already split
+    /// into their children hold no source features and yield nothing, as do tiles without
+    /// content, see [`InternalTile::source_features`].
+    pub fn features_in_tile(
+        &mut self,
+        z: u8,
+        x: u32,
+        y: u32,
+    ) -> impl Iterator<Item = &Arc<VtFeature>> {
+        self.tile(z, x, y);
+        let id = self.content_id(z, x, y);
+        let tile = match self.tiles.get(&id) {
+            Some(tile) => Some(tile),
+            None => self.cache.as_ref().and_then(|cache| cache.peek(&id)),
+        };
+        tile.map_or(&[][..], |tile| &tile.source_feature).iter()
+    }
+    /// Returns the coordinates of tile `z/x/y` and of its ancestors, from the root down to it.
+    ///
+    /// This is the path walked up when generating a tile on demand: its features are clipped
+    /// from the closest ancestor of the chain that is in the index.
+    pub fn tile_ancestry(z: u8, x: u32, y: u32) -> Vec<TileCoord> {
+        (0..=z)
+            .map(|z0| {
+                let shift = z - z0;
+                TileCoord::new(x >> shift, y >> shift, z0)
+            })
+            .collect()
+    }
+    /// Lazily yields every non-empty tile at zoom `z`.
+    ///
+    /// Tiles already in the cache are cloned; the others are clipped depth-first from the
+    /// indexed leaves without being added to the cache, so only one branch of intermediate
+    /// features is held in memory at a time.
+    pub fn tiles_at_zoom(&self, z: u8) -> TilesAtZoom<'_> {
+        if z > self.options.tile_max_zoom() {
+            panic!("Requested zoom higher than maxZoom: {}", z);
+        }
+        let mut cached = Vec::new();
+        let mut stack = Vec::new();
+        if self.options.content_zoom(z) >= self.options.min_zoom {
+            for (id, tile) in &self.tiles {
+                let pending = self.evicted.contains(id) || self.deferred.contains(id);
+                if tile.z == z && pending {
+                    stack.push((tile.z, tile.x, tile.y, tile.source_feature.to_vec()));
+                } else if tile.z == z {
+                    cached.push(tile);
+                } else if tile.z < z && !tile.source_feature.is_empty() {
+                    stack.push((tile.z, tile.x, tile.y, tile.source_feature.to_vec()));
+                }
+            }
+        }
+        TilesAtZoom {
+            options: &self.options,
+            tile_options: &self.tile_options,
+            z,
+            cached,
+            stack,
+        }
+    }
+    /// Releases the features kept for generating tiles on demand.
+    ///
+    /// Already generated tiles keep being served; any other tile is returned empty from then on.
+    pub fn drop_sources(&mut self) {
+        let pending: HashSet<u64> = self.evicted.union(&self.deferred).copied().collect();
+        for id in pending {
+            self.generate_pending(id);
+        }
+        for tile in self.tiles.values_mut() {
+            tile.source_feature = Arc::default();
+        }
+        self.source = Vec::new();
+        self.source_tiles = HashMap::new();
+        self.read_only = true;
+    }
+    /// Drops the generated content of the tile at `z/x/y` so it is rebuilt on next request, and
+    /// signals the receivers of [`GeoJSONVT::watch_tile`] for it.
+    ///
+    /// Only tiles that can be rebuilt are evicted: those held by the cache bounded by
+    /// [`Options::tile_cache_capacity`] and the indexed tiles that still keep their source
+    /// features. Returns whether the tile was evicted.
+    pub fn evict_tile(&mut self, z: u8, x: u32, y: u32) -> bool {
+        let id = self.content_id(z, x, y);
+        let evicted = if let Some(cache) = &mut self.cache
+            && let Some(tile) = cache.pop(&id)
+        {
+            self.uncount_cached(&tile);
+            true
+        } else if let Some(tile) = self.tiles.get_mut(&id)
+            && !tile.source_feature.is_empty()
+        {
+            count_tile(&mut self.non_empty_stats, tile, -1);
+            tile.tile = Arc::new(tile.tile.without_features());
+            #[cfg(feature = "compress")]
+            tile.encoded.clear();
+            self.evicted.insert(id);
+            true
+        } else {
+            false
+        };
+        if evicted {
+            self.notify_watchers(id);
+        }
+        evicted
+    }
+    /// Returns a receiver signalled each time the content of the tile at `z/x/y` is invalidated,
+    /// such as by [`GeoJSONVT::evict_tile`].
+    ///
+    /// Dropped receivers are forgotten on the next signal.
+    pub fn watch_tile(&mut self, z: u8, x: u32, y: u32) -> Receiver<()> {
+        let (sender, receiver) = mpsc::channel();
+        let id = self.content_id(z, x, y);
+        self.watchers.entry(id).or_default().push(sender);
+        receiver
+    }
+    fn notify_watchers(&mut self, id: u64) {
+        if let Entry::Occupied(mut entry) = self.watchers.entry(id) {
+            entry.get_mut().retain(|sender| sender.send(()).is_ok());
+            if entry.get().is_empty() {
+                entry.remove();
+            }
+        }
+    }
+    /// Id of the tile holding the content requested at `z/x/y`, see [`GeoJSONVT::tile`].
+    pub(crate) fn content_id(&self, z: u8, x: u32, y: u32) -> u64 {
+        to_id(z, x % (1u32 << z), y)
+    }
+    /// Whether [`GeoJSONVT::drop_sources`] was called on this index.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+    /// Whether the index still keeps the features it was built from, see
+    /// [`Options::retain_sources`].
+    pub fn has_sources(&self) -> bool {
+        self.options.retain_sources && !self.read_only
+    }
 
+    /// Files the source features under the smallest tile containing their bounds, see
+    /// [`GeoJSONVT::sources_in`].
+    pub(crate) fn index_sources(&mut self) {
+        let max_zoom = self.options.index_max_zoom;
+        let mut source_tiles: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (i, feature) in self.source.iter().enumerate() {
+            let Some(bbox) = &feature.bbox else {
+                continue;
+            };
+            let (z, x, y) = containing_tile(bbox, max_zoom);
+            source_tiles.entry(to_id(z, x, y)).or_default().push(i);
+            for z0 in 0..z {
+                let shift = z - z0;
+                source_tiles
+                    .entry(to_id(z0, x >> shift, y >> shift))
+                    .or_default();
+            }
+        }
+        self.source_tiles = source_tiles;
+    }
+    /// Positions in `source` of the features filed under the tiles intersecting `area`, in the
+    /// projected 0..1 space. Features reaching beyond 0..1 are filed under the root, which is
+    /// always searched. Tiles below `max_zoom` are searched whatever their bounds.
+    fn sources_in(&self, area: &BBox, max_zoom: u8) -> Vec<usize> {
+        let mut found = Vec::new();
+        let mut stack = vec![(0u8, 0u32, 0u32)];
+        while let Some((z, x, y)) = stack.pop() {
+            let Some(features) = self.source_tiles.get(&to_id(z, x, y)) else {
+                continue;
+            };
+            if z > 0 && z <= max_zoom && !tile_bbox(z, x, y).intersects(area) {
+                continue;
+            }
+            found.extend(features);
+            if z < self.options.index_max_zoom {
+                let (x, y) = (x * 2, y * 2);
+                stack.extend(
+                    [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)].map(|(x, y)| (z + 1, x, y)),
+                );
+            }
+        }
+        found
+    }
+    /// Returns a tile outside of the index from the LRU cache, clipping it from its nearest
+    /// indexed ancestor on a miss. The index itself is left untouched, so evicted tiles can
+    /// always be generated again.
+    fn cached_tile(&mut self, z: u8, x: u32, y: u32) -> &Tile {
+        let id = to_id(z, x, y);
+        if !self.cache.as_ref().unwrap().contains(&id) {
+            let Some(parent) = self.find_parent(z, x, y) else {
+                return &EMPTY_TILE;
+            };
+            let start = self.start_timer();
+            let features = clip_to_tile(
+                &parent.source_feature,
+                (parent.z, parent.x, parent.y),
+                (z, x, y),
+                &self.options,
+            );
+            let clip_time = elapsed(start);
+            if features.is_empty() {
+                return &EMPTY_TILE;
+            }
+            let start = self.start_timer();
+            let mut tile = InternalTile::new(&features, z, x, y, &self.tile_options);
+            if let Some(start) = start {
+                let metrics = tile_metrics(&tile, features.len(), clip_time, start.elapsed());
+                self.record_tile_metrics(TileCoord::new(x, y, z), metrics);
+            }
+            // Kept for `features_in_tile`, the tile is never split.
+            tile.source_feature = features.into();
+            *self.cache_stats.entry(z).or_default() += 1;
+            count_tile(&mut self.cache_non_empty_stats, &tile, 1);
+            if let Some((_, evicted)) = self.cache.as_mut().unwrap().push(id, tile) {
+                self.uncount_cached(&evicted);
+            }
+        }
+        &self.cache.as_mut().unwrap().get(&id).unwrap().tile
+    }
+    /// Removes `tile`, no longer held by the cache, from the cache counts.
+    fn uncount_cached(&mut self, tile: &InternalTile) {
+        count_tile(&mut self.cache_non_empty_stats, tile, -1);
+        let count = self.cache_stats.entry(tile.z).or_default();
+        *count -= 1;
+        if *count == 0 {
+            self.cache_stats.remove(&tile.z);
+        }
+    }
     fn find_parent(&self, z: u8, x: u32, y: u32) -> Option<&InternalTile> {
         let mut z0 = z;
         let mut x0 = x;
@@ -111,152 +1630,826 @@ impl GeoJSONVT {
         }
         parent
     }
+    #[allow(clippy::too_many_arguments)]
     fn split_tile(
         &mut self,
-        vt_features: &[Rc<VtFeature>],
+        vt_features: &[Arc<VtFeature>],
         z: u8,
         x: u32,
         y: u32,
         cz: u8,
         cx: u32,
         cy: u32,
+        clip_time: Duration,
     ) {
-        let z2 = (1u32 << z) as f64;
+        // Children without features are never materialized; `tile` resolves them through the
+        // nearest ancestor, which has no features left for them either.
+        if vt_features.is_empty() && z > 0 {
+            return;
+        }
         let id = to_id(z, x, y);
-        if let Entry::Vacant(entry) = self.tiles.entry(id) {
-            let tolerance = if z == self.options.max_zoom {
-                0.
-            } else {
-                self.options.tolerance / (z2 * self.options.extent as f64)
-            };
-            self.tile_coords.push(TileCoord::new(x, y, z));
-            entry.insert(InternalTile::new(
-                vt_features,
-                z,
-                x,
-                y,
-                self.options.extent,
-                tolerance,
-                self.options.line_metrics,
-            ));
-            self.stats.insert(
-                z,
-                if self.stats.contains_key(&z) {
-                    self.stats[&z] + 1
+        match self.tiles.entry(id) {
+            Entry::Vacant(entry) if self.options.content_zoom(z) < self.options.min_zoom => {
+                entry.insert(InternalTile::new_routing(
+                    vt_features,
+                    z,
+                    x,
+                    y,
+                    &self.tile_options,
+                ));
+            }
+            Entry::Vacant(entry) => {
+                self.tile_coords.push(TileCoord::new(x, y, z));
+                let points: u32 = vt_features.iter().map(|feature| feature.point_count).sum();
+                // Tiles where the initial split stops for their point count keep their source
+                // features, their output is only generated on the first request.
+                if cz == 0
+                    && !self.options.eager
+                    && self.options.on_tile_created.is_none()
+                    && z < self.options.tile_index_max_zoom()
+                    && points <= self.options.index_max_points
+                {
+                    let mut tile = InternalTile::new(&[], z, x, y, &self.tile_options);
+                    tile.track(vt_features);
+                    entry.insert(tile);
+                    self.deferred.insert(id);
                 } else {
-                    1
-                },
-            );
-            self.total += 1;
+                    let start = self.options.on_tile_created.is_some().then(Instant::now);
+                    let tile = InternalTile::new(vt_features, z, x, y, &self.tile_options);
+                    let tile = entry.insert(tile);
+                    count_tile(&mut self.non_empty_stats, tile, 1);
+                    if let Some(start) = start {
+                        let elapsed = start.elapsed();
+                        let metrics = tile_metrics(tile, vt_features.len(), clip_time, elapsed);
+                        self.record_tile_metrics(TileCoord::new(x, y, z), metrics);
+                    }
+                }
+                self.stats.insert(
+                    z,
+                    if self.stats.contains_key(&z) {
+                        self.stats[&z] + 1
+                    } else {
+                        1
+                    },
+                );
+                self.total += 1;
+            }
+            Entry::Occupied(_) => (),
         }
 
         let internal_tile = self.tiles.get_mut(&id).unwrap();
         if cz == 0u8 {
-            if z == self.options.index_max_zoom
-                || internal_tile.tile.point_count <= self.options.index_max_points
+            if z == self.options.tile_index_max_zoom()
+                || (!self.options.eager
+                    && internal_tile.tile.point_count <= self.options.index_max_points)
             {
-                internal_tile.source_feature = vt_features.to_vec();
+                internal_tile.source_feature = vt_features.into();
                 return;
             }
         } else {
-            if z == self.options.max_zoom {
+            if z == self.options.tile_max_zoom() {
                 return;
             }
             if z == cz {
-                internal_tile.source_feature = vt_features.to_vec();
+                internal_tile.source_feature = vt_features.into();
                 return;
             }
             let m = (1u32 << (cz - z)) as f64;
             let a = (cx as f64 / m).floor() as u32;
             let b = (cy as f64 / m).floor() as u32;
             if x != a || y != b {
-                internal_tile.source_feature = vt_features.to_vec();
+                internal_tile.source_feature = vt_features.into();
                 return;
             }
         }
-        internal_tile.source_feature.clear();
+        // The output of a tile is generated from its source features, which are about to move
+        // to its children.
+        self.generate_pending(id);
+        self.tiles.get_mut(&id).unwrap().source_feature = Arc::default();
         if vt_features.is_empty() {
             return;
         }
 
-        let p = 0.5 * self.options.buffer as f64 / self.options.extent as f64;
-        let bbox = internal_tile.bbox;
-
-        let left = clip::<0>(
-            vt_features,
-            (x as f64 - p) / z2,
-            (x as f64 + 0.5 + p) / z2,
-            bbox.min_x,
-            bbox.max_x,
-            self.options.line_metrics,
-        );
-
-        let left_top = clip::<1>(
-            &left,
-            (y as f64 - p) / z2,
-            (y as f64 + 0.5 + p) / z2,
-            bbox.min_y,
-            bbox.max_y,
-            self.options.line_metrics,
-        );
-
-        self.split_tile(&left_top, z + 1, x * 2, y * 2, cz, cx, cy);
-        let left_bottom = clip::<1>(
-            &left,
-            (y as f64 + 0.5 - p) / z2,
-            (y as f64 + 1. + p) / z2,
-            bbox.min_y,
-            bbox.max_y,
-            self.options.line_metrics,
-        );
-        self.split_tile(&left_bottom, z + 1, x * 2, y * 2 + 1, cz, cx, cy);
-        let right = clip::<0>(
-            vt_features,
-            (x as f64 + 0.5 - p) / z2,
-            (x as f64 + 1. + p) / z2,
-            bbox.min_x,
-            bbox.max_x,
-            self.options.line_metrics,
-        );
-        let right_top = clip::<1>(
-            &right,
-            (y as f64 - p) / z2,
-            (y as f64 + 0.5 + p) / z2,
-            bbox.min_y,
-            bbox.max_y,
-            self.options.line_metrics,
-        );
-        self.split_tile(&right_top, z + 1, x * 2 + 1, y * 2, cz, cx, cy);
-        let right_bottom = clip::<1>(
-            &right,
-            (y as f64 + 0.5 - p) / z2,
-            (y as f64 + 1. + p) / z2,
-            bbox.min_y,
-            bbox.max_y,
-            self.options.line_metrics,
-        );
-        self.split_tile(&right_bottom, z + 1, x * 2 + 1, y * 2 + 1, cz, cx, cy);
+        let start = self.start_timer();
+        let children = split_once(vt_features, z, x, y, &self.options);
+        let clip_time = elapsed(start);
+        for (child, features) in children {
+            self.split_tile(
+                &features,
+                child.z(),
+                child.x(),
+                child.y(),
+                cz,
+                cx,
+                cy,
+                clip_time,
+            );
+        }
+    }
+    /// Generates the output of the tile `id` from its source features if it was evicted or
+    /// deferred when indexing.
+    fn generate_pending(&mut self, id: u64) {
+        if !(self.evicted.remove(&id) | self.deferred.remove(&id)) {
+            return;
+        }
+        let start = self.start_timer();
+        let tile = self.tiles.get_mut(&id).unwrap();
+        tile.generate();
+        count_tile(&mut self.non_empty_stats, tile, 1);
+        if let Some(start) = start {
+            let (features, elapsed) = (tile.source_feature.len(), start.elapsed());
+            let metrics = tile_metrics(tile, features, Duration::ZERO, elapsed);
+            let coord = tile.coord();
+            self.record_tile_metrics(coord, metrics);
+        }
+    }
+    /// Starts timing tile generation, only when [`Options::on_tile_created`] is set.
+    fn start_timer(&self) -> Option<Instant> {
+        self.options.on_tile_created.is_some().then(Instant::now)
+    }
+    fn record_tile_metrics(&mut self, coord: TileCoord, metrics: TileMetrics) {
+        if let Some(OnTileCreated(callback)) = &self.options.on_tile_created {
+            let mut callback = callback.lock().unwrap_or_else(PoisonError::into_inner);
+            callback(&coord, &metrics);
+        }
+        let index = self
+            .slowest
+            .partition_point(|(_, slow)| slow.total_time() >= metrics.total_time());
+        if index < SLOWEST_TILES {
+            self.slowest.insert(index, (coord, metrics));
+            self.slowest.truncate(SLOWEST_TILES);
+        }
+    }
+    /// Returns the tiles that took the longest to generate, slowest first, with their metrics.
+    ///
+    /// Only tiles generated while [`Options::on_tile_created`] is set are timed, the report is
+    /// empty otherwise.
+    pub fn slowest_tiles(&self) -> &[(TileCoord, TileMetrics)] {
+        &self.slowest
     }
 
+    /// Approximates the heap memory held by the index, in bytes.
+    ///
+    /// Counts the cached tiles with their output coordinates and the projected source features
+    /// they keep for on-demand generation, each shared feature once. Properties are ignored, so
+    /// the figure is an order of magnitude rather than an exact measure.
+    pub fn estimate_memory_bytes(&self) -> usize {
+        let mut seen = HashSet::new();
+        let mut source_bytes = |features: &[Arc<VtFeature>]| {
+            let mut bytes = size_of_val(features);
+            for feature in features {
+                if seen.insert(Arc::as_ptr(feature)) {
+                    bytes += size_of::<VtFeature>()
+                        + feature.point_count as usize * size_of::<VtPoint>();
+                }
+            }
+            bytes
+        };
+        let position_bytes = size_of::<Position>() + 2 * size_of::<f64>();
+        let mut bytes = source_bytes(&self.source);
+        let cached = self
+            .cache
+            .iter()
+            .flat_map(|cache| cache.iter().map(|(_, tile)| tile));
+        for tile in self.tiles.values().chain(cached) {
+            bytes += size_of::<u64>() + size_of::<InternalTile>() + size_of::<TileCoord>();
+            bytes += source_bytes(&tile.source_feature);
+            bytes += tile.feature_count() * size_of::<Feature>();
+            bytes += tile.tile.simplified_count as usize * position_bytes;
+        }
+        bytes
+    }
+    /// Measures the memory held by the index, in bytes, from the allocated capacity of every
+    /// vector it owns.
+    ///
+    /// Walks the source features, each shared one counted once with its `Arc` counters, and the
+    /// generated tiles with their output geometries. Property maps are counted by their handle
+    /// only, and hash tables as one slot plus one control byte per entry of capacity. Slower than
+    /// [`GeoJSONVT::estimate_memory_bytes`] as every geometry is visited.
+    pub fn index_size_bytes(&self) -> usize {
+        let mut seen = HashSet::new();
+        let mut source_bytes = |features: &[Arc<VtFeature>]| {
+            let mut bytes = size_of_val(features);
+            for feature in features {
+                if seen.insert(Arc::as_ptr(feature)) {
+                    bytes += 2 * size_of::<usize>() + size_of::<VtFeature>();
+                    bytes += vt_geometry_heap_bytes(&feature.geometry);
+                }
+            }
+            bytes
+        };
+        let tile_bytes = |tile: &InternalTile| {
+            let tile = &tile.tile;
+            let mut bytes = 4 * size_of::<usize>() + size_of::<Tile>();
+            bytes += tile.feature_collection.features.capacity() * size_of::<Feature>();
+            bytes += tile.layers.capacity() * size_of::<(Option<String>, FeatureCollection)>();
+            for (name, layer) in &tile.layers {
+                bytes += name.as_ref().map_or(0, String::capacity);
+                bytes += layer.features.capacity() * size_of::<Feature>();
+            }
+            for feature in tile.features() {
+                bytes += feature.geometry.as_ref().map_or(0, |geometry| {
+                    size_of::<Geometry>() + value_heap_bytes(&geometry.value)
+                });
+            }
+            bytes
+        };
+        let mut bytes = size_of::<Self>() + source_bytes(&self.source);
+        bytes += (self.source.capacity() - self.source.len()) * size_of::<Arc<VtFeature>>();
+        bytes += self.tiles.capacity() * (size_of::<(u64, InternalTile)>() + 1);
+        bytes += self.tile_coords.capacity() * size_of::<TileCoord>();
+        bytes += self.stats.capacity() * (size_of::<(u8, u32)>() + 1);
+        bytes += self.warnings.capacity() * size_of::<ConvertWarning>();
+        bytes += self.feature_ids.capacity() * (size_of::<(String, Vec<Arc<VtFeature>>)>() + 1);
+        for (key, features) in &self.feature_ids {
+            bytes += key.capacity() + features.capacity() * size_of::<Arc<VtFeature>>();
+        }
+        bytes += self.slowest.capacity() * size_of::<(TileCoord, TileMetrics)>();
+        bytes += self.evicted.capacity() * (size_of::<u64>() + 1);
+        bytes += self.deferred.capacity() * (size_of::<u64>() + 1);
+        bytes += self.watchers.capacity() * (size_of::<(u64, Vec<Sender<()>>)>() + 1);
+        for senders in self.watchers.values() {
+            bytes += senders.capacity() * size_of::<Sender<()>>();
+        }
+        for tile in self.tiles.values() {
+            bytes += source_bytes(&tile.source_feature) + tile_bytes(tile);
+        }
+        for (_, tile) in self.cache.iter().flat_map(|cache| cache.iter()) {
+            bytes += size_of::<(u64, InternalTile)>();
+            bytes += source_bytes(&tile.source_feature) + tile_bytes(tile);
+        }
+        bytes
+    }
+    /// Returns the lowest zoom at which every line and polygon of the index spans at least
+    /// `min_pixels` on screen, for tiles `tile_width_pixels` wide (`Options::extent` to measure
+    /// in tile units), clamped to `Options::max_zoom`.
+    ///
+    /// Polygons are sized by the square root of the area of their outer rings and lines by their
+    /// length, so the zoom is `ceil(log2(min_pixels / (tile_width_pixels * size)))` for the
+    /// smallest feature. Points are ignored; an index without lines or polygons returns 0.
+    pub fn zoom_for_feature_size(&self, min_pixels: f64, tile_width_pixels: u32) -> u8 {
+        let Some(size) = self.min_feature_size else {
+            return 0;
+        };
+        let zoom = (min_pixels / (tile_width_pixels as f64 * size))
+            .log2()
+            .ceil();
+        zoom.clamp(0., self.options.tile_max_zoom() as f64) as u8
+    }
+    /// Simplification tolerance of the lines in the tiles at zoom `z`, in the projected 0..1
+    /// space: [`Options::line_tolerance`] divided by `2^z * extent`, or the entry of
+    /// [`Options::tolerance_schedule`]. Tiles at `Options::max_zoom` are not simplified, 0,
+    /// and zooms above it are clamped to it. With [`TileSize::Px512`] `z` is the zoom tiles
+    /// are requested at, whose content is simplified as that of `z - 1`.
+    pub fn tolerance_at_zoom(&self, z: u8) -> f64 {
+        self.options
+            .tile_tolerances(z.min(self.options.tile_max_zoom()))
+            .0
+    }
+    /// Returns the lowest zoom whose tolerance, see [`GeoJSONVT::tolerance_at_zoom`], is at most
+    /// `desired_tolerance` in the projected 0..1 space, clamped to `Options::max_zoom`.
+    ///
+    /// Simplification is negligible from that zoom on, which makes it a candidate for
+    /// `Options::index_max_zoom`.
+    pub fn zoom_for_tolerance(&self, desired_tolerance: f64) -> u8 {
+        (0..self.options.tile_max_zoom())
+            .find(|&z| self.tolerance_at_zoom(z) <= desired_tolerance)
+            .unwrap_or(self.options.tile_max_zoom())
+    }
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+    /// Projected features the index was built from, empty without [`Options::retain_sources`]
+    /// and after [`GeoJSONVT::drop_sources`].
+    pub(crate) fn source(&self) -> &[Arc<VtFeature>] {
+        &self.source
+    }
+    /// Data quality issues found while converting the input features.
+    pub fn warnings(&self) -> &[ConvertWarning] {
+        &self.warnings
+    }
+    /// Features skipped or heavily simplified while converting the input features.
+    pub fn conversion_report(&self) -> &ConversionReport {
+        &self.conversion_report
+    }
+    /// Features with coordinates out of range skipped with [`Options::strict_coordinates`].
+    pub fn conversion_errors(&self) -> &[ConvertError] {
+        &self.conversion_report.errors
+    }
+    /// Tiles of the index by id. Their output and source features are shared with the clones
+    /// of the index until either changes them, see [`GeoJSONVT`]'s `Clone`.
     pub fn internal_tiles(&self) -> &HashMap<u64, InternalTile> {
         &self.tiles
     }
+    /// Number of tiles held by the cache bounded by [`Options::tile_cache_capacity`].
+    pub fn tile_cache_len(&self) -> usize {
+        self.cache.as_ref().map_or(0, |cache| cache.len())
+    }
     pub fn tile_coords(&self) -> &Vec<TileCoord> {
         &self.tile_coords
     }
+    #[deprecated(note = "use `GeoJSONVT::index_stats`, which tells empty tiles apart, instead")]
     pub fn total(&self) -> u32 {
         self.total
     }
+    #[deprecated(note = "use `GeoJSONVT::index_stats` instead")]
     pub fn stats(&self) -> &HashMap<u8, u32> {
         &self.stats
     }
+    /// Returns the number of tiles generated so far, per zoom and in total, with and without
+    /// the empty ones, with their points and how long the initial build took.
+    pub fn index_stats(&self) -> IndexStats {
+        let cached = self
+            .cache
+            .iter()
+            .flat_map(|cache| cache.iter().map(|(_, tile)| tile));
+        let (original, simplified) =
+            self.tiles
+                .values()
+                .chain(cached)
+                .fold((0, 0), |(original, simplified), tile| {
+                    (
+                        original + tile.tile.point_count,
+                        simplified + tile.tile.simplified_count,
+                    )
+                });
+        let merged = |index: &HashMap<u8, u32>, cache: &HashMap<u8, u32>| {
+            let mut counts = index.clone();
+            for (z, count) in cache {
+                *counts.entry(*z).or_default() += count;
+            }
+            counts
+        };
+        let tiles_per_zoom = merged(&self.stats, &self.cache_stats);
+        let non_empty_tiles_per_zoom = merged(&self.non_empty_stats, &self.cache_non_empty_stats);
+        IndexStats {
+            total_tiles: tiles_per_zoom.values().sum(),
+            tiles_per_zoom,
+            non_empty_tiles: non_empty_tiles_per_zoom.values().sum(),
+            non_empty_tiles_per_zoom,
+            total_input_features: self.input_features,
+            total_simplified_points: simplified,
+            total_original_points: original,
+            build_duration: self.build_duration,
+        }
+    }
+    /// Estimates the fraction of the world covered by data at zoom `z`, as the number of
+    /// generated tiles at that zoom with at least one feature, see
+    /// [`IndexStats::non_empty_tiles_per_zoom`], divided by the `4^z` tiles of the full grid.
+    pub fn coverage_ratio(&self, z: u8) -> f64 {
+        let count = |stats: &HashMap<u8, u32>| stats.get(&z).copied().unwrap_or(0);
+        let count = count(&self.non_empty_stats) + count(&self.cache_non_empty_stats);
+        count as f64 / 4f64.powi(z as i32)
+    }
+}
+
+/// Outcome of [`GeoJSONVT::tile_status`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TileStatus<'a> {
+    /// The tile has at least one feature.
+    Generated(&'a Tile),
+    /// The tile exists but has no features.
+    Empty { reason: EmptyReason },
+    /// The zoom is above `Options::max_zoom` or the row is outside the grid at that zoom.
+    OutOfRange,
+}
+
+/// Why a tile has no features, see [`TileStatus::Empty`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum EmptyReason {
+    /// No feature reaches the tile, buffer included.
+    OutsideData,
+    /// Features only reach the buffer of the tile, which [`Options::clip_output_to_extent`]
+    /// leaves out.
+    ClippedToExtent,
+    /// Features reach the tile but all of them are too small to be kept at its zoom.
+    FilteredByTolerance,
+    /// The tile was never generated and the sources it would be clipped from were dropped with
+    /// [`GeoJSONVT::drop_sources`], while its nearest indexed ancestor has features reaching it.
+    /// Tiles that ancestor has no features near are reported [`EmptyReason::OutsideData`].
+    NoSourceFeatures,
+    /// The tile is below `Options::min_zoom`.
+    BelowMinZoom,
+}
+
+/// Iterator over the non-empty tiles of one zoom level, see [`GeoJSONVT::tiles_at_zoom`].
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct TilesAtZoom<'a> {
+    options: &'a Options,
+    tile_options: &'a Arc<TileOptions>,
+    z: u8,
+    cached: Vec<&'a InternalTile>,
+    stack: Vec<(u8, u32, u32, Vec<Arc<VtFeature>>)>,
+}
+
+impl Iterator for TilesAtZoom<'_> {
+    type Item = (TileCoord, Tile);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(tile) = self.cached.pop() {
+            if tile.feature_count() > 0 {
+                return Some((
+                    TileCoord::new(tile.x, tile.y, self.z),
+                    Tile::clone(&tile.tile),
+                ));
+            }
+        }
+        while let Some((z, x, y, features)) = self.stack.pop() {
+            if z == self.z {
+                let tile = InternalTile::new(&features, z, x, y, self.tile_options);
+                if tile.feature_count() > 0 {
+                    return Some((
+                        TileCoord::new(x, y, self.z),
+                        Arc::unwrap_or_clone(tile.tile),
+                    ));
+                }
+                continue;
+            }
+            let bbox = features_bbox(&features);
+            let children = split_features(&features, z, x, y, &bbox, self.options);
+            let child_coords = [(0, 0), (1, 0), (0, 1), (1, 1)];
+            for ((dx, dy), features) in child_coords.into_iter().zip(children).rev() {
+                if !features.is_empty() {
+                    self.stack.push((z + 1, x * 2 + dx, y * 2 + dy, features));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Accumulates GeoJSON features and builds a [`GeoJSONVT`] from them in one pass.
+#[derive(Debug, Default, Clone)]
+pub struct GeoJSONVTBuilder {
+    features: Vec<Feature>,
+}
+
+impl GeoJSONVTBuilder {
+    pub fn add_feature(&mut self, feature: Feature) -> &mut Self {
+        self.features.push(feature);
+        self
+    }
+    pub fn add_feature_collection(&mut self, fc: FeatureCollection) -> &mut Self {
+        self.features.extend(fc.features);
+        self
+    }
+    pub fn add_geojson(&mut self, geojson: &GeoJson) -> &mut Self {
+        self.add_feature_collection(geojson_to_feature_collection(geojson))
+    }
+    /// Number of features accumulated so far.
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+    /// Converts, wraps and indexes all accumulated features.
+    ///
+    /// # Panics
+    ///
+    /// Like [`GeoJSONVT::new`], see [`GeoJSONVT::try_new`] to get an error instead.
+    #[must_use = "`build` consumes the builder and returns the index"]
+    pub fn build(self, options: Options) -> GeoJSONVT {
+        GeoJSONVT::new(FeatureCollection::from_iter(self.features), options)
+    }
+}
+
+/// Clips the features of the tile at `from` down to its descendant at `to`.
+fn clip_to_tile(
+    features: &[Arc<VtFeature>],
+    from: (u8, u32, u32),
+    to: (u8, u32, u32),
+    options: &Options,
+) -> Vec<Arc<VtFeature>> {
+    let ((mut z, mut x, mut y), (cz, cx, cy)) = (from, to);
+    let mut features = features.to_vec();
+    while z < cz && !features.is_empty() {
+        let bbox = features_bbox(&features);
+        let shift = cz - z - 1;
+        let (dx, dy) = ((cx >> shift) & 1, (cy >> shift) & 1);
+        let [top_left, top_right, bottom_left, bottom_right] =
+            split_features(&features, z, x, y, &bbox, options);
+        features = match (dx, dy) {
+            (0, 0) => top_left,
+            (1, 0) => top_right,
+            (0, _) => bottom_left,
+            _ => bottom_right,
+        };
+        (z, x, y) = (z + 1, x * 2 + dx, y * 2 + dy);
+    }
+    features
+}
+
+/// Clips the projected features of tile `z/x/y` into its four children, each with its buffer,
+/// in the order top-left, top-right, bottom-left, bottom-right.
+///
+/// This is the step [`GeoJSONVT`] takes to split a tile of its pyramid, without any state, so
+/// that repeating it from the source features of [`GeoJSONVT::features_in_tile`] yields the
+/// same features as the index.
+#[must_use = "`split_once` returns the clipped children"]
+pub fn split_once(
+    features: &[Arc<VtFeature>],
+    z: u8,
+    x: u32,
+    y: u32,
+    options: &Options,
+) -> [(TileCoord, Vec<Arc<VtFeature>>); 4] {
+    let bbox = features_bbox(features);
+    let [top_left, top_right, bottom_left, bottom_right] =
+        split_features(features, z, x, y, &bbox, options);
+    let (x, y, z) = (x * 2, y * 2, z + 1);
+    [
+        (TileCoord::new(x, y, z), top_left),
+        (TileCoord::new(x + 1, y, z), top_right),
+        (TileCoord::new(x, y + 1, z), bottom_left),
+        (TileCoord::new(x + 1, y + 1, z), bottom_right),
+    ]
+}
+
+/// Clips the features of tile `z/x/y`, bounded by `bbox`, into its four children, in the order
+/// of [`InternalTile::child_coords`].
+pub(crate) fn split_features(
+    features: &[Arc<VtFeature>],
+    z: u8,
+    x: u32,
+    y: u32,
+    bbox: &BBox,
+    options: &Options,
+) -> [Vec<Arc<VtFeature>>; 4] {
+    let z2 = (1u32 << z) as f64;
+    let p = 0.5 * options.extent_buffer() as f64 / options.extent as f64;
+    let (x, y) = (x as f64, y as f64);
+    let (line_metrics, boundary) = (options.line_metrics, options.point_boundary);
+    let clipped_area = options.remove_slivers;
+
+    let left = clip::<0>(
+        features,
+        (x - p) / z2,
+        (x + 0.5 + p) / z2,
+        bbox.min_x,
+        bbox.max_x,
+        line_metrics,
+        clipped_area,
+        boundary,
+    );
+    let right = clip::<0>(
+        features,
+        (x + 0.5 - p) / z2,
+        (x + 1. + p) / z2,
+        bbox.min_x,
+        bbox.max_x,
+        line_metrics,
+        clipped_area,
+        boundary,
+    );
+    let top = |features: &[Arc<VtFeature>]| {
+        clip::<1>(
+            features,
+            (y - p) / z2,
+            (y + 0.5 + p) / z2,
+            bbox.min_y,
+            bbox.max_y,
+            line_metrics,
+            clipped_area,
+            boundary,
+        )
+    };
+    let bottom = |features: &[Arc<VtFeature>]| {
+        clip::<1>(
+            features,
+            (y + 0.5 - p) / z2,
+            (y + 1. + p) / z2,
+            bbox.min_y,
+            bbox.max_y,
+            line_metrics,
+            clipped_area,
+            boundary,
+        )
+    };
+    [top(&left), top(&right), bottom(&left), bottom(&right)]
+}
+
+/// Clips projected features to a WGS-84 `[min_lng, min_lat, max_lng, max_lat]` box.
+fn clip_to_bbox(
+    features: &[Arc<VtFeature>],
+    bbox: [f64; 4],
+    line_metrics: bool,
+    clipped_area: bool,
+) -> Vec<Arc<VtFeature>> {
+    let (min_x, max_x) = (lng_to_mercator_x(bbox[0]), lng_to_mercator_x(bbox[2]));
+    let (min_y, max_y) = (lat_to_mercator_y(bbox[3]), lat_to_mercator_y(bbox[1]));
+    let (min_all, max_all) = (f64::NEG_INFINITY, f64::INFINITY);
+    let both = Boundary::Both;
+    let clipped = clip::<0>(
+        features,
+        min_x,
+        max_x,
+        min_all,
+        max_all,
+        line_metrics,
+        clipped_area,
+        both,
+    );
+    clip::<1>(
+        &clipped,
+        min_y,
+        max_y,
+        min_all,
+        max_all,
+        line_metrics,
+        clipped_area,
+        both,
+    )
+}
+
+/// Size of a line or polygon in projected units, see [`GeoJSONVT::zoom_for_feature_size`].
+fn geometry_size(geometry: &VtGeometry) -> Option<f64> {
+    match geometry {
+        VtGeometry::Point(_) | VtGeometry::MultiPoint(_) => None,
+        VtGeometry::LineString(line) => Some(line.dist),
+        VtGeometry::MultiLineString(lines) => Some(lines.iter().map(|line| line.dist).sum()),
+        VtGeometry::Polygon(rings) => rings.first().map(|ring| ring.area.sqrt()),
+        VtGeometry::MultiPolygon(polygons) => Some(
+            polygons
+                .iter()
+                .filter_map(|rings| rings.first())
+                .map(|ring| ring.area)
+                .sum::<f64>()
+                .sqrt(),
+        ),
+        VtGeometry::GeometryCollection(geometries) => {
+            geometries.iter().filter_map(geometry_size).reduce(f64::max)
+        }
+    }
+}
+
+/// Bytes allocated by the vectors of a projected geometry.
+fn vt_geometry_heap_bytes(geometry: &VtGeometry) -> usize {
+    let points = |points: &Vec<VtPoint>| points.capacity() * size_of::<VtPoint>();
+    let polygon = |rings: &Vec<VtLinearRing>| {
+        rings.capacity() * size_of::<VtLinearRing>()
+            + rings
+                .iter()
+                .map(|ring| points(&ring.elements))
+                .sum::<usize>()
+    };
+    match geometry {
+        VtGeometry::Point(_) => 0,
+        VtGeometry::MultiPoint(multi_point) => {
+            points(&multi_point.elements) + multi_point.indices.capacity() * size_of::<u32>()
+        }
+        VtGeometry::LineString(line) => points(&line.elements),
+        VtGeometry::MultiLineString(lines) => {
+            lines.capacity() * size_of::<VtLineString>()
+                + lines
+                    .iter()
+                    .map(|line| points(&line.elements))
+                    .sum::<usize>()
+        }
+        VtGeometry::Polygon(rings) => polygon(rings),
+        VtGeometry::MultiPolygon(polygons) => {
+            polygons.capacity() * size_of::<Vec<VtLinearRing>>()
+                + polygons.iter().map(polygon).sum::<usize>()
+        }
+        VtGeometry::GeometryCollection(geometries) => {
+            geometries.capacity() * size_of::<VtGeometry>()
+                + geometries.iter().map(vt_geometry_heap_bytes).sum::<usize>()
+        }
+    }
+}
+
+/// Bytes allocated by the vectors of a GeoJSON geometry.
+fn value_heap_bytes(value: &Value) -> usize {
+    let position = |position: &Position| position.capacity() * size_of::<f64>();
+    let line = |line: &Vec<Position>| {
+        line.capacity() * size_of::<Position>() + line.iter().map(position).sum::<usize>()
+    };
+    let polygon = |rings: &Vec<Vec<Position>>| {
+        rings.capacity() * size_of::<Vec<Position>>() + rings.iter().map(line).sum::<usize>()
+    };
+    match value {
+        Value::Point(point) => position(point),
+        Value::MultiPoint(points) | Value::LineString(points) => line(points),
+        Value::MultiLineString(rings) | Value::Polygon(rings) => polygon(rings),
+        Value::MultiPolygon(polygons) => {
+            polygons.capacity() * size_of::<Vec<Vec<Position>>>()
+                + polygons.iter().map(polygon).sum::<usize>()
+        }
+        Value::GeometryCollection(geometries) => {
+            geometries.capacity() * size_of::<Geometry>()
+                + geometries
+                    .iter()
+                    .map(|geometry| value_heap_bytes(&geometry.value))
+                    .sum::<usize>()
+        }
+    }
+}
+
+fn elapsed(start: Option<Instant>) -> Duration {
+    start.map_or(Duration::ZERO, |start| start.elapsed())
+}
+
+fn tile_metrics(
+    tile: &InternalTile,
+    input_features: usize,
+    clip_time: Duration,
+    transform_time: Duration,
+) -> TileMetrics {
+    TileMetrics {
+        clip_time,
+        transform_time,
+        input_features,
+        output_features: tile.feature_count(),
+        point_count: tile.tile.point_count,
+        simplified_count: tile.tile.simplified_count,
+    }
 }
 
 #[inline]
-fn to_id(z: u8, x: u32, y: u32) -> u64 {
+/// Adds `delta` to the count of the zoom of `tile` if it has at least one feature, dropping
+/// the zooms left without any.
+pub(crate) fn count_tile(counts: &mut HashMap<u8, u32>, tile: &InternalTile, delta: i32) {
+    if tile.feature_count() == 0 {
+        return;
+    }
+    let count = counts.entry(tile.z).or_default();
+    *count = count.checked_add_signed(delta).unwrap();
+    if *count == 0 {
+        counts.remove(&tile.z);
+    }
+}
+
+/// Bounds of tile `z/x/y` in the projected 0..1 space.
+fn tile_bbox(z: u8, x: u32, y: u32) -> BBox {
+    let z2 = (1u32 << z) as f64;
+    BBox {
+        min_x: x as f64 / z2,
+        min_y: y as f64 / z2,
+        max_x: (x + 1) as f64 / z2,
+        max_y: (y + 1) as f64 / z2,
+    }
+}
+
+/// Smallest tile down to `max_zoom` containing `bbox`, the root when it reaches beyond 0..1.
+fn containing_tile(bbox: &BBox, max_zoom: u8) -> (u8, u32, u32) {
+    (1..=max_zoom)
+        .rev()
+        .find_map(|z| {
+            let z2 = (1u32 << z) as f64;
+            let (x, y) = ((bbox.min_x * z2).floor(), (bbox.min_y * z2).floor());
+            let within = (0. ..z2).contains(&x) && (0. ..z2).contains(&y);
+            (within && x == (bbox.max_x * z2).floor() && y == (bbox.max_y * z2).floor())
+                .then_some((z, x as u32, y as u32))
+        })
+        .unwrap_or((0, 0, 0))
+}
+
+pub(crate) fn to_id(z: u8, x: u32, y: u32) -> u64 {
     ((1u64 << z) * y as u64 + x as u64) * 32 + z as u64
 }
 
+/// Inverse-projects `feature` to a GeoJSON feature in WGS-84 coordinates.
+fn lnglat_feature(feature: &VtFeature) -> Feature {
+    Feature {
+        bbox: None,
+        geometry: Some(Geometry::new(lnglat_value(&feature.geometry))),
+        id: feature.id.clone(),
+        properties: (*feature.properties).clone(),
+        foreign_members: None,
+    }
+}
+
+/// Inverse-projects `geometry` to a GeoJSON geometry in WGS-84 coordinates.
+fn lnglat_value(geometry: &VtGeometry) -> Value {
+    let position = |p: &VtPoint| vec![mercator_x_to_lng(p.x), mercator_y_to_lat(p.y)];
+    let line = |points: &[VtPoint]| points.iter().map(position).collect::<Vec<_>>();
+    let polygon = |rings: &Vec<VtLinearRing>| {
+        rings
+            .iter()
+            .map(|ring| line(&ring.elements))
+            .collect::<Vec<_>>()
+    };
+    match geometry {
+        VtGeometry::Point(p) => Value::Point(position(p)),
+        VtGeometry::MultiPoint(ps) => Value::MultiPoint(line(&ps.elements)),
+        VtGeometry::LineString(ls) => Value::LineString(line(&ls.elements)),
+        VtGeometry::MultiLineString(mls) => {
+            Value::MultiLineString(mls.iter().map(|ls| line(&ls.elements)).collect())
+        }
+        VtGeometry::Polygon(rings) => Value::Polygon(polygon(rings)),
+        VtGeometry::MultiPolygon(polygons) => {
+            Value::MultiPolygon(polygons.iter().map(polygon).collect())
+        }
+        VtGeometry::GeometryCollection(geometries) => Value::GeometryCollection(
+            geometries
+                .iter()
+                .map(|geometry| Geometry::new(lnglat_value(geometry)))
+                .collect(),
+        ),
+    }
+}
+
 fn geojson_to_feature_collection(geojson: &GeoJson) -> FeatureCollection {
     match geojson {
         GeoJson::Geometry(geom) => FeatureCollection {
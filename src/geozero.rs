@@ -0,0 +1,453 @@
+use ::geozero::{
+    ColumnValue, FeatureProcessor, GeomProcessor, GeozeroDatasource, PropertyProcessor,
+    error::{GeozeroError, Result},
+};
+use geojson::{Feature, Geometry, JsonObject, JsonValue, Position, Value};
+use serde_json::Number;
+#[cfg(feature = "flatgeobuf")]
+use {
+    flatgeobuf::FgbReader,
+    std::io::{Read, Seek},
+};
+
+use crate::{
+    BuildError, GeoJSONVT, Options,
+    convert::{
+        ConvertWarning, Converter, Deduplicator, IdGenerator, SkipReason, flatten_collection,
+    },
+    tile::{Tile, TileOrigin, YAxis},
+    types::VtFeature,
+};
+
+impl GeoJSONVT {
+    /// Builds the index from any geozero datasource (FlatGeobuf, GeoParquet, WKB, CSV, ...).
+    ///
+    /// Coordinates are expected as WGS-84 longitude/latitude pairs. geozero carries no feature
+    /// ids, so features only get ids generated or promoted by [`Options::id_policy`].
+    pub fn from_geozero(mut reader: impl GeozeroDatasource, options: Options) -> Result<Self> {
+        let mut builder = GeozeroBuilder::new(&options);
+        reader.process(&mut builder)?;
+        if builder.skipped {
+            let total_input_features = builder.input_features;
+            let warning = ConvertWarning::FeatureLimitReached {
+                total_input_features,
+            };
+            builder.converter.warnings.push(warning);
+        }
+        let warning = builder.deduplicator.warning();
+        builder.converter.warnings.extend(warning);
+        let report = builder.converter.report;
+        if options.strict
+            && let Some(skipped) = report.first_invalid()
+        {
+            let error = BuildError::SkippedFeature(skipped.clone());
+            return Err(GeozeroError::Feature(error.to_string()));
+        }
+        let mut geojsonvt = Self::from_vt_features(builder.vt_features, options);
+        geojsonvt.warnings = builder.converter.warnings;
+        geojsonvt.conversion_report = report;
+        Ok(geojsonvt)
+    }
+    /// Builds the index from a FlatGeobuf file.
+    ///
+    /// With a `[min_lng, min_lat, max_lng, max_lat]` `bbox`, the packed R-tree of the file is used
+    /// to only convert the features intersecting it. Features keep their full geometry, set
+    /// [`Options::clip_to_bbox`] to the same box to also clip them to it.
+    #[cfg(feature = "flatgeobuf")]
+    pub fn from_flatgeobuf<R: Read + Seek>(
+        reader: R,
+        options: Options,
+        bbox: Option<[f64; 4]>,
+    ) -> Result<Self> {
+        let fgb = FgbReader::open(reader).map_err(fgb_error)?;
+        let features = match bbox {
+            Some([min_x, min_y, max_x, max_y]) => fgb.select_bbox(min_x, min_y, max_x, max_y),
+            None => fgb.select_all(),
+        }
+        .map_err(fgb_error)?;
+        Self::from_geozero(features, options)
+    }
+}
+
+#[cfg(feature = "flatgeobuf")]
+fn fgb_error(error: flatgeobuf::Error) -> GeozeroError {
+    match error {
+        flatgeobuf::Error::IO(error) => GeozeroError::IoError(error),
+        error => GeozeroError::Dataset(error.to_string()),
+    }
+}
+
+/// Tiles are processed in tile coordinates, so they can be handed to any geozero writer,
+/// including its MVT writer.
+///
+/// geozero has no notion of feature ids, writers receive the position of each feature in the
+/// tile as `idx`. Tiles with a [`TileOrigin::Center`] origin or a [`YAxis::CartesianCoords`]
+/// y axis are rejected, vector tile coordinates run down from the top-left corner.
+impl GeozeroDatasource for Tile {
+    fn process<P: FeatureProcessor>(&mut self, processor: &mut P) -> Result<()> {
+        if self.origin != TileOrigin::TopLeft || self.y_axis != YAxis::ScreenCoords {
+            return Err(GeozeroError::Dataset(
+                "tiles must have a top-left origin and screen coordinates".to_string(),
+            ));
+        }
+        processor.dataset_begin(None)?;
+        for (idx, feature) in self.features().enumerate() {
+            let idx = idx as u64;
+            processor.feature_begin(idx)?;
+            if let Some(properties) = &feature.properties {
+                processor.properties_begin()?;
+                process_properties(properties, processor)?;
+                processor.properties_end()?;
+            }
+            if let Some(geometry) = &feature.geometry {
+                processor.geometry_begin()?;
+                process_geometry(geometry, 0, processor)?;
+                processor.geometry_end()?;
+            }
+            processor.feature_end(idx)?;
+        }
+        processor.dataset_end()
+    }
+}
+
+fn process_properties<P: PropertyProcessor>(
+    properties: &JsonObject,
+    processor: &mut P,
+) -> Result<()> {
+    for (idx, (name, value)) in properties.iter().enumerate() {
+        let json;
+        let value = match value {
+            JsonValue::Null => continue,
+            JsonValue::Bool(b) => ColumnValue::Bool(*b),
+            JsonValue::Number(n) => {
+                if let Some(i) = n.as_i64() {
+                    ColumnValue::Long(i)
+                } else if let Some(u) = n.as_u64() {
+                    ColumnValue::ULong(u)
+                } else {
+                    ColumnValue::Double(n.as_f64().unwrap_or(f64::NAN))
+                }
+            }
+            JsonValue::String(s) => ColumnValue::String(s),
+            JsonValue::Array(_) | JsonValue::Object(_) => {
+                json = value.to_string();
+                ColumnValue::Json(&json)
+            }
+        };
+        if processor.property(idx, name, &value)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn process_geometry<P: GeomProcessor>(
+    geometry: &Geometry,
+    idx: usize,
+    processor: &mut P,
+) -> Result<()> {
+    match &geometry.value {
+        Value::Point(p) => {
+            processor.point_begin(idx)?;
+            processor.xy(p[0], p[1], 0)?;
+            processor.point_end(idx)
+        }
+        Value::MultiPoint(points) => {
+            processor.multipoint_begin(points.len(), idx)?;
+            process_positions(points, processor)?;
+            processor.multipoint_end(idx)
+        }
+        Value::LineString(line) => process_line_string(line, true, idx, processor),
+        Value::MultiLineString(lines) => {
+            processor.multilinestring_begin(lines.len(), idx)?;
+            for (i, line) in lines.iter().enumerate() {
+                process_line_string(line, false, i, processor)?;
+            }
+            processor.multilinestring_end(idx)
+        }
+        Value::Polygon(rings) => process_polygon(rings, true, idx, processor),
+        Value::MultiPolygon(polygons) => {
+            processor.multipolygon_begin(polygons.len(), idx)?;
+            for (i, rings) in polygons.iter().enumerate() {
+                process_polygon(rings, false, i, processor)?;
+            }
+            processor.multipolygon_end(idx)
+        }
+        Value::GeometryCollection(geometries) => {
+            processor.geometrycollection_begin(geometries.len(), idx)?;
+            for (i, geometry) in geometries.iter().enumerate() {
+                process_geometry(geometry, i, processor)?;
+            }
+            processor.geometrycollection_end(idx)
+        }
+    }
+}
+
+fn process_positions<P: GeomProcessor>(positions: &[Position], processor: &mut P) -> Result<()> {
+    for (i, p) in positions.iter().enumerate() {
+        processor.xy(p[0], p[1], i)?;
+    }
+    Ok(())
+}
+
+fn process_line_string<P: GeomProcessor>(
+    line: &[Position],
+    tagged: bool,
+    idx: usize,
+    processor: &mut P,
+) -> Result<()> {
+    processor.linestring_begin(tagged, line.len(), idx)?;
+    process_positions(line, processor)?;
+    processor.linestring_end(tagged, idx)
+}
+
+fn process_polygon<P: GeomProcessor>(
+    rings: &[Vec<Position>],
+    tagged: bool,
+    idx: usize,
+    processor: &mut P,
+) -> Result<()> {
+    processor.polygon_begin(tagged, rings.len(), idx)?;
+    for (i, ring) in rings.iter().enumerate() {
+        process_line_string(ring, false, i, processor)?;
+    }
+    processor.polygon_end(tagged, idx)
+}
+
+/// Geometry part still receiving coordinates or members.
+enum Part {
+    Point(Vec<Position>),
+    MultiPoint(Vec<Position>),
+    LineString(Vec<Position>),
+    MultiLineString(Vec<Vec<Position>>),
+    Polygon(Vec<Vec<Position>>),
+    MultiPolygon(Vec<Vec<Vec<Position>>>),
+    GeometryCollection(Vec<Geometry>),
+}
+
+/// Assembles each geozero feature as a GeoJSON `Feature` and converts it on `feature_end`,
+/// mirroring [`crate::convert`].
+struct GeozeroBuilder {
+    converter: Converter,
+    ids: IdGenerator,
+    deduplicator: Deduplicator,
+    flatten_collections: bool,
+    feature_limit: Option<usize>,
+    input_features: usize,
+    converted: usize,
+    /// Whether features were left out because of `feature_limit`.
+    skipped: bool,
+    parts: Vec<Part>,
+    geometry: Option<Geometry>,
+    properties: Option<JsonObject>,
+    vt_features: Vec<VtFeature>,
+}
+
+impl GeozeroBuilder {
+    fn new(options: &Options) -> Self {
+        let options = options.convert_options();
+        Self {
+            converter: Converter::with_options(&options),
+            ids: IdGenerator::new(options.id_policy, &[]),
+            deduplicator: Deduplicator::new(options.dedupe),
+            flatten_collections: options.flatten_collections,
+            feature_limit: options.feature_limit,
+            input_features: 0,
+            converted: 0,
+            skipped: false,
+            parts: Vec::new(),
+            geometry: None,
+            properties: None,
+            vt_features: Vec::new(),
+        }
+    }
+
+    fn begin(&mut self, part: Part) -> Result<()> {
+        self.parts.push(part);
+        Ok(())
+    }
+
+    /// Closes the innermost part and hands it to its parent, or makes it the feature geometry.
+    fn end(&mut self) -> Result<()> {
+        let part = self
+            .parts
+            .pop()
+            .ok_or_else(|| GeozeroError::Geometry("unbalanced geometry end".to_string()))?;
+        let value = match (part, self.parts.last_mut()) {
+            (Part::LineString(line), Some(Part::MultiLineString(lines))) => {
+                lines.push(line);
+                return Ok(());
+            }
+            (Part::LineString(ring), Some(Part::Polygon(rings))) => {
+                rings.push(ring);
+                return Ok(());
+            }
+            (Part::Polygon(rings), Some(Part::MultiPolygon(polygons))) => {
+                polygons.push(rings);
+                return Ok(());
+            }
+            (Part::Point(mut p), _) => match p.pop() {
+                Some(p) => Value::Point(p),
+                None => return Ok(()),
+            },
+            (Part::MultiPoint(points), _) => Value::MultiPoint(points),
+            (Part::LineString(line), _) => Value::LineString(line),
+            (Part::MultiLineString(lines), _) => Value::MultiLineString(lines),
+            (Part::Polygon(rings), _) => Value::Polygon(rings),
+            (Part::MultiPolygon(polygons), _) => Value::MultiPolygon(polygons),
+            (Part::GeometryCollection(geometries), _) => Value::GeometryCollection(geometries),
+        };
+        match self.parts.last_mut() {
+            Some(Part::GeometryCollection(geometries)) => geometries.push(Geometry::new(value)),
+            _ => self.geometry = Some(Geometry::new(value)),
+        }
+        Ok(())
+    }
+}
+
+impl GeomProcessor for GeozeroBuilder {
+    fn xy(&mut self, x: f64, y: f64, _idx: usize) -> Result<()> {
+        match self.parts.last_mut() {
+            Some(Part::Point(positions))
+            | Some(Part::MultiPoint(positions))
+            | Some(Part::LineString(positions)) => {
+                positions.push(vec![x, y]);
+                Ok(())
+            }
+            _ => Err(GeozeroError::Geometry(
+                "coordinate outside of a point or line".to_string(),
+            )),
+        }
+    }
+    fn empty_point(&mut self, _idx: usize) -> Result<()> {
+        Ok(())
+    }
+    fn point_begin(&mut self, _idx: usize) -> Result<()> {
+        self.begin(Part::Point(Vec::with_capacity(1)))
+    }
+    fn point_end(&mut self, _idx: usize) -> Result<()> {
+        self.end()
+    }
+    fn multipoint_begin(&mut self, size: usize, _idx: usize) -> Result<()> {
+        self.begin(Part::MultiPoint(Vec::with_capacity(size)))
+    }
+    fn multipoint_end(&mut self, _idx: usize) -> Result<()> {
+        self.end()
+    }
+    fn linestring_begin(&mut self, _tagged: bool, size: usize, _idx: usize) -> Result<()> {
+        self.begin(Part::LineString(Vec::with_capacity(size)))
+    }
+    fn linestring_end(&mut self, _tagged: bool, _idx: usize) -> Result<()> {
+        self.end()
+    }
+    fn multilinestring_begin(&mut self, size: usize, _idx: usize) -> Result<()> {
+        self.begin(Part::MultiLineString(Vec::with_capacity(size)))
+    }
+    fn multilinestring_end(&mut self, _idx: usize) -> Result<()> {
+        self.end()
+    }
+    fn polygon_begin(&mut self, _tagged: bool, size: usize, _idx: usize) -> Result<()> {
+        self.begin(Part::Polygon(Vec::with_capacity(size)))
+    }
+    fn polygon_end(&mut self, _tagged: bool, _idx: usize) -> Result<()> {
+        self.end()
+    }
+    fn multipolygon_begin(&mut self, size: usize, _idx: usize) -> Result<()> {
+        self.begin(Part::MultiPolygon(Vec::with_capacity(size)))
+    }
+    fn multipolygon_end(&mut self, _idx: usize) -> Result<()> {
+        self.end()
+    }
+    fn geometrycollection_begin(&mut self, size: usize, _idx: usize) -> Result<()> {
+        self.begin(Part::GeometryCollection(Vec::with_capacity(size)))
+    }
+    fn geometrycollection_end(&mut self, _idx: usize) -> Result<()> {
+        self.end()
+    }
+}
+
+impl PropertyProcessor for GeozeroBuilder {
+    fn property(&mut self, _idx: usize, name: &str, value: &ColumnValue) -> Result<bool> {
+        let value = match *value {
+            ColumnValue::Byte(v) => JsonValue::from(v),
+            ColumnValue::UByte(v) => JsonValue::from(v),
+            ColumnValue::Bool(v) => JsonValue::from(v),
+            ColumnValue::Short(v) => JsonValue::from(v),
+            ColumnValue::UShort(v) => JsonValue::from(v),
+            ColumnValue::Int(v) => JsonValue::from(v),
+            ColumnValue::UInt(v) => JsonValue::from(v),
+            ColumnValue::Long(v) => JsonValue::from(v),
+            ColumnValue::ULong(v) => JsonValue::from(v),
+            ColumnValue::Float(v) => {
+                Number::from_f64(v as f64).map_or(JsonValue::Null, JsonValue::Number)
+            }
+            ColumnValue::Double(v) => {
+                Number::from_f64(v).map_or(JsonValue::Null, JsonValue::Number)
+            }
+            ColumnValue::String(v) | ColumnValue::DateTime(v) => JsonValue::from(v),
+            ColumnValue::Json(v) => serde_json::from_str(v).unwrap_or_else(|_| JsonValue::from(v)),
+            ColumnValue::Binary(v) => JsonValue::from(v),
+        };
+        self.properties
+            .get_or_insert_with(JsonObject::new)
+            .insert(name.to_string(), value);
+        Ok(false)
+    }
+}
+
+impl FeatureProcessor for GeozeroBuilder {
+    fn feature_begin(&mut self, _idx: u64) -> Result<()> {
+        self.parts.clear();
+        self.geometry = None;
+        self.properties = None;
+        Ok(())
+    }
+    fn feature_end(&mut self, idx: u64) -> Result<()> {
+        self.input_features += 1;
+        let Some(geometry) = self.geometry.take() else {
+            let skipped = SkipReason::NoGeometry;
+            self.converter.report.skip(idx as usize, None, skipped);
+            return Ok(());
+        };
+        if self
+            .feature_limit
+            .is_some_and(|limit| self.converted >= limit)
+        {
+            self.skipped = true;
+            return Ok(());
+        }
+        let feature = Feature {
+            bbox: None,
+            geometry: Some(geometry),
+            id: None,
+            properties: self.properties.take(),
+            foreign_members: None,
+        };
+        let id = self
+            .ids
+            .id(idx as usize, &feature, &mut self.converter.warnings);
+        if self.deduplicator.is_duplicate_id(&id) {
+            return Ok(());
+        }
+        let vt_feature = self
+            .converter
+            .convert_input_feature(idx as usize, feature, id);
+        if let Some(vt_feature) = vt_feature {
+            let start = self.vt_features.len();
+            if self.flatten_collections {
+                flatten_collection(vt_feature, &mut self.vt_features);
+            } else {
+                self.vt_features.push(vt_feature);
+            }
+            if self
+                .deduplicator
+                .is_duplicate_feature(&self.vt_features, start)
+            {
+                self.vt_features.truncate(start);
+                return Ok(());
+            }
+            self.converted += 1;
+        }
+        Ok(())
+    }
+}
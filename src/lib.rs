@@ -1,10 +1,47 @@
 mod clip;
+mod compact;
+#[cfg(feature = "compress")]
+mod compress;
 mod convert;
+mod cover;
+#[cfg(feature = "geo-types")]
+mod geo_types;
 mod geojson_vt;
+#[cfg(feature = "geozero")]
+mod geozero;
+mod mask;
+#[cfg(feature = "persist")]
+mod persist;
 mod simplify;
 mod tile;
 mod types;
+pub mod validate;
 mod wrap;
 
-pub use geojson_vt::{GeoJSONVT, Options};
-pub use types::{VtGeometry, VtPoint};
+pub use clip::Boundary;
+pub use compact::{CompactTile, CompactTileError};
+#[cfg(feature = "compress")]
+pub use compress::Encoding;
+pub use convert::{
+    ConversionReport, ConvertError, ConvertOptions, ConvertWarning, DedupePolicy, IdPolicy,
+    SimplifiedFeature, SkipReason, SkippedFeature, convert, convert_feature, convert_with_warnings,
+};
+pub use cover::{tiles_covering, tiles_covering_bbox};
+pub use geojson_vt::{
+    BufferUnit, BuildError, EmptyReason, GeoJSONVT, GeoJSONVTBuilder, IndexStats, OnTileCreated,
+    Options, OptionsError, QueryError, TileError, TileSize, TileStatus, TilesAtZoom, ToleranceUnit,
+    split_once,
+};
+#[cfg(feature = "persist")]
+pub use persist::DeserializeError;
+pub use simplify::{
+    point_to_segment_dist, point_to_segment_sq_dist, simplify, simplify_vw, simplify_with_forced,
+};
+pub use tile::{
+    FeatureOrder, InternalTile, NumberStyle, SortDirection, Tile, TileCoord, TileMetrics,
+    TileOrigin, TilePropertyHook, YAxis,
+};
+pub use types::{
+    BBox, VtFeature, VtGeometry, VtGeometryCollection, VtLineString, VtLinearRing,
+    VtMultiLineString, VtMultiPoint, VtMultiPolygon, VtPoint, VtPolygon,
+};
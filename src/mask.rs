@@ -0,0 +1,418 @@
+use std::sync::Arc;
+
+use geojson::Geometry;
+
+use crate::{
+    OptionsError,
+    convert::convert_feature,
+    types::{BBox, VtFeature, VtGeometry, VtLineString, VtLinearRing, VtPoint, edge_measure},
+};
+
+/// Clip area made of the outer rings of a `Polygon` or `MultiPolygon`, in projected coordinates.
+///
+/// Each ring is expected to be convex: features are clipped against the half-planes of its edges
+/// (Sutherland–Hodgman for rings, Cyrus–Beck for lines). Holes of the mask are ignored.
+pub(crate) struct Mask {
+    /// Open rings (without the closing point) with a positive signed area.
+    rings: Vec<Vec<VtPoint>>,
+    /// Bounds of all rings, features outside of them are dropped without clipping.
+    bbox: BBox,
+}
+
+impl Mask {
+    /// Fails if `geometry` is not a non-empty `Polygon` or `MultiPolygon`, or if one of its
+    /// outer rings is not convex.
+    pub(crate) fn new(geometry: &Geometry) -> Result<Self, OptionsError> {
+        let feature = geojson::Feature::from(geometry.clone());
+        let polygons = match convert_feature(feature, 0., 0., None, None).map(|f| f.geometry) {
+            Some(VtGeometry::Polygon(rings)) => vec![rings],
+            Some(VtGeometry::MultiPolygon(polygons)) => polygons,
+            _ => return Err(OptionsError::MaskNotPolygon),
+        };
+        let rings = polygons
+            .into_iter()
+            .filter_map(|polygon| polygon.into_iter().next())
+            .map(|ring| {
+                let mut points = ring.elements;
+                if points.len() > 1 && points.first() == points.last() {
+                    points.pop();
+                }
+                if signed_area(&points) < 0. {
+                    points.reverse();
+                }
+                points
+            })
+            .filter(|points| points.len() >= 3)
+            .collect::<Vec<_>>();
+        if rings.is_empty() {
+            return Err(OptionsError::MaskNotPolygon);
+        }
+        if !rings.iter().all(|ring| is_convex(ring)) {
+            return Err(OptionsError::NonConvexMask);
+        }
+        let mut bbox = BBox::default();
+        for point in rings.iter().flatten() {
+            bbox.merge(&BBox {
+                min_x: point.x,
+                min_y: point.y,
+                max_x: point.x,
+                max_y: point.y,
+            });
+        }
+        Ok(Self { rings, bbox })
+    }
+
+    pub(crate) fn clip(
+        &self,
+        features: &[Arc<VtFeature>],
+        line_metrics: bool,
+    ) -> Vec<Arc<VtFeature>> {
+        let mut clipped_features = Vec::with_capacity(features.len());
+        for feature in features {
+            if !feature.bbox.is_some_and(|bbox| bbox.intersects(&self.bbox)) {
+                continue;
+            }
+            let Some(geometry) = self.clip_geometry(&feature.geometry, line_metrics) else {
+                continue;
+            };
+            if line_metrics && let VtGeometry::MultiLineString(lines) = geometry {
+                for line in lines {
+                    clipped_features.push(Arc::new(VtFeature::new(
+                        VtGeometry::LineString(line),
+                        feature.properties.clone(),
+                        feature.id.clone(),
+                    )));
+                }
+                continue;
+            }
+            clipped_features.push(Arc::new(VtFeature::new(
+                geometry,
+                feature.properties.clone(),
+                feature.id.clone(),
+            )));
+        }
+        clipped_features
+    }
+
+    fn clip_geometry(&self, geometry: &VtGeometry, line_metrics: bool) -> Option<VtGeometry> {
+        match geometry {
+            VtGeometry::Point(point) => self.contains(point).then_some(VtGeometry::Point(*point)),
+            VtGeometry::MultiPoint(points) => {
+                let points = points.filtered(|p| self.contains(p));
+                (!points.is_empty()).then_some(VtGeometry::MultiPoint(points))
+            }
+            VtGeometry::LineString(line) => {
+                lines_geometry(self.clip_line(line, line_metrics).collect())
+            }
+            VtGeometry::MultiLineString(lines) => lines_geometry(
+                lines
+                    .iter()
+                    .flat_map(|line| self.clip_line(line, line_metrics))
+                    .collect(),
+            ),
+            VtGeometry::Polygon(polygon) => polygons_geometry(self.clip_polygon(polygon).collect()),
+            VtGeometry::MultiPolygon(polygons) => polygons_geometry(
+                polygons
+                    .iter()
+                    .flat_map(|polygon| self.clip_polygon(polygon))
+                    .collect(),
+            ),
+            VtGeometry::GeometryCollection(geometries) => {
+                let geometries: Vec<_> = geometries
+                    .iter()
+                    .filter_map(|g| self.clip_geometry(g, line_metrics))
+                    .collect();
+                (!geometries.is_empty()).then_some(VtGeometry::GeometryCollection(geometries))
+            }
+        }
+    }
+
+    fn contains(&self, point: &VtPoint) -> bool {
+        self.bbox.contains_point(point.x, point.y)
+            && self
+                .rings
+                .iter()
+                .any(|ring| edges(ring).all(|(a, b)| side(a, b, point) >= 0.))
+    }
+
+    fn clip_line<'a>(
+        &'a self,
+        line: &'a VtLineString,
+        line_metrics: bool,
+    ) -> impl Iterator<Item = VtLineString> + 'a {
+        self.rings
+            .iter()
+            .flat_map(move |ring| clip_line(ring, line, line_metrics))
+    }
+
+    fn clip_polygon<'a>(
+        &'a self,
+        polygon: &'a [VtLinearRing],
+    ) -> impl Iterator<Item = Vec<VtLinearRing>> + 'a {
+        self.rings.iter().filter_map(move |mask| {
+            let mut rings = polygon.iter().map(|ring| clip_ring(mask, ring));
+            // A polygon whose outer ring is clipped away is dropped with its holes.
+            let outer = rings.next()??;
+            Some(std::iter::once(outer).chain(rings.flatten()).collect())
+        })
+    }
+}
+
+fn lines_geometry(mut lines: Vec<VtLineString>) -> Option<VtGeometry> {
+    match lines.len() {
+        0 => None,
+        1 => lines.pop().map(VtGeometry::LineString),
+        _ => Some(VtGeometry::MultiLineString(lines)),
+    }
+}
+
+fn polygons_geometry(mut polygons: Vec<Vec<VtLinearRing>>) -> Option<VtGeometry> {
+    match polygons.len() {
+        0 => None,
+        1 => polygons.pop().map(VtGeometry::Polygon),
+        _ => Some(VtGeometry::MultiPolygon(polygons)),
+    }
+}
+
+/// Edges of an open ring, including the closing one.
+fn edges(ring: &[VtPoint]) -> impl Iterator<Item = (&VtPoint, &VtPoint)> {
+    ring.iter().zip(ring.iter().cycle().skip(1))
+}
+
+fn signed_area(ring: &[VtPoint]) -> f64 {
+    edges(ring).map(|(a, b)| a.x * b.y - b.x * a.y).sum::<f64>() / 2.
+}
+
+/// Whether the open, positively oriented `ring` turns left or goes straight at every point,
+/// and around only once.
+fn is_convex(ring: &[VtPoint]) -> bool {
+    let edges: Vec<(f64, f64)> = edges(ring)
+        .map(|(a, b)| (b.x - a.x, b.y - a.y))
+        .filter(|&(dx, dy)| dx != 0. || dy != 0.)
+        .collect();
+    let mut turned = 0.;
+    for (&(ax, ay), &(bx, by)) in edges.iter().zip(edges.iter().cycle().skip(1)) {
+        let (cross, dot) = (ax * by - ay * bx, ax * bx + ay * by);
+        if cross < -1e-9 * ax.hypot(ay) * bx.hypot(by) {
+            return false;
+        }
+        turned += cross.atan2(dot);
+    }
+    turned < 2. * std::f64::consts::PI + 1e-6
+}
+
+/// Positive when `p` lies left of the edge `a -> b`, i.e. inside a positively oriented ring.
+#[inline]
+fn side(a: &VtPoint, b: &VtPoint, p: &VtPoint) -> f64 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+#[inline]
+fn lerp(a: &VtPoint, b: &VtPoint, t: f64) -> VtPoint {
+    VtPoint::new(a.x + t * (b.x - a.x), a.y + t * (b.y - a.y), 1.)
+}
+
+/// Clips `line` to the convex `ring`, returning the inner parts.
+fn clip_line(ring: &[VtPoint], line: &VtLineString, line_metrics: bool) -> Vec<VtLineString> {
+    let mut slices = Vec::new();
+    let new_slice = || VtLineString {
+        dist: line.dist,
+        ..Default::default()
+    };
+    let mut slice = new_slice();
+    let mut line_len = line.seg_start;
+    for w in line.elements.windows(2) {
+        let (a, b) = (&w[0], &w[1]);
+        let seg_len = if line_metrics {
+            (b.x - a.x).hypot(b.y - a.y)
+        } else {
+            0.
+        };
+        let (mut t0, mut t1) = (0f64, 1f64);
+        for (p, q) in edges(ring) {
+            let (fa, fb) = (side(p, q, a), side(p, q, b));
+            if fa < 0. && fb < 0. {
+                t0 = 1.;
+                t1 = 0.;
+                break;
+            } else if fa < 0. {
+                t0 = t0.max(fa / (fa - fb));
+            } else if fb < 0. {
+                t1 = t1.min(fa / (fa - fb));
+            }
+        }
+        if t0 <= t1 {
+            if slice.elements.is_empty() {
+                slice
+                    .elements
+                    .push(if t0 > 0. { lerp(a, b, t0) } else { *a });
+                if line_metrics {
+                    slice.seg_start = line_len + seg_len * t0;
+                }
+            }
+            slice
+                .elements
+                .push(if t1 < 1. { lerp(a, b, t1) } else { *b });
+            if line_metrics {
+                slice.seg_end = line_len + seg_len * t1;
+            }
+        }
+        if t0 > t1 || t1 < 1. {
+            if slice.elements.len() >= 2 {
+                slices.push(std::mem::replace(&mut slice, new_slice()));
+            } else {
+                slice = new_slice();
+            }
+        }
+        line_len += seg_len;
+    }
+    if slice.elements.len() >= 2 {
+        slices.push(slice);
+    }
+    slices
+}
+
+/// Clips a closed ring to the convex `mask` (Sutherland–Hodgman).
+///
+/// With ring metrics, each point keeps its distance along the perimeter of the source ring.
+fn clip_ring(mask: &[VtPoint], ring: &VtLinearRing) -> Option<VtLinearRing> {
+    let measured = ring.dist > 0. && ring.measures.len() == ring.elements.len();
+    let mut points: Vec<(VtPoint, Option<f64>)> = ring
+        .elements
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (*p, ring.measures.get(i).copied().flatten()))
+        .collect();
+    if points.len() > 1 && points.first().map(|p| p.0) == points.last().map(|p| p.0) {
+        points.pop();
+    }
+    let intersection = |(a, ma): &(VtPoint, Option<f64>), (b, mb): &(VtPoint, Option<f64>), t| {
+        let measure = ma
+            .zip(*mb)
+            .and_then(|(ma, mb)| edge_measure(ring.dist, (a, ma), (b, mb), t));
+        (lerp(a, b, t), measure)
+    };
+    for (p, q) in edges(mask) {
+        if points.is_empty() {
+            break;
+        }
+        let input = std::mem::take(&mut points);
+        for (a, b) in input.iter().zip(input.iter().cycle().skip(1)) {
+            let (fa, fb) = (side(p, q, &a.0), side(p, q, &b.0));
+            if fa >= 0. {
+                points.push(*a);
+                if fb < 0. {
+                    points.push(intersection(a, b, fa / (fa - fb)));
+                }
+            } else if fb >= 0. {
+                points.push(intersection(a, b, fa / (fa - fb)));
+            }
+        }
+    }
+    if points.len() < 3 {
+        return None;
+    }
+    points.push(points[0]);
+    let (elements, measures): (Vec<_>, Vec<_>) = points.into_iter().unzip();
+    let mut clipped = VtLinearRing {
+        area: signed_area(&elements).abs(),
+        elements,
+        dist: ring.dist,
+        seg_start: ring.seg_start,
+        seg_end: ring.seg_end,
+        measures: Vec::new(),
+    };
+    if measured {
+        clipped.measures = measures;
+        (clipped.seg_start, clipped.seg_end) = clipped.kept_range().unwrap_or_default();
+    }
+    Some(clipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(min: f64, max: f64) -> Vec<VtPoint> {
+        vec![
+            VtPoint::from_xy(min, min),
+            VtPoint::from_xy(max, min),
+            VtPoint::from_xy(max, max),
+            VtPoint::from_xy(min, max),
+        ]
+    }
+
+    #[test]
+    fn test_clip_line_to_convex_ring() {
+        let line = VtLineString::new(vec![
+            VtPoint::new(0., 0.5, 1.),
+            VtPoint::new(0.5, 0.5, 1.),
+            VtPoint::new(0.5, 1., 1.),
+        ]);
+        let parts = clip_line(&square(0.25, 0.75), &line, true);
+        assert_eq!(parts.len(), 1);
+        let xy: Vec<_> = parts[0].elements.iter().map(|p| (p.x, p.y)).collect();
+        assert_eq!(xy, [(0.25, 0.5), (0.5, 0.5), (0.5, 0.75)]);
+        assert_eq!(parts[0].seg_start, 0.25);
+        assert_eq!(parts[0].seg_end, 0.75);
+
+        let outside = VtLineString::new(vec![VtPoint::from_xy(0., 0.), VtPoint::from_xy(1., 0.)]);
+        assert!(clip_line(&square(0.25, 0.75), &outside, false).is_empty());
+    }
+
+    #[test]
+    fn test_clip_ring_to_convex_ring() {
+        let mut ring = square(0., 1.);
+        ring.push(ring[0]);
+        let ring = VtLinearRing::new(ring);
+        let mut diamond = vec![
+            VtPoint::from_xy(0.5, 0.),
+            VtPoint::from_xy(1.5, 1.),
+            VtPoint::from_xy(0.5, 2.),
+            VtPoint::from_xy(-0.5, 1.),
+        ];
+        let clipped = clip_ring(&diamond, &ring).unwrap();
+        assert_eq!(clipped.elements.first(), clipped.elements.last());
+        assert!((clipped.area - 0.75).abs() < 1e-12);
+
+        diamond.iter_mut().for_each(|p| p.x += 3.);
+        assert!(clip_ring(&diamond, &ring).is_none());
+    }
+
+    #[test]
+    fn test_is_convex() {
+        assert!(is_convex(&square(0., 1.)));
+        let mut collinear = square(0., 1.);
+        collinear.insert(1, VtPoint::from_xy(0.5, 0.));
+        assert!(is_convex(&collinear));
+        let mut dented = square(0., 1.);
+        dented.insert(1, VtPoint::from_xy(0.5, 0.25));
+        assert!(!is_convex(&dented));
+        // A pentagram turns left at every point but goes around twice.
+        let star: Vec<_> = (0..5)
+            .map(|i| {
+                let a = (i * 2) as f64 * 2. * std::f64::consts::PI / 5.;
+                VtPoint::from_xy(a.cos(), a.sin())
+            })
+            .collect();
+        assert!(signed_area(&star) > 0.);
+        assert!(!is_convex(&star));
+    }
+
+    #[test]
+    fn test_clip_ring_keeps_ring_metrics() {
+        let mut ring = square(0., 1.);
+        ring.push(ring[0]);
+        let mut ring = VtLinearRing::new(ring);
+        ring.measure();
+        let mask = [
+            VtPoint::from_xy(0.5, -1.),
+            VtPoint::from_xy(2., -1.),
+            VtPoint::from_xy(2., 2.),
+            VtPoint::from_xy(0.5, 2.),
+        ];
+        let clipped = clip_ring(&mask, &ring).unwrap();
+        assert_eq!(clipped.dist, 4.);
+        assert_eq!((clipped.seg_start, clipped.seg_end), (0.5, 2.5));
+    }
+}
@@ -0,0 +1,354 @@
+use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
+
+use geojson::{JsonObject, JsonValue, feature::Id};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    EmptyReason, GeoJSONVT, Options, OptionsError,
+    geojson_vt::to_id,
+    tile::{Tile, TileCoord, TilePropertyHook},
+    types::{BBox, VtFeature, VtGeometry},
+};
+
+/// Start of the data written by [`GeoJSONVT::serialize_index`], followed by the format version.
+const MAGIC: &[u8; 4] = b"GJVT";
+const VERSION: u32 = 2;
+
+/// Error loading an index with [`GeoJSONVT::deserialize_index`].
+#[derive(Debug)]
+pub enum DeserializeError {
+    /// The data does not start like the output of [`GeoJSONVT::serialize_index`].
+    InvalidHeader,
+    /// The data was written in another version of the format.
+    UnsupportedVersion(u32),
+    Decode(rmp_serde::decode::Error),
+    InvalidOptions(OptionsError),
+    /// A tile or id refers to a feature missing from the data.
+    MissingFeature(usize),
+    /// A feature refers to properties missing from the data.
+    MissingProperties(usize),
+    /// A tile lies outside the tile grid of the index.
+    InvalidTile {
+        z: u8,
+        x: u32,
+        y: u32,
+    },
+    /// The index was written with a [`Options::tile_property_hook`] and none was given to
+    /// [`GeoJSONVT::deserialize_index`], or the other way around.
+    TilePropertyHookMismatch,
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializeError::InvalidHeader => write!(f, "not a serialized index"),
+            DeserializeError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported index format version {version}, expected {VERSION}"
+            ),
+            DeserializeError::Decode(error) => write!(f, "invalid index data: {error}"),
+            DeserializeError::InvalidOptions(error) => error.fmt(f),
+            DeserializeError::MissingFeature(index) => {
+                write!(f, "invalid index data: missing feature {index}")
+            }
+            DeserializeError::MissingProperties(index) => {
+                write!(f, "invalid index data: missing properties {index}")
+            }
+            DeserializeError::InvalidTile { z, x, y } => {
+                write!(
+                    f,
+                    "invalid index data: tile {z}/{x}/{y} is outside the grid"
+                )
+            }
+            DeserializeError::TilePropertyHookMismatch => write!(
+                f,
+                "the tile property hook must be given exactly when the index was written with one"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DeserializeError::Decode(error) => Some(error),
+            DeserializeError::InvalidOptions(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A [`GeoJSONVT`] as written to bytes. Features and properties shared between tiles are
+/// stored once and referred to by their position.
+#[derive(Serialize, Deserialize)]
+struct Index {
+    options: Options,
+    /// Whether the index was written with [`Options::tile_property_hook`], which is not stored.
+    tile_property_hook: bool,
+    properties: Vec<Option<JsonObject>>,
+    features: Vec<Feature>,
+    source: Vec<usize>,
+    tiles: Vec<InternalTile>,
+    tile_coords: Vec<TileCoord>,
+    total: u32,
+    stats: HashMap<u8, u32>,
+    non_empty_stats: HashMap<u8, u32>,
+    read_only: bool,
+    feature_ids: Vec<(String, Vec<usize>)>,
+    evicted: Vec<u64>,
+    deferred: Vec<u64>,
+    input_features: usize,
+    min_feature_size: Option<f64>,
+    build_duration: Option<Duration>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Feature {
+    /// `Id` has no `Deserialize`, it is kept as the JSON string or number it serializes to.
+    id: Option<JsonValue>,
+    geometry: VtGeometry,
+    properties: usize,
+    bbox: Option<BBox>,
+    point_count: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct InternalTile {
+    z: u8,
+    x: u32,
+    y: u32,
+    features: Vec<usize>,
+    bbox: BBox,
+    tile: Tile,
+    empty_reason: Option<EmptyReason>,
+}
+
+/// Gives each distinct feature and properties object a position, in order of first use.
+#[derive(Default)]
+struct Tables {
+    properties: Vec<Option<JsonObject>>,
+    property_indices: HashMap<*const Option<JsonObject>, usize>,
+    features: Vec<Feature>,
+    feature_indices: HashMap<*const VtFeature, usize>,
+}
+
+impl Tables {
+    fn feature(&mut self, feature: &Arc<VtFeature>) -> usize {
+        if let Some(&index) = self.feature_indices.get(&Arc::as_ptr(feature)) {
+            return index;
+        }
+        let properties = *self
+            .property_indices
+            .entry(Arc::as_ptr(&feature.properties))
+            .or_insert_with(|| {
+                self.properties.push((*feature.properties).clone());
+                self.properties.len() - 1
+            });
+        self.features.push(Feature {
+            id: feature.id.as_ref().map(|id| match id {
+                Id::String(s) => JsonValue::String(s.clone()),
+                Id::Number(n) => JsonValue::Number(n.clone()),
+            }),
+            geometry: feature.geometry.clone(),
+            properties,
+            bbox: feature.bbox,
+            point_count: feature.point_count,
+        });
+        self.feature_indices
+            .insert(Arc::as_ptr(feature), self.features.len() - 1);
+        self.features.len() - 1
+    }
+    fn features(&mut self, features: &[Arc<VtFeature>]) -> Vec<usize> {
+        features
+            .iter()
+            .map(|feature| self.feature(feature))
+            .collect()
+    }
+}
+
+impl GeoJSONVT {
+    /// Writes the index to bytes, to be loaded back with [`GeoJSONVT::deserialize_index`]
+    /// instead of building it again.
+    ///
+    /// The options, the source features and the generated tiles are kept, including the tiles
+    /// generated on demand into the index. The tiles of the cache bounded by
+    /// [`Options::tile_cache_capacity`], the receivers of [`GeoJSONVT::watch_tile`], tile
+    /// metrics, [`GeoJSONVT::warnings`] and [`GeoJSONVT::conversion_report`] are not.
+    ///
+    /// Of the options, the callbacks [`Options::on_tile_created`] and
+    /// [`Options::tile_property_hook`] are not written. The index records whether a tile
+    /// property hook was set, and the same hook must be given back to
+    /// [`GeoJSONVT::deserialize_index`] so that the tiles generated after loading match those
+    /// generated before.
+    #[must_use = "`serialize_index` returns the serialized index"]
+    pub fn serialize_index(&self) -> Vec<u8> {
+        let mut tables = Tables::default();
+        let source = tables.features(&self.source);
+        let mut tiles: Vec<_> = self.tiles.iter().collect();
+        tiles.sort_by_key(|(id, _)| **id);
+        let tiles = tiles
+            .into_iter()
+            .map(|(_, tile)| InternalTile {
+                z: tile.z,
+                x: tile.x,
+                y: tile.y,
+                features: tables.features(&tile.source_feature),
+                bbox: tile.bbox,
+                tile: Tile::clone(&tile.tile),
+                empty_reason: tile.empty_reason,
+            })
+            .collect();
+        let mut feature_ids: Vec<_> = self
+            .feature_ids
+            .iter()
+            .map(|(key, features)| (key.clone(), tables.features(features)))
+            .collect();
+        feature_ids.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut evicted = Vec::from_iter(self.evicted.iter().copied());
+        evicted.sort();
+        let mut deferred = Vec::from_iter(self.deferred.iter().copied());
+        deferred.sort();
+        let index = Index {
+            options: self.options().clone(),
+            tile_property_hook: self.options().tile_property_hook.is_some(),
+            properties: tables.properties,
+            features: tables.features,
+            source,
+            tiles,
+            tile_coords: self.tile_coords.clone(),
+            total: self.total,
+            stats: self.stats.clone(),
+            non_empty_stats: self.non_empty_stats.clone(),
+            read_only: self.read_only,
+            feature_ids,
+            evicted,
+            deferred,
+            input_features: self.input_features,
+            min_feature_size: self.min_feature_size,
+            build_duration: self.build_duration,
+        };
+        let mut bytes = Vec::from(MAGIC);
+        bytes.extend(VERSION.to_le_bytes());
+        rmp_serde::encode::write(&mut bytes, &index).expect("an index always serializes");
+        bytes
+    }
+    /// Loads an index written by [`GeoJSONVT::serialize_index`].
+    ///
+    /// `tile_property_hook` replaces [`Options::tile_property_hook`], which is not written: it
+    /// must be given exactly when the index was written with one, failing with
+    /// [`DeserializeError::TilePropertyHookMismatch`] otherwise. [`Options::on_tile_created`]
+    /// is not written either and is left unset.
+    pub fn deserialize_index(
+        data: &[u8],
+        tile_property_hook: Option<TilePropertyHook>,
+    ) -> Result<Self, DeserializeError> {
+        let (Some(magic), Some(version)) = (data.get(..4), data.get(4..8)) else {
+            return Err(DeserializeError::InvalidHeader);
+        };
+        if magic != MAGIC {
+            return Err(DeserializeError::InvalidHeader);
+        }
+        let version = u32::from_le_bytes(version.try_into().unwrap());
+        if version != VERSION {
+            return Err(DeserializeError::UnsupportedVersion(version));
+        }
+        let mut index: Index =
+            rmp_serde::from_slice(&data[8..]).map_err(DeserializeError::Decode)?;
+        if index.tile_property_hook != tile_property_hook.is_some() {
+            return Err(DeserializeError::TilePropertyHookMismatch);
+        }
+        index.options.tile_property_hook = tile_property_hook;
+        index
+            .options
+            .validate()
+            .map_err(DeserializeError::InvalidOptions)?;
+        let max_zoom = index.options.tile_max_zoom();
+        if let Some(tile) = index
+            .tiles
+            .iter()
+            .find(|tile| tile.z > max_zoom || tile.x >= 1 << tile.z || tile.y >= 1 << tile.z)
+        {
+            let (z, x, y) = (tile.z, tile.x, tile.y);
+            return Err(DeserializeError::InvalidTile { z, x, y });
+        }
+
+        let properties: Vec<_> = index.properties.into_iter().map(Arc::new).collect();
+        let features = index
+            .features
+            .into_iter()
+            .map(|feature| {
+                let properties = properties
+                    .get(feature.properties)
+                    .ok_or(DeserializeError::MissingProperties(feature.properties))?;
+                Ok(Arc::new(VtFeature {
+                    id: feature.id.and_then(|id| match id {
+                        JsonValue::String(s) => Some(Id::String(s)),
+                        JsonValue::Number(n) => Some(Id::Number(n)),
+                        _ => None,
+                    }),
+                    geometry: feature.geometry,
+                    properties: properties.clone(),
+                    bbox: feature.bbox,
+                    point_count: feature.point_count,
+                }))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let resolve = |indices: Vec<usize>| {
+            indices
+                .into_iter()
+                .map(|i| {
+                    features
+                        .get(i)
+                        .cloned()
+                        .ok_or(DeserializeError::MissingFeature(i))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let mut geojsonvt = GeoJSONVT::try_from_source(Vec::new(), index.options)
+            .map_err(DeserializeError::InvalidOptions)?;
+        geojsonvt.tiles.clear();
+        for tile in index.tiles {
+            let mut internal = crate::tile::InternalTile::new(
+                &[],
+                tile.z,
+                tile.x,
+                tile.y,
+                &geojsonvt.tile_options,
+            );
+            internal.source_feature = resolve(tile.features)?.into();
+            internal.bbox = tile.bbox;
+            internal.tile = Arc::new(tile.tile);
+            internal.empty_reason = tile.empty_reason;
+            geojsonvt
+                .tiles
+                .insert(to_id(tile.z, tile.x, tile.y), internal);
+        }
+        geojsonvt.source = resolve(index.source)?;
+        geojsonvt.index_sources();
+        geojsonvt.feature_ids = index
+            .feature_ids
+            .into_iter()
+            .map(|(key, indices)| Ok((key, resolve(indices)?)))
+            .collect::<Result<_, DeserializeError>>()?;
+        geojsonvt.tile_coords = index.tile_coords;
+        geojsonvt.total = index.total;
+        geojsonvt.stats = index.stats;
+        geojsonvt.non_empty_stats = index.non_empty_stats;
+        geojsonvt.read_only = index.read_only;
+        geojsonvt.evicted = index
+            .evicted
+            .into_iter()
+            .filter(|id| geojsonvt.tiles.contains_key(id))
+            .collect();
+        geojsonvt.deferred = index
+            .deferred
+            .into_iter()
+            .filter(|id| geojsonvt.tiles.contains_key(id))
+            .collect();
+        geojsonvt.input_features = index.input_features;
+        geojsonvt.min_feature_size = index.min_feature_size;
+        geojsonvt.build_duration = index.build_duration;
+        Ok(geojsonvt)
+    }
+}
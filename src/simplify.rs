@@ -1,17 +1,18 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
 use crate::types::VtPoint;
 
 /// Simplifies a polyline using the [Douglas-Peucker algorithm](https://en.wikipedia.org/wiki/Ramer–Douglas–Peucker_algorithm)
 ///
 /// # Arguments
 /// * `points` - A mutable vector of [`VtPoint`] representing the original polyline.
-///              The z-coordinate will be modified to store simplification metrics.
+///   The z-coordinate will be modified to store simplification metrics.
 /// * `tolerance` - Simplification tolerance (squared value). Points with square distance
-///                less than this value may be removed.
+///   less than this value may be removed.
 ///
 /// # Examples
-/// ```ignore
-/// use geojsonvt::simplify::simplify;
-/// use geojsonvt::types::VtPoint;
+/// ```
+/// use geojsonvt::{VtPoint, simplify};
 ///
 /// let mut points = vec![
 ///     VtPoint::new(0.0, 0.0, 0.0),
@@ -24,13 +25,35 @@ use crate::types::VtPoint;
 /// simplify(&mut points, 0.5);
 /// ```
 pub fn simplify(points: &mut [VtPoint], tolerance: f64) {
-    let len = points.len() - 1;
+    simplify_with_forced(points, tolerance, &[]);
+}
+
+/// Same as [`simplify`], always keeping the points at the `forced` indices.
+///
+/// Forced points get a `z` of `f64::MAX` and split the polyline into sections simplified
+/// independently, as its endpoints do, so they are kept at every zoom along with the points
+/// around them that shape each section. Indices out of bounds are ignored.
+pub fn simplify_with_forced(points: &mut [VtPoint], tolerance: f64, forced: &[usize]) {
     if points.is_empty() {
         return;
     }
-    points.first_mut().unwrap().z = 1.;
-    points.last_mut().unwrap().z = 1.;
-    douglas_peucker(points, 0, len, tolerance * tolerance);
+    let len = points.len() - 1;
+    points.first_mut().unwrap().z = f64::MAX;
+    points.last_mut().unwrap().z = f64::MAX;
+    let mut anchors = Vec::with_capacity(forced.len() + 2);
+    anchors.push(0);
+    for &index in forced.iter().filter(|&&index| index <= len) {
+        points[index].z = f64::MAX;
+        anchors.push(index);
+    }
+    anchors.push(len);
+    anchors.sort_unstable();
+    anchors.dedup();
+    for section in anchors.windows(2) {
+        if section[1] - section[0] > 1 {
+            douglas_peucker(points, section[0], section[1], tolerance * tolerance);
+        }
+    }
 }
 
 fn douglas_peucker(points: &mut [VtPoint], first: usize, last: usize, sq_tolerance: f64) {
@@ -40,7 +63,7 @@ fn douglas_peucker(points: &mut [VtPoint], first: usize, last: usize, sq_toleran
     let mut min_pos_to_mid = (last - first) as i64;
 
     for i in first + 1..last {
-        let sq_dist = point_segment_dist(&points[i], &points[first], &points[last]);
+        let sq_dist = point_to_segment_sq_dist(&points[i], &points[first], &points[last]);
         if sq_dist > max_sq_dist {
             index = i;
             max_sq_dist = sq_dist;
@@ -63,7 +86,78 @@ fn douglas_peucker(points: &mut [VtPoint], first: usize, last: usize, sq_toleran
     }
 }
 
-fn point_segment_dist(p: &VtPoint, a: &VtPoint, b: &VtPoint) -> f64 {
+/// Simplifies a polyline using the [Visvalingam-Whyatt algorithm](https://en.wikipedia.org/wiki/Visvalingam–Whyatt_algorithm),
+/// which better keeps the shape of smooth curves than [`simplify`].
+///
+/// Points are removed by increasing area of the triangle they form with their remaining
+/// neighbours, the effective area of each point being the largest area removed up to it. Like
+/// the squared distance stored by [`simplify`], that area is stored in `z` for the points whose
+/// area exceeds the squared `tolerance`, and the endpoints get a `z` of `f64::MAX`, so the same
+/// `p.z > tolerance * tolerance` filter applies to the output of both.
+pub fn simplify_vw(points: &mut [VtPoint], tolerance: f64) {
+    if points.is_empty() {
+        return;
+    }
+    let len = points.len();
+    points[0].z = f64::MAX;
+    points[len - 1].z = f64::MAX;
+    let sq_tolerance = tolerance * tolerance;
+    let mut prev: Vec<usize> = (0..len).map(|i| i.wrapping_sub(1)).collect();
+    let mut next: Vec<usize> = (1..=len).collect();
+    let mut areas: Vec<f64> = (0..len).map(|_| f64::INFINITY).collect();
+    let mut heap = BinaryHeap::new();
+    for i in 1..len.saturating_sub(1) {
+        areas[i] = triangle_area(&points[i - 1], &points[i], &points[i + 1]);
+        heap.push(Candidate(areas[i], i));
+    }
+    let mut max_area: f64 = 0.;
+    while let Some(Candidate(area, i)) = heap.pop() {
+        // Skips the stale entries of points whose area changed after being queued.
+        if area != areas[i] {
+            continue;
+        }
+        max_area = max_area.max(area);
+        if max_area > sq_tolerance {
+            points[i].z = max_area;
+        }
+        areas[i] = f64::NAN;
+        let (p, n) = (prev[i], next[i]);
+        next[p] = n;
+        prev[n] = p;
+        for j in [p, n] {
+            if j != 0 && j != len - 1 {
+                areas[j] = triangle_area(&points[prev[j]], &points[j], &points[next[j]]);
+                heap.push(Candidate(areas[j], j));
+            }
+        }
+    }
+}
+
+/// Point queued by [`simplify_vw`], the smallest area first.
+struct Candidate(f64, usize);
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for Candidate {}
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0).then(other.1.cmp(&self.1))
+    }
+}
+
+fn triangle_area(a: &VtPoint, b: &VtPoint, c: &VtPoint) -> f64 {
+    ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)).abs() / 2.
+}
+
+/// Returns the squared distance from `p` to the segment `a`-`b`, ignoring `z`.
+pub fn point_to_segment_sq_dist(p: &VtPoint, a: &VtPoint, b: &VtPoint) -> f64 {
     let mut x = a.x;
     let mut y = a.y;
     let mut dx = b.x - x;
@@ -83,9 +177,17 @@ fn point_segment_dist(p: &VtPoint, a: &VtPoint, b: &VtPoint) -> f64 {
     dx * dx + dy * dy
 }
 
+/// Returns the distance from `p` to the segment `a`-`b`, ignoring `z`.
+pub fn point_to_segment_dist(p: &VtPoint, a: &VtPoint, b: &VtPoint) -> f64 {
+    point_to_segment_sq_dist(p, a, b).sqrt()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::simplify;
+    use super::{
+        point_to_segment_dist, point_to_segment_sq_dist, simplify, simplify_vw,
+        simplify_with_forced,
+    };
     use crate::types::VtPoint;
     fn create_points_from_array(points: &[[f64; 2]]) -> Vec<VtPoint> {
         points
@@ -94,6 +196,62 @@ mod tests {
             .collect::<Vec<_>>()
     }
     #[test]
+    fn test_point_to_segment_dist() {
+        let a = VtPoint::from_xy(0., 0.);
+        let b = VtPoint::from_xy(4., 0.);
+        assert_eq!(
+            point_to_segment_sq_dist(&VtPoint::from_xy(2., 3.), &a, &b),
+            9.
+        );
+        assert_eq!(point_to_segment_dist(&VtPoint::from_xy(2., 3.), &a, &b), 3.);
+        assert_eq!(point_to_segment_dist(&VtPoint::from_xy(7., 4.), &a, &b), 5.);
+        assert_eq!(
+            point_to_segment_dist(&VtPoint::from_xy(-3., 4.), &a, &b),
+            5.
+        );
+        assert_eq!(point_to_segment_dist(&VtPoint::from_xy(3., 4.), &a, &a), 5.);
+    }
+    #[test]
+    fn test_simplify_with_forced() {
+        let line = (0..9).map(|i| [i as f64, 0.]).collect::<Vec<_>>();
+        let mut points = create_points_from_array(&line);
+        simplify_with_forced(&mut points, 0.1, &[5, 5, 20]);
+        let z = points.iter().map(|p| p.z).collect::<Vec<_>>();
+        assert_eq!(z, [f64::MAX, 0., 0., 0., 0., f64::MAX, 0., 0., f64::MAX]);
+
+        // The forced point anchors the sections on either side of it.
+        let mut points =
+            create_points_from_array(&[[0., 0.], [1., 1.], [2., 0.], [3., 1.], [4., 0.]]);
+        simplify_with_forced(&mut points, 0.1, &[2]);
+        assert!(points.iter().all(|p| p.z > 0.));
+        let mut points =
+            create_points_from_array(&[[0., 0.], [1., 0.], [2., 5.], [3., 0.], [4., 0.]]);
+        simplify_with_forced(&mut points, 0.1, &[1]);
+        assert_eq!(points[1].z, f64::MAX);
+        assert_eq!(points[2].z, 25.);
+    }
+    #[test]
+    fn test_simplify_vw() {
+        let mut points =
+            create_points_from_array(&[[0., 0.], [1., 0.1], [2., 0.], [3., 2.], [4., 0.]]);
+        simplify_vw(&mut points, 0.5);
+        let z = points.iter().map(|p| p.z).collect::<Vec<_>>();
+        assert_eq!(z, [f64::MAX, 0., 2., 4., f64::MAX]);
+        let kept = |sq_tolerance: f64| points.iter().filter(|p| p.z > sq_tolerance).count();
+        assert_eq!(kept(0.25), 4);
+        assert_eq!(kept(3.), 3);
+
+        // A point keeps the largest area removed before it, even if its own is smaller.
+        let mut points = create_points_from_array(&[[0., 0.], [1., -2.], [2., 1.], [4., 0.]]);
+        simplify_vw(&mut points, 0.);
+        assert_eq!(points[1].z, 2.5);
+        assert_eq!(points[2].z, 2.5);
+
+        let mut single = create_points_from_array(&[[0., 0.]]);
+        simplify_vw(&mut single, 1.);
+        assert_eq!(single[0].z, f64::MAX);
+    }
+    #[test]
     fn test_simplify() {
         let raw_points = [
             [0.22455, 0.25015],
@@ -1,4 +1,4 @@
-use std::rc::Rc;
+use std::{collections::BTreeMap, io, sync::Arc, time::Duration};
 
 use geojson::{
     Feature, FeatureCollection, Geometry, JsonObject, JsonValue, PointType, Position, Value,
@@ -6,6 +6,10 @@ use geojson::{
 };
 use serde_json::Number;
 
+use crate::clip::{Boundary, clip};
+use crate::convert::{lat_to_mercator_y, lng_to_mercator_x, mercator_x_to_lng, mercator_y_to_lat};
+use crate::geojson_vt::EmptyReason;
+use crate::simplify::point_to_segment_dist;
 use crate::types::{
     BBox, VtFeature, VtGeometry, VtLineString, VtLinearRing, VtMultiLineString, VtMultiPoint,
     VtMultiPolygon, VtPoint, VtPolygon,
@@ -20,28 +24,232 @@ pub static EMPTY_TILE: Tile = {
         },
         point_count: 0,
         simplified_count: 0,
+        extent: 0,
+        coord: None,
+        origin: TileOrigin::TopLeft,
+        y_axis: YAxis::ScreenCoords,
+        layers: Vec::new(),
     }
 };
 #[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tile {
     pub feature_collection: FeatureCollection,
     pub point_count: u32,
     pub simplified_count: u32,
+    /// Size of the tile in the units of its coordinates, 0 for the shared empty tile returned
+    /// when there is no content.
+    pub extent: u32,
+    /// Position of the tile as requested, `None` for the shared empty tile.
+    pub coord: Option<TileCoord>,
+    /// Where the coordinates of the tile are measured from, see [`crate::Options::origin`].
+    pub origin: TileOrigin,
+    /// Direction of the y coordinates of the tile, see [`crate::Options::y_axis`].
+    pub y_axis: YAxis,
+    /// Features of each layer, by name, with [`crate::Options::layer_by_property`], which
+    /// leaves `feature_collection` empty. The layer of the features without the property is
+    /// named `None`, and comes first. Empty otherwise, see [`Tile::features`].
+    pub layers: Vec<(Option<String>, FeatureCollection)>,
 }
 
-#[derive(Debug)]
+impl Tile {
+    /// A copy of the tile without its features or layers, keeping its counts and position.
+    pub(crate) fn without_features(&self) -> Tile {
+        Tile {
+            feature_collection: FeatureCollection::default(),
+            point_count: self.point_count,
+            simplified_count: self.simplified_count,
+            extent: self.extent,
+            coord: self.coord,
+            origin: self.origin,
+            y_axis: self.y_axis,
+            layers: Vec::new(),
+        }
+    }
+    /// Whether this is the shared empty tile returned when there is no content at the
+    /// requested coordinates, rather than a tile generated by the index.
+    pub fn is_placeholder(&self) -> bool {
+        self.coord.is_none()
+    }
+    /// Every feature of the tile, those of its [`Tile::layers`] included, in draw order.
+    pub fn features(&self) -> impl Iterator<Item = &Feature> {
+        let layers = self.layers.iter().flat_map(|(_, layer)| &layer.features);
+        self.feature_collection.features.iter().chain(layers)
+    }
+    /// Serializes the tile's features as a GeoJSON `FeatureCollection` string, see
+    /// [`Tile::to_geojson_writer`].
+    #[must_use = "`to_geojson_string` returns the serialized tile"]
+    pub fn to_geojson_string(&self) -> String {
+        String::from_utf8(self.to_geojson_bytes()).expect("JSON is always UTF-8")
+    }
+    /// Serializes the tile's features as GeoJSON into a byte buffer, see
+    /// [`Tile::to_geojson_writer`].
+    #[must_use = "`to_geojson_bytes` returns the serialized tile"]
+    pub fn to_geojson_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.to_geojson_writer(&mut bytes)
+            .expect("a FeatureCollection always serializes");
+        bytes
+    }
+    /// Streams the tile's features as GeoJSON into `writer`.
+    ///
+    /// A tile with [`Tile::layers`] is written as an array of one `FeatureCollection` per
+    /// layer, each with its name as a `"layer"` member, `null` for the features without the
+    /// property.
+    pub fn to_geojson_writer<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        if self.layers.is_empty() {
+            return serde_json::to_writer(writer, &self.feature_collection)
+                .map_err(io::Error::from);
+        }
+        for (i, (name, layer)) in self.layers.iter().enumerate() {
+            writer.write_all(if i == 0 { b"[" } else { b"," })?;
+            writer.write_all(br#"{"type":"FeatureCollection","layer":"#)?;
+            serde_json::to_writer(&mut writer, name)?;
+            writer.write_all(br#","features":"#)?;
+            serde_json::to_writer(&mut writer, &layer.features)?;
+            writer.write_all(b"}")?;
+        }
+        writer.write_all(b"]")
+    }
+    /// Converts `(px, py)`, in tile coordinates, to `(lng, lat)` in WGS-84 degrees. `None`
+    /// without a `coord`.
+    pub fn pixel_to_lnglat(&self, px: f64, py: f64) -> Option<(f64, f64)> {
+        let coord = self.coord?;
+        let z2 = (1u64 << coord.z) as f64;
+        let extent = self.extent as f64;
+        let (px, py) = self.top_left_coords(px, py);
+        Some((
+            mercator_x_to_lng((coord.x as f64 + px / extent) / z2),
+            mercator_y_to_lat((coord.y as f64 + py / extent) / z2),
+        ))
+    }
+    /// Converts `(lng, lat)`, in WGS-84 degrees, to `(px, py)` in tile coordinates, unrounded.
+    /// `None` without a `coord`.
+    pub fn lnglat_to_pixel(&self, lng: f64, lat: f64) -> Option<(f64, f64)> {
+        let coord = self.coord?;
+        let z2 = (1u64 << coord.z) as f64;
+        let extent = self.extent as f64;
+        Some(self.output_coords(
+            (lng_to_mercator_x(lng) * z2 - coord.x as f64) * extent,
+            (lat_to_mercator_y(lat) * z2 - coord.y as f64) * extent,
+        ))
+    }
+    /// Converts `(px, py)`, measured from the top-left corner with y down, to the coordinates
+    /// of the tile, see [`Tile::origin`] and [`Tile::y_axis`].
+    fn output_coords(&self, px: f64, py: f64) -> (f64, f64) {
+        let offset = self.origin.offset(self.extent);
+        let py = match self.y_axis {
+            YAxis::ScreenCoords => py,
+            YAxis::CartesianCoords => self.extent as f64 - py,
+        };
+        (px - offset, py - offset)
+    }
+    /// Inverse of [`Tile::output_coords`].
+    fn top_left_coords(&self, x: f64, y: f64) -> (f64, f64) {
+        let offset = self.origin.offset(self.extent);
+        let (x, y) = (x + offset, y + offset);
+        match self.y_axis {
+            YAxis::ScreenCoords => (x, y),
+            YAxis::CartesianCoords => (x, self.extent as f64 - y),
+        }
+    }
+    /// Returns the features under `(px, py)`, in tile coordinates, in draw order.
+    ///
+    /// Points and lines match within `radius` of the position. Polygons match when it lies
+    /// inside them, by the even-odd rule over all their rings so that holes are left out, or
+    /// within `radius` of one of their rings.
+    #[must_use = "`query_point` returns the matched features"]
+    pub fn query_point(&self, px: f64, py: f64, radius: f64) -> Vec<&Feature> {
+        let p = VtPoint::from_xy(px, py);
+        self.features()
+            .filter(|feature| {
+                let geometry = feature.geometry.as_ref();
+                geometry.is_some_and(|geometry| hits(&geometry.value, &p, radius))
+            })
+            .collect()
+    }
+}
+
+/// Whether `value` lies within `radius` of `p`, or contains it for polygons, see
+/// [`Tile::query_point`].
+fn hits(value: &Value, p: &VtPoint, radius: f64) -> bool {
+    let point = |position: &Position| (position[0] - p.x).hypot(position[1] - p.y) <= radius;
+    let line = |positions: &[Position]| {
+        let points: Vec<_> = positions
+            .iter()
+            .map(|position| VtPoint::from_xy(position[0], position[1]))
+            .collect();
+        match points.as_slice() {
+            [only] => point_to_segment_dist(p, only, only) <= radius,
+            _ => points
+                .windows(2)
+                .any(|w| point_to_segment_dist(p, &w[0], &w[1]) <= radius),
+        }
+    };
+    let polygon = |rings: &[Vec<Position>]| {
+        let crossings = rings
+            .iter()
+            .flat_map(|ring| ring.windows(2))
+            .filter(|w| {
+                let (a, b) = (&w[0], &w[1]);
+                (a[1] > p.y) != (b[1] > p.y)
+                    && p.x < a[0] + (p.y - a[1]) / (b[1] - a[1]) * (b[0] - a[0])
+            })
+            .count();
+        crossings % 2 == 1 || rings.iter().any(|ring| line(ring))
+    };
+    match value {
+        Value::Point(position) => point(position),
+        Value::MultiPoint(positions) => positions.iter().any(point),
+        Value::LineString(positions) => line(positions),
+        Value::MultiLineString(lines) => lines.iter().any(|positions| line(positions)),
+        Value::Polygon(rings) => polygon(rings),
+        Value::MultiPolygon(polygons) => polygons.iter().any(|rings| polygon(rings)),
+        Value::GeometryCollection(geometries) => geometries
+            .iter()
+            .any(|geometry| hits(&geometry.value, p, radius)),
+    }
+}
+
+/// Settings shared by every tile of an index, built once from its [`crate::Options`].
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TileOptions {
+    pub(crate) extent: u32,
+    /// Line and polygon tolerances of each zoom from 0, in the projected 0..1 space.
+    pub(crate) tolerances: Vec<(f64, f64)>,
+    pub(crate) line_metrics: bool,
+    pub(crate) remove_collinear: bool,
+    pub(crate) remove_slivers: bool,
+    pub(crate) explode_multipoints: bool,
+    pub(crate) suffix_numeric_ids: bool,
+    pub(crate) origin: TileOrigin,
+    pub(crate) y_axis: YAxis,
+    pub(crate) feature_order: FeatureOrder,
+    pub(crate) clip_to_extent: bool,
+    pub(crate) sort_features: bool,
+    pub(crate) property_hook: Option<TilePropertyHook>,
+    pub(crate) number_style: NumberStyle,
+    pub(crate) layer_property: Option<String>,
+}
+
+/// A tile of the index, with the projected source features it was generated from.
+#[derive(Debug, Clone)]
 pub struct InternalTile {
-    pub x: u32,
-    pub y: u32,
-    pub z: u8,
-    extent: u16,
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+    pub(crate) z: u8,
     z2: f64,
-    tolerance: f64,
-    sq_tolerance: f64,
-    line_metrics: bool,
-    pub source_feature: Vec<Rc<VtFeature>>,
-    pub bbox: BBox,
-    pub tile: Tile,
+    line_tolerance: f64,
+    polygon_sq_tolerance: f64,
+    options: Arc<TileOptions>,
+    pub(crate) source_feature: Arc<[Arc<VtFeature>]>,
+    pub(crate) bbox: BBox,
+    pub(crate) tile: Arc<Tile>,
+    /// Why `tile` was generated without features, `None` when it has some.
+    pub(crate) empty_reason: Option<EmptyReason>,
+    /// Serializations of `tile` cached by [`crate::GeoJSONVT::tile_bytes`].
+    #[cfg(feature = "compress")]
+    pub(crate) encoded: Vec<(crate::Encoding, Arc<[u8]>)>,
 }
 impl PartialEq for InternalTile {
     fn eq(&self, other: &Self) -> bool {
@@ -49,43 +257,247 @@ impl PartialEq for InternalTile {
     }
 }
 impl InternalTile {
-    pub fn new(
-        source_feature: &[Rc<VtFeature>],
+    pub(crate) fn new(
+        source_feature: &[Arc<VtFeature>],
         z: u8,
         x: u32,
         y: u32,
-        extent: u16,
-        tolerance: f64,
-        line_metrics: bool,
+        options: &Arc<TileOptions>,
     ) -> InternalTile {
+        let (line_tolerance, polygon_tolerance) = options
+            .tolerances
+            .get(z as usize)
+            .copied()
+            .unwrap_or_default();
         let mut tile = Self {
             x,
             y,
             z,
-            extent,
             z2: (1u32 << z) as f64,
-            tolerance,
-            sq_tolerance: tolerance * tolerance,
-            line_metrics,
-            source_feature: vec![],
+            line_tolerance,
+            polygon_sq_tolerance: polygon_tolerance * polygon_tolerance,
+            options: options.clone(),
+            source_feature: Arc::default(),
             bbox: Default::default(),
-            tile: Tile {
+            tile: Arc::new(Tile {
                 feature_collection: FeatureCollection::default(),
                 point_count: 0,
                 simplified_count: 0,
-            },
+                extent: options.extent,
+                coord: Some(TileCoord::new(x, y, z)),
+                origin: options.origin,
+                y_axis: options.y_axis,
+                layers: Vec::new(),
+            }),
+            empty_reason: None,
+            #[cfg(feature = "compress")]
+            encoded: Vec::new(),
         };
+        tile.track(source_feature);
+        let clipped;
+        let output_features = if options.clip_to_extent {
+            clipped = tile.clipped_to_extent(source_feature);
+            &clipped[..]
+        } else {
+            source_feature
+        };
+        let clipped_away = output_features.is_empty();
+        let mut output_features: Vec<_> = output_features.iter().collect();
+        if options.sort_features {
+            output_features.sort_by(|a, b| a.cmp_by_id(b));
+        }
+        for feature in output_features {
+            tile.add_feature(&feature.geometry, &feature.properties, &feature.id);
+        }
+        if tile.tile.feature_collection.features.is_empty() {
+            tile.empty_reason = Some(if source_feature.is_empty() {
+                EmptyReason::OutsideData
+            } else if clipped_away {
+                EmptyReason::ClippedToExtent
+            } else {
+                EmptyReason::FilteredByTolerance
+            });
+        }
+        // Named before the hook, which may strip the property.
+        let layer_names = options.layer_property.as_ref().map(|key| {
+            let features = &tile.tile.feature_collection.features;
+            Vec::from_iter(features.iter().map(|feature| layer_name(feature, key)))
+        });
+        if let Some(TilePropertyHook(hook)) = &options.property_hook {
+            let empty = JsonObject::new();
+            for feature in &mut tile.tile_mut().feature_collection.features {
+                let properties = feature.properties.as_ref().unwrap_or(&empty);
+                if let Some(properties) = hook(z, x, y, properties) {
+                    feature.properties = Some(properties);
+                }
+            }
+        }
+        match layer_names {
+            Some(names) => tile.group_layers(names),
+            None => options
+                .feature_order
+                .sort(&mut tile.tile_mut().feature_collection.features),
+        }
+        tile
+    }
+
+    /// Moves the features of the tile into the layers named by `names`, sorting each layer in
+    /// `feature_order`.
+    fn group_layers(&mut self, names: Vec<Option<String>>) {
+        let mut layers: BTreeMap<Option<String>, Vec<Feature>> = BTreeMap::new();
+        let features = std::mem::take(&mut self.tile_mut().feature_collection.features);
+        for (name, feature) in names.into_iter().zip(features) {
+            layers.entry(name).or_default().push(feature);
+        }
+        for (name, mut features) in layers {
+            self.options.feature_order.sort(&mut features);
+            let layer = FeatureCollection {
+                bbox: None,
+                features,
+                foreign_members: None,
+            };
+            self.tile_mut().layers.push((name, layer));
+        }
+    }
+
+    /// Creates a tile that only tracks the bounds and point count of `source_feature`, used to
+    /// route features below `min_zoom` without generating any output.
+    pub(crate) fn new_routing(
+        source_feature: &[Arc<VtFeature>],
+        z: u8,
+        x: u32,
+        y: u32,
+        options: &Arc<TileOptions>,
+    ) -> InternalTile {
+        let mut tile = Self::new(&[], z, x, y, options);
+        tile.track(source_feature);
+        tile
+    }
+
+    /// The output of the tile, copied first if a clone of the index shares it.
+    fn tile_mut(&mut self) -> &mut Tile {
+        Arc::make_mut(&mut self.tile)
+    }
+    fn push_feature(&mut self, feature: Feature) {
+        self.tile_mut().feature_collection.features.push(feature);
+    }
+
+    /// Adds the bounds and point count of `source_feature` to the tile, without generating any
+    /// output for them.
+    pub(crate) fn track(&mut self, source_feature: &[Arc<VtFeature>]) {
         for feature in source_feature {
-            let geometry = &feature.geometry;
-            let properties = &feature.properties;
-            let id = &feature.id;
-            tile.tile.point_count += &feature.point_count;
-            tile.add_feature(geometry, properties, id);
+            self.tile_mut().point_count += feature.point_count;
             if let Some(bbox) = &feature.bbox {
-                tile.bbox.merge(bbox);
+                self.bbox.merge(bbox);
             }
         }
-        tile
+    }
+
+    /// Clips `features` to the bounds of the tile without its buffer, see
+    /// [`crate::Options::clip_output_to_extent`].
+    fn clipped_to_extent(&self, features: &[Arc<VtFeature>]) -> Vec<Arc<VtFeature>> {
+        let (x0, y0) = (self.x as f64 / self.z2, self.y as f64 / self.z2);
+        let (x1, y1) = ((self.x + 1) as f64 / self.z2, (self.y + 1) as f64 / self.z2);
+        let BBox {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        } = self.bbox;
+        let both = Boundary::Both;
+        let clipped = clip::<0>(
+            features,
+            x0,
+            x1,
+            min_x,
+            max_x,
+            self.options.line_metrics,
+            self.options.remove_slivers,
+            both,
+        );
+        clip::<1>(
+            &clipped,
+            y0,
+            y1,
+            min_y,
+            max_y,
+            self.options.line_metrics,
+            self.options.remove_slivers,
+            both,
+        )
+    }
+
+    pub fn coord(&self) -> TileCoord {
+        TileCoord::new(self.x, self.y, self.z)
+    }
+    /// Bounds of the features clipped to this tile, in projected coordinates.
+    pub fn bbox(&self) -> &BBox {
+        &self.bbox
+    }
+    /// Number of features in the generated tile.
+    pub fn feature_count(&self) -> usize {
+        self.tile.features().count()
+    }
+    /// Number of source features kept to generate the tiles below this one, 0 once they have
+    /// been split into its children.
+    pub fn source_feature_count(&self) -> usize {
+        self.source_feature.len()
+    }
+    /// Projected source features kept to generate the tiles below this one.
+    pub fn source_features(&self) -> &[Arc<VtFeature>] {
+        &self.source_feature
+    }
+    pub fn tile(&self) -> &Tile {
+        &self.tile
+    }
+
+    /// Generates the output of the tile from its source features, see
+    /// [`InternalTile::tile_with_extent`].
+    pub(crate) fn generate(&mut self) {
+        let tile = Self::new(&self.source_feature, self.z, self.x, self.y, &self.options);
+        (self.tile, self.empty_reason) = (tile.tile, tile.empty_reason);
+    }
+
+    /// Transforms the source features of this tile again with `extent` instead of the extent
+    /// the index was built with, keeping the same simplification.
+    ///
+    /// Only tiles that still hold their source features, the leaves of the index, produce any
+    /// output; the others return an empty tile.
+    #[must_use = "`tile_with_extent` builds a new tile, leaving the cached one unchanged"]
+    pub fn tile_with_extent(&self, extent: u32) -> Tile {
+        let options = TileOptions {
+            extent,
+            ..TileOptions::clone(&self.options)
+        };
+        let tile = Self::new(
+            &self.source_feature,
+            self.z,
+            self.x,
+            self.y,
+            &Arc::new(options),
+        );
+        Arc::unwrap_or_clone(tile.tile)
+    }
+
+    /// Returns the coordinates of the four children of this tile at `z + 1`,
+    /// in the order top-left, top-right, bottom-left, bottom-right.
+    pub fn child_coords(&self) -> [TileCoord; 4] {
+        let (x, y, z) = (self.x * 2, self.y * 2, self.z + 1);
+        [
+            TileCoord::new(x, y, z),
+            TileCoord::new(x + 1, y, z),
+            TileCoord::new(x, y + 1, z),
+            TileCoord::new(x + 1, y + 1, z),
+        ]
+    }
+
+    /// Returns the coordinate of the parent tile, or `None` for the root tile.
+    pub fn parent_coord(&self) -> Option<TileCoord> {
+        if self.z == 0 {
+            None
+        } else {
+            Some(TileCoord::new(self.x / 2, self.y / 2, self.z - 1))
+        }
     }
 
     pub fn add_feature(
@@ -113,11 +525,11 @@ impl InternalTile {
     fn add_point(&mut self, value: &VtPoint, properties: &Option<JsonObject>, id: &Option<Id>) {
         let coords = self.transform_point(value);
         let geometry = Some(Geometry::new(Value::Point(coords)));
-        self.tile.feature_collection.features.push(Feature {
+        self.push_feature(Feature {
             bbox: None,
             geometry,
             id: id.clone(),
-            properties: properties.as_ref().map(|p| p.clone()),
+            properties: properties.clone(),
             // TODO: Avoid clone
             // properties: None,
             foreign_members: None,
@@ -129,28 +541,55 @@ impl InternalTile {
         properties: &Option<JsonObject>,
         id: &Option<Id>,
     ) {
-        let multi_coords: Vec<Position> = points.iter().map(|p| self.transform_point(p)).collect();
-
+        if self.options.explode_multipoints {
+            for (point, &index) in points.elements.iter().zip(&points.indices) {
+                let coords = self.transform_point(point);
+                self.push_feature(Feature {
+                    bbox: None,
+                    geometry: Some(Geometry::new(Value::Point(coords))),
+                    id: self.exploded_id(id, index),
+                    properties: properties.clone(),
+                    foreign_members: None,
+                });
+            }
+            return;
+        }
+        let multi_coords: Vec<Position> = points
+            .elements
+            .iter()
+            .map(|p| self.transform_point(p))
+            .collect();
         match multi_coords.len() {
             0 => (),
-            1 => self.tile.feature_collection.features.push(Feature {
+            1 => self.push_feature(Feature {
                 bbox: None,
                 geometry: Some(Geometry::new(Value::Point(multi_coords[0].clone()))),
                 id: id.clone(),
                 // properties: None,
-                properties: properties.as_ref().map(|p| p.clone()),
+                properties: properties.clone(),
                 foreign_members: None,
             }),
-            _ => self.tile.feature_collection.features.push(Feature {
+            _ => self.push_feature(Feature {
                 bbox: None,
                 geometry: Some(Geometry::new(Value::MultiPoint(multi_coords))),
                 id: id.clone(),
                 // properties: None,
-                properties: properties.as_ref().map(|p| p.clone()),
+                properties: properties.clone(),
                 foreign_members: None,
             }),
         }
     }
+    /// Id of the point at `index` of the source `MultiPoint` once exploded, see
+    /// [`crate::Options::explode_multipoints`].
+    fn exploded_id(&self, id: &Option<Id>, index: u32) -> Option<Id> {
+        match id {
+            Some(Id::String(id)) => Some(Id::String(format!("{id}-{index}"))),
+            Some(Id::Number(id)) if self.options.suffix_numeric_ids => {
+                Some(Id::String(format!("{id}-{index}")))
+            }
+            _ => None,
+        }
+    }
     fn add_line_string(
         &mut self,
         line: &VtLineString,
@@ -161,44 +600,32 @@ impl InternalTile {
         if coords.is_empty() {
             return;
         }
-        if self.line_metrics {
-            let mut new_properties = properties.as_ref().map(|p| p.clone()).unwrap_or_default();
-            let start = line.seg_start / line.dist;
-            new_properties.insert(
-                "mapbox_clip_start".to_string(),
-                if start.fract() == 0.0 {
-                    JsonValue::Number(Number::from(start as i64))
-                } else {
-                    JsonValue::Number(Number::from_f64(start).unwrap())
-                },
-            );
-            let end = line.seg_end / line.dist;
-            new_properties.insert(
-                "mapbox_clip_end".to_string(),
-                if end.fract() == 0.0 {
-                    JsonValue::Number(Number::from(end as i64))
-                } else {
-                    JsonValue::Number(Number::from_f64(end).unwrap())
-                },
-            );
-            self.tile.feature_collection.features.push(Feature {
-                bbox: None,
-                geometry: Some(Geometry::new(Value::LineString(coords))),
-                id: id.clone(),
-                // properties: None,
-                properties: Some(new_properties),
-                foreign_members: None,
-            });
+        // Lines never cut by clipping keep a zero `seg_end`, they span the whole line and get
+        // no metrics.
+        let seg_end = if line.seg_end == 0. {
+            line.dist
         } else {
-            self.tile.feature_collection.features.push(Feature {
-                bbox: None,
-                geometry: Some(Geometry::new(Value::LineString(coords))),
-                id: id.clone(),
-                // properties: None,
-                properties: properties.as_ref().map(|p| p.clone()),
-                foreign_members: None,
-            });
-        }
+            line.seg_end
+        };
+        let (start, end) = (line.seg_start / line.dist, seg_end / line.dist);
+        let properties = if self.options.line_metrics && (start != 0. || end != 1.) {
+            Some(with_clip_metrics(
+                properties,
+                start,
+                end,
+                self.options.number_style,
+            ))
+        } else {
+            properties.clone()
+        };
+        self.push_feature(Feature {
+            bbox: None,
+            geometry: Some(Geometry::new(Value::LineString(coords))),
+            id: id.clone(),
+            // properties: None,
+            properties,
+            foreign_members: None,
+        });
     }
     fn add_multi_line_string(
         &mut self,
@@ -206,7 +633,7 @@ impl InternalTile {
         properties: &Option<JsonObject>,
         id: &Option<Id>,
     ) {
-        let tolerance = self.tolerance;
+        let tolerance = self.line_tolerance;
         let multi_coords: Vec<_> = multi_lines
             .iter()
             .filter(|line| line.dist > tolerance)
@@ -214,20 +641,20 @@ impl InternalTile {
             .collect();
         match multi_coords.len() {
             0 => (),
-            1 => self.tile.feature_collection.features.push(Feature {
+            1 => self.push_feature(Feature {
                 bbox: None,
                 geometry: Some(Geometry::new(Value::LineString(multi_coords[0].clone()))),
                 id: id.clone(),
                 // properties: None,
-                properties: properties.as_ref().map(|p| p.clone()),
+                properties: properties.clone(),
                 foreign_members: None,
             }),
-            _ => self.tile.feature_collection.features.push(Feature {
+            _ => self.push_feature(Feature {
                 bbox: None,
                 geometry: Some(Geometry::new(Value::MultiLineString(multi_coords))),
                 id: id.clone(),
                 // properties: None,
-                properties: properties.as_ref().map(|p| p.clone()),
+                properties: properties.clone(),
                 foreign_members: None,
             }),
         }
@@ -240,22 +667,53 @@ impl InternalTile {
     ) {
         let coords = self.transform_polygon(polygon);
         if !coords.is_empty() {
-            self.tile.feature_collection.features.push(Feature {
+            let properties = match self.ring_metrics(polygon) {
+                Some((start, end)) => Some(with_clip_metrics(
+                    properties,
+                    start,
+                    end,
+                    self.options.number_style,
+                )),
+                None => properties.clone(),
+            };
+            self.push_feature(Feature {
                 bbox: None,
                 geometry: Some(Geometry::new(Value::Polygon(coords))),
                 id: id.clone(),
                 // properties: None,
-                properties: properties.as_ref().map(|p| p.clone()),
+                properties,
                 foreign_members: None,
             })
         }
     }
+    /// Where the outer ring of a polygon cut by clipping starts and ends along its perimeter,
+    /// for [`crate::Options::ring_metrics`].
+    fn ring_metrics(&self, polygon: &VtPolygon) -> Option<(f64, f64)> {
+        let ring = polygon.first()?;
+        if !self.options.line_metrics
+            || ring.dist == 0.
+            || (ring.seg_start == 0. && ring.seg_end == 0.)
+        {
+            return None;
+        }
+        Some((ring.seg_start / ring.dist, ring.seg_end / ring.dist))
+    }
     fn add_multi_polygon(
         &mut self,
         polygons: &VtMultiPolygon,
         properties: &Option<JsonObject>,
         id: &Option<Id>,
     ) {
+        // Each polygon gets the metrics of its own outer ring.
+        if polygons
+            .iter()
+            .any(|polygon| self.ring_metrics(polygon).is_some())
+        {
+            for polygon in polygons {
+                self.add_polygon(polygon, properties, id);
+            }
+            return;
+        }
         let multi_coords: Vec<_> = polygons
             .iter()
             .filter_map(|polygon| {
@@ -269,20 +727,20 @@ impl InternalTile {
             .collect();
         match multi_coords.len() {
             0 => (),
-            1 => self.tile.feature_collection.features.push(Feature {
+            1 => self.push_feature(Feature {
                 bbox: None,
                 geometry: Some(Geometry::new(Value::Polygon(multi_coords[0].clone()))),
                 id: id.clone(),
                 // properties: None,
-                properties: properties.as_ref().map(|p| p.clone()),
+                properties: properties.clone(),
                 foreign_members: None,
             }),
-            _ => self.tile.feature_collection.features.push(Feature {
+            _ => self.push_feature(Feature {
                 bbox: None,
                 geometry: Some(Geometry::new(Value::MultiPolygon(multi_coords))),
                 id: id.clone(),
                 // properties: None,
-                properties: properties.as_ref().map(|p| p.clone()),
+                properties: properties.clone(),
                 foreign_members: None,
             }),
         }
@@ -299,44 +757,117 @@ impl InternalTile {
     }
 
     fn transform_point(&mut self, p: &VtPoint) -> PointType {
-        self.tile.simplified_count += 1;
-        let x = ((p.x * self.z2 - self.x as f64) * self.extent as f64).round();
-        let y = ((p.y * self.z2 - self.y as f64) * self.extent as f64).round();
+        self.tile_mut().simplified_count += 1;
+        let x = ((p.x * self.z2 - self.x as f64) * self.options.extent as f64).round();
+        let y = ((p.y * self.z2 - self.y as f64) * self.options.extent as f64).round();
+        let (x, y) = self.tile.output_coords(x, y);
         vec![x, y]
     }
     fn transform_line_string(&mut self, line: &VtLineString) -> Vec<Position> {
-        if line.dist < self.tolerance {
+        if line.dist < self.line_tolerance {
             return vec![];
         }
-        let tolerance = self.tolerance;
-        line.elements
+        let tolerance = self.line_tolerance;
+        let coords = line
+            .elements
             .iter()
             .filter(|p| p.z > tolerance)
             .map(|p| self.transform_point(p))
-            .collect()
+            .collect();
+        self.collinear_removed(coords, 2)
     }
     fn transform_line_ring(&mut self, ring: &VtLinearRing) -> Vec<Position> {
-        if ring.area < self.sq_tolerance {
+        if ring.area < self.polygon_sq_tolerance {
             return vec![];
         }
-        let sq_tolerance = self.sq_tolerance;
-        ring.elements
+        let sq_tolerance = self.polygon_sq_tolerance;
+        let coords = ring
+            .elements
             .iter()
             .filter(|p| p.z > sq_tolerance)
             .map(|p| self.transform_point(p))
-            .collect()
+            .collect();
+        self.collinear_removed(coords, 4)
+    }
+    /// Drops interior points lying between their kept predecessor and their successor on the
+    /// line through both, unless fewer than `min_len` points would remain. Points where the
+    /// line turns back are kept, as are the endpoints and thus ring closure.
+    fn collinear_removed(&mut self, coords: Vec<Position>, min_len: usize) -> Vec<Position> {
+        if !self.options.remove_collinear || coords.len() < 3 {
+            return coords;
+        }
+        let mut kept: Vec<Position> = Vec::with_capacity(coords.len());
+        kept.push(coords[0].clone());
+        for w in coords.windows(2).skip(1) {
+            let (a, b, c) = (kept.last().unwrap(), &w[0], &w[1]);
+            let cross = (b[0] - a[0]) * (c[1] - b[1]) - (b[1] - a[1]) * (c[0] - b[0]);
+            let dot = (b[0] - a[0]) * (c[0] - b[0]) + (b[1] - a[1]) * (c[1] - b[1]);
+            if cross != 0. || dot < 0. {
+                kept.push(b.clone());
+            }
+        }
+        kept.push(coords[coords.len() - 1].clone());
+        if kept.len() < min_len {
+            return coords;
+        }
+        self.tile_mut().simplified_count -= (coords.len() - kept.len()) as u32;
+        kept
     }
     fn transform_polygon(&mut self, rings: &VtPolygon) -> Vec<Vec<Position>> {
-        let sq_tolerance = self.sq_tolerance;
-        rings
+        let sq_tolerance = self.polygon_sq_tolerance;
+        let coords = rings
             .iter()
             .filter(|ring| ring.area > sq_tolerance)
             .map(|ring| self.transform_line_ring(ring))
-            .collect::<Vec<_>>()
+            .collect::<Vec<_>>();
+        if self.options.remove_slivers {
+            // Rings clipped close to a tile corner can collapse once rounded to the extent; a
+            // polygon whose outer ring collapsed is dropped with its holes.
+            let coords: Vec<_> = coords
+                .into_iter()
+                .map(|ring| self.sliver_removed(ring))
+                .collect();
+            if coords.first().is_some_and(|ring| ring.is_empty()) {
+                return vec![];
+            }
+            return coords.into_iter().filter(|ring| !ring.is_empty()).collect();
+        }
+        if !self.options.clip_to_extent {
+            return coords;
+        }
+        // Clipping to the extent can leave slivers collapsing to fewer than 4 points.
+        if coords.first().is_some_and(|ring| ring.len() < 4) {
+            return vec![];
+        }
+        coords.into_iter().filter(|ring| ring.len() >= 4).collect()
+    }
+    /// Drops the consecutive repeated points left by rounding a ring to the extent, closing it
+    /// again if needed, and returns no points at all if the ring then encloses no area.
+    fn sliver_removed(&mut self, mut ring: Vec<Position>) -> Vec<Position> {
+        let len = ring.len();
+        ring.dedup();
+        if ring.len() > 1 && ring.first() == ring.last() {
+            ring.pop();
+        }
+        let area: f64 = (0..ring.len())
+            .map(|i| {
+                let (a, b) = (&ring[i], &ring[(i + 1) % ring.len()]);
+                a[0] * b[1] - b[0] * a[1]
+            })
+            .sum();
+        if ring.len() < 3 || area == 0. {
+            self.tile_mut().simplified_count -= len as u32;
+            return vec![];
+        }
+        ring.push(ring[0].clone());
+        self.tile_mut().simplified_count -= len as u32;
+        self.tile_mut().simplified_count += ring.len() as u32;
+        ring
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
 pub struct TileCoord {
     x: u32,
     y: u32,
@@ -347,4 +878,300 @@ impl TileCoord {
     pub fn new(x: u32, y: u32, z: u8) -> TileCoord {
         TileCoord { x, y, z }
     }
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+    pub fn z(&self) -> u8 {
+        self.z
+    }
+}
+
+/// Work done to generate one tile, see [`crate::Options::on_tile_created`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TileMetrics {
+    /// Time spent clipping the parent tile into its four children, this tile included.
+    pub clip_time: Duration,
+    /// Time spent simplifying the tile's features and transforming them to tile coordinates.
+    pub transform_time: Duration,
+    /// Number of projected features clipped to the tile.
+    pub input_features: usize,
+    /// Number of features in the generated tile.
+    pub output_features: usize,
+    pub point_count: u32,
+    pub simplified_count: u32,
+}
+
+impl TileMetrics {
+    /// Clipping and transforming time combined.
+    pub fn total_time(&self) -> Duration {
+        self.clip_time + self.transform_time
+    }
+}
+
+/// Where tile coordinates are measured from, see [`crate::Options::origin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum TileOrigin {
+    /// Coordinates run from 0 to `extent`, as in vector tiles.
+    #[default]
+    TopLeft,
+    /// Coordinates run from `-extent / 2` to `extent / 2`, `extent / 2` rounded down so they
+    /// stay integers.
+    Center,
+}
+
+impl TileOrigin {
+    /// Tile coordinates of the origin, measured from the top-left corner of the tile.
+    pub fn offset(&self, extent: u32) -> f64 {
+        match self {
+            TileOrigin::TopLeft => 0.,
+            TileOrigin::Center => (extent / 2) as f64,
+        }
+    }
+}
+
+/// Direction of the y axis of tile coordinates, see [`crate::Options::y_axis`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum YAxis {
+    /// Y grows downwards, as on screen and in vector tiles.
+    #[default]
+    ScreenCoords,
+    /// Y grows upwards, as in OpenGL or PDF. Flipping y also reverses the winding of rings.
+    CartesianCoords,
+}
+
+/// JSON number type of the properties generated for tile features, such as those of
+/// [`crate::Options::line_metrics`], see [`crate::Options::numeric_property_style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum NumberStyle {
+    /// Integers for whole values such as 0 and 1, floats otherwise.
+    Mixed,
+    /// Floats for every value, so consumers always see the same type for a key.
+    #[default]
+    AlwaysFloat,
+}
+
+impl NumberStyle {
+    /// `value` as a JSON number of this style, `None` if it is not finite.
+    fn number(&self, value: f64) -> Option<Number> {
+        match self {
+            NumberStyle::Mixed if value.fract() == 0.0 => Some(Number::from(value as i64)),
+            _ => Number::from_f64(value),
+        }
+    }
+}
+
+/// Callback of [`crate::Options::tile_property_hook`], shared by the clones of the options it
+/// is set on.
+#[derive(Clone)]
+pub struct TilePropertyHook(Arc<PropertyHook>);
+
+type PropertyHook = dyn Fn(u8, u32, u32, &JsonObject) -> Option<JsonObject> + Send + Sync;
+
+impl TilePropertyHook {
+    pub fn new(
+        f: impl Fn(u8, u32, u32, &JsonObject) -> Option<JsonObject> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+impl std::fmt::Debug for TilePropertyHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TilePropertyHook")
+    }
+}
+
+/// Order of the features within each tile, see [`crate::Options::feature_order`].
+///
+/// Sorting is stable, features with equal keys keep their input order.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum FeatureOrder {
+    #[default]
+    InputOrder,
+    /// Polygons, then lines, then points, then geometry collections, the largest first within
+    /// polygons (summed ring area) and lines (length), as measured in tile coordinates.
+    GeometryTypeThenSize,
+    /// By the value of a property, in `SortDirection` among numbers and among strings. Numbers
+    /// always come first, then strings, then the features without a number or string value.
+    ByProperty(String, SortDirection),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl FeatureOrder {
+    fn sort(&self, features: &mut [Feature]) {
+        match self {
+            FeatureOrder::InputOrder => {}
+            FeatureOrder::GeometryTypeThenSize => {
+                features.sort_by(|a, b| {
+                    let (a, b) = (geometry_sort_key(a), geometry_sort_key(b));
+                    a.0.cmp(&b.0).then(b.1.total_cmp(&a.1))
+                });
+            }
+            FeatureOrder::ByProperty(key, direction) => {
+                features.sort_by(|a, b| {
+                    let ordering = match (a.property(key), b.property(key)) {
+                        (Some(JsonValue::Number(a)), Some(JsonValue::Number(b))) => {
+                            let (a, b) = (a.as_f64().unwrap_or(0.), b.as_f64().unwrap_or(0.));
+                            a.total_cmp(&b)
+                        }
+                        (Some(JsonValue::String(a)), Some(JsonValue::String(b))) => a.cmp(b),
+                        (a, b) => return property_rank(a).cmp(&property_rank(b)),
+                    };
+                    match direction {
+                        SortDirection::Ascending => ordering,
+                        SortDirection::Descending => ordering.reverse(),
+                    }
+                });
+            }
+        }
+    }
+}
+
+/// Layer of `feature` with [`crate::Options::layer_by_property`]: the string value of the `key`
+/// property, other values as JSON, `None` when missing or null.
+fn layer_name(feature: &Feature, key: &str) -> Option<String> {
+    match feature.property(key) {
+        Some(JsonValue::String(name)) => Some(name.clone()),
+        None | Some(JsonValue::Null) => None,
+        Some(value) => Some(value.to_string()),
+    }
+}
+
+/// `properties` with the `mapbox_clip_start` and `mapbox_clip_end` of a clipped line or ring,
+/// leaving out values that are not finite, such as those of lines without length.
+fn with_clip_metrics(
+    properties: &Option<JsonObject>,
+    start: f64,
+    end: f64,
+    style: NumberStyle,
+) -> JsonObject {
+    let mut properties = properties.clone().unwrap_or_default();
+    for (key, value) in [("mapbox_clip_start", start), ("mapbox_clip_end", end)] {
+        if let Some(number) = style.number(value) {
+            properties.insert(key.to_string(), JsonValue::Number(number));
+        }
+    }
+    properties
+}
+
+fn property_rank(value: Option<&JsonValue>) -> u8 {
+    match value {
+        Some(JsonValue::Number(_)) => 0,
+        Some(JsonValue::String(_)) => 1,
+        _ => 2,
+    }
+}
+
+/// Geometry class rank and size of a transformed feature, see
+/// [`FeatureOrder::GeometryTypeThenSize`].
+fn geometry_sort_key(feature: &Feature) -> (u8, f64) {
+    let area = |ring: &[Position]| {
+        ring.windows(2)
+            .map(|w| w[0][0] * w[1][1] - w[1][0] * w[0][1])
+            .sum::<f64>()
+            .abs()
+            / 2.
+    };
+    let length = |line: &[Position]| {
+        line.windows(2)
+            .map(|w| (w[1][0] - w[0][0]).hypot(w[1][1] - w[0][1]))
+            .sum::<f64>()
+    };
+    match feature.geometry.as_ref().map(|geometry| &geometry.value) {
+        Some(Value::Polygon(rings)) => (0, rings.iter().map(|ring| area(ring)).sum()),
+        Some(Value::MultiPolygon(polygons)) => {
+            (0, polygons.iter().flatten().map(|ring| area(ring)).sum())
+        }
+        Some(Value::LineString(line)) => (1, length(line)),
+        Some(Value::MultiLineString(lines)) => (1, lines.iter().map(|line| length(line)).sum()),
+        Some(Value::Point(_) | Value::MultiPoint(_)) => (2, 0.),
+        _ => (3, 0.),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_child_and_parent_coords() {
+        let options = Arc::new(TileOptions {
+            extent: 4096,
+            ..TileOptions::default()
+        });
+        let tile = InternalTile::new(&[], 3, 5, 2, &options);
+        assert_eq!(
+            tile.child_coords(),
+            [
+                TileCoord::new(10, 4, 4),
+                TileCoord::new(11, 4, 4),
+                TileCoord::new(10, 5, 4),
+                TileCoord::new(11, 5, 4),
+            ]
+        );
+        assert_eq!(tile.parent_coord(), Some(TileCoord::new(2, 1, 2)));
+
+        let root = InternalTile::new(&[], 0, 0, 0, &options);
+        assert_eq!(root.parent_coord(), None);
+        for child in root.child_coords() {
+            let tile = InternalTile::new(&[], child.z, child.x, child.y, &options);
+            assert_eq!(tile.parent_coord(), Some(TileCoord::new(0, 0, 0)));
+        }
+    }
+
+    #[test]
+    fn test_tile_with_extent() {
+        let line = VtLineString::new(vec![
+            VtPoint::new(0.25, 0.25, 1.),
+            VtPoint::new(0.5, 0.75, 1.),
+            VtPoint::new(0.75, 0.25, 1.),
+        ]);
+        let feature = Arc::new(VtFeature::new(
+            VtGeometry::LineString(line),
+            Arc::new(None),
+            None,
+        ));
+        let features = vec![feature];
+        let options = Arc::new(TileOptions {
+            extent: 4096,
+            remove_collinear: true,
+            remove_slivers: true,
+            ..TileOptions::default()
+        });
+        let mut tile = InternalTile::new(&features, 0, 0, 0, &options);
+        let empty = Tile {
+            extent: 4096,
+            coord: Some(TileCoord::new(0, 0, 0)),
+            ..EMPTY_TILE.clone()
+        };
+        assert_eq!(tile.tile_with_extent(4096), empty);
+        tile.source_feature = features.into();
+        assert_eq!(tile.tile_with_extent(4096), *tile.tile);
+
+        let small = tile.tile_with_extent(512);
+        assert_eq!(small.point_count, tile.tile.point_count);
+        assert_eq!(small.extent, 512);
+        let geometry = small.feature_collection.features[0].geometry.as_ref();
+        assert_eq!(
+            geometry.map(|g| &g.value),
+            Some(&Value::LineString(vec![
+                vec![128., 128.],
+                vec![256., 384.],
+                vec![384., 128.],
+            ]))
+        );
+    }
 }
@@ -1,19 +1,48 @@
 use geojson::{JsonObject, feature::Id};
-use std::rc::Rc;
+use std::{cmp::Ordering, fmt, sync::Arc};
 
-#[derive(Debug, Clone)]
+use crate::{
+    clip::clip_ring,
+    convert::{mercator_x_to_lng, mercator_y_to_lat},
+    simplify::point_to_segment_sq_dist,
+};
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct VtFeature {
     pub id: Option<Id>,
     pub geometry: VtGeometry,
-    pub properties: Rc<Option<JsonObject>>,
+    pub properties: Arc<Option<JsonObject>>,
     pub bbox: Option<BBox>,
     pub point_count: u32,
 }
 
 impl VtFeature {
+    /// Compares features by `id` alone: features without an id come first, then numeric ids in
+    /// numeric order, then string ids in lexicographic order. Used by
+    /// [`crate::Options::sort_features`].
+    pub fn cmp_by_id(&self, other: &Self) -> Ordering {
+        match (&self.id, &other.id) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some(Id::Number(a)), Some(Id::Number(b))) => match (a.as_i64(), b.as_i64()) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                _ => match (a.as_u64(), b.as_u64()) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    _ => {
+                        let (a, b) = (a.as_f64().unwrap_or(0.), b.as_f64().unwrap_or(0.));
+                        a.total_cmp(&b)
+                    }
+                },
+            },
+            (Some(Id::Number(_)), Some(Id::String(_))) => Ordering::Less,
+            (Some(Id::String(_)), Some(Id::Number(_))) => Ordering::Greater,
+            (Some(Id::String(a)), Some(Id::String(b))) => a.cmp(b),
+        }
+    }
     pub fn new(
         mut geometry: VtGeometry,
-        properties: Rc<Option<JsonObject>>,
+        properties: Arc<Option<JsonObject>>,
         id: Option<Id>,
     ) -> Self {
         let mut bbox = BBox::default();
@@ -33,9 +62,33 @@ impl VtFeature {
             point_count,
         }
     }
+    /// Center of the feature in projected coordinates, see [`VtGeometry::centroid`].
+    ///
+    /// The coordinates of the feature are used as they are, so the copies of a feature wrapped
+    /// across the antimeridian get a centroid outside `[0, 1]`, within their own bounds.
+    pub fn compute_centroid_mercator(&self) -> Option<VtPoint> {
+        self.geometry.centroid()
+    }
+    /// Same as [`VtFeature::new`] with `bbox` as the bounds of the geometry, without going
+    /// through its points.
+    pub fn with_bbox(
+        geometry: VtGeometry,
+        properties: Arc<Option<JsonObject>>,
+        id: Option<Id>,
+        bbox: BBox,
+    ) -> Self {
+        VtFeature {
+            id,
+            point_count: geometry.point_count(),
+            geometry,
+            properties,
+            bbox: Some(bbox),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
 pub struct BBox {
     pub min_x: f64,
     pub min_y: f64,
@@ -55,6 +108,65 @@ impl BBox {
             && self.max_x.is_infinite()
             && self.max_y.is_infinite()
     }
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+    /// Whether the boxes overlap, edges included.
+    pub fn intersects(&self, other: &BBox) -> bool {
+        self.min_x <= other.max_x
+            && other.min_x <= self.max_x
+            && self.min_y <= other.max_y
+            && other.min_y <= self.max_y
+    }
+    /// Whether `(x, y)` lies within the box, edges included.
+    pub fn contains_point(&self, x: f64, y: f64) -> bool {
+        (self.min_x..=self.max_x).contains(&x) && (self.min_y..=self.max_y).contains(&y)
+    }
+    /// Returns the `(west, south, east, north)` bounds of the box in WGS-84 degrees.
+    #[must_use = "`to_wgs84` returns the converted bounds"]
+    pub fn to_wgs84(&self) -> (f64, f64, f64, f64) {
+        (
+            mercator_x_to_lng(self.min_x),
+            mercator_y_to_lat(self.max_y),
+            mercator_x_to_lng(self.max_x),
+            mercator_y_to_lat(self.min_y),
+        )
+    }
+    /// Formats the box as `BBOX(west, south, east, north)` in WGS-84 degrees.
+    pub fn display_wgs84(&self) -> impl fmt::Display {
+        BBoxDisplay(self.to_wgs84())
+    }
+    /// Formats the box as `BBOX(min_x, min_y, max_x, max_y)` in the projected 0..1 space, for
+    /// boxes that are not necessarily geographic.
+    pub fn display_mercator(&self) -> impl fmt::Display {
+        BBoxDisplay((self.min_x, self.min_y, self.max_x, self.max_y))
+    }
+}
+/// Formats in WGS-84 degrees with the `wgs84_display` feature, see [`BBox::display_wgs84`], and
+/// in the projected space otherwise, see [`BBox::display_mercator`].
+impl fmt::Display for BBox {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if cfg!(feature = "wgs84_display") {
+            self.display_wgs84().fmt(f)
+        } else {
+            self.display_mercator().fmt(f)
+        }
+    }
+}
+
+struct BBoxDisplay((f64, f64, f64, f64));
+
+impl fmt::Display for BBoxDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (a, b, c, d) = self.0;
+        match f.precision() {
+            Some(p) => write!(f, "BBOX({a:.p$}, {b:.p$}, {c:.p$}, {d:.p$})"),
+            None => write!(f, "BBOX({a}, {b}, {c}, {d})"),
+        }
+    }
 }
 impl Default for BBox {
     fn default() -> Self {
@@ -67,7 +179,18 @@ impl Default for BBox {
     }
 }
 
+/// Bounds of the `features`, merged from their `bbox`.
+pub(crate) fn features_bbox(features: &[Arc<VtFeature>]) -> BBox {
+    let mut bbox = BBox::default();
+    features
+        .iter()
+        .filter_map(|feature| feature.bbox.as_ref())
+        .for_each(|b| bbox.merge(b));
+    bbox
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
 pub enum VtGeometry {
     // Empty,
     Point(VtPoint),
@@ -83,7 +206,7 @@ impl VtGeometry {
         let mut f = &mut f as &mut dyn FnMut(&mut VtPoint);
         match self {
             VtGeometry::Point(p) => f(p),
-            VtGeometry::MultiPoint(ps) => ps.iter_mut().for_each(&mut f),
+            VtGeometry::MultiPoint(ps) => ps.elements.iter_mut().for_each(&mut f),
             VtGeometry::LineString(ls) => ls.elements.iter_mut().for_each(&mut f),
             VtGeometry::MultiLineString(mls) => mls
                 .iter_mut()
@@ -102,12 +225,245 @@ impl VtGeometry {
             }
         }
     }
+    /// Number of points of the geometry.
+    pub fn point_count(&self) -> u32 {
+        let count = match self {
+            VtGeometry::Point(_) => 1,
+            VtGeometry::MultiPoint(ps) => ps.len(),
+            VtGeometry::LineString(ls) => ls.elements.len(),
+            VtGeometry::MultiLineString(mls) => mls.iter().map(|ls| ls.elements.len()).sum(),
+            VtGeometry::Polygon(poly) => poly.iter().map(|ring| ring.elements.len()).sum(),
+            VtGeometry::MultiPolygon(mpoly) => {
+                mpoly.iter().flatten().map(|ring| ring.elements.len()).sum()
+            }
+            VtGeometry::GeometryCollection(gc) => {
+                return gc.iter().map(VtGeometry::point_count).sum();
+            }
+        };
+        count as u32
+    }
+    /// Geometry type of the Mapbox Vector Tile specification: 1 for points, 2 for lines and 3
+    /// for polygons, single or multi. Collections have no such type and return 0.
+    pub fn mvt_geometry_type(&self) -> u8 {
+        match self {
+            VtGeometry::Point(_) | VtGeometry::MultiPoint(_) => 1,
+            VtGeometry::LineString(_) | VtGeometry::MultiLineString(_) => 2,
+            VtGeometry::Polygon(_) | VtGeometry::MultiPolygon(_) => 3,
+            VtGeometry::GeometryCollection(_) => 0,
+        }
+    }
+    /// Replaces every `(x, y)` pair of the geometry with `f(x, y)`, keeping `z`.
+    ///
+    /// Line lengths and ring areas are left as they were, call
+    /// [`VtGeometry::recompute_metrics`] if `f` does not preserve them.
+    pub fn transform<F: Fn(f64, f64) -> (f64, f64)>(&mut self, f: F) {
+        self.iter_each_point(|p| (p.x, p.y) = f(p.x, p.y));
+    }
+    /// Recomputes the `dist` of every line and the `area` of every ring from their points,
+    /// along with the perimeter of the rings measured for [`crate::Options::ring_metrics`].
+    pub fn recompute_metrics(&mut self) {
+        match self {
+            VtGeometry::Point(_) | VtGeometry::MultiPoint(_) => {}
+            VtGeometry::LineString(ls) => ls.dist = VtLineString::length(&ls.elements),
+            VtGeometry::MultiLineString(mls) => mls
+                .iter_mut()
+                .for_each(|ls| ls.dist = VtLineString::length(&ls.elements)),
+            VtGeometry::Polygon(poly) => poly.iter_mut().for_each(VtLinearRing::recompute_metrics),
+            VtGeometry::MultiPolygon(mpoly) => mpoly
+                .iter_mut()
+                .flatten()
+                .for_each(VtLinearRing::recompute_metrics),
+            VtGeometry::GeometryCollection(gc) => {
+                gc.iter_mut().for_each(VtGeometry::recompute_metrics)
+            }
+        }
+    }
+    /// Shifts every point of the geometry by `dx` and `dy`.
+    pub fn translate(&mut self, dx: f64, dy: f64) {
+        self.transform(|x, y| (x + dx, y + dy));
+    }
+    /// Reverses the order of the points of every line and ring, each ring of a polygon on its
+    /// own. Points and multi-points are left as they are.
+    pub fn reverse_vertex_order(&mut self) {
+        match self {
+            VtGeometry::Point(_) | VtGeometry::MultiPoint(_) => {}
+            VtGeometry::LineString(ls) => ls.elements.reverse(),
+            VtGeometry::MultiLineString(mls) => mls.iter_mut().for_each(|ls| ls.elements.reverse()),
+            VtGeometry::Polygon(poly) => poly.iter_mut().for_each(VtLinearRing::reverse),
+            VtGeometry::MultiPolygon(mpoly) => {
+                mpoly.iter_mut().flatten().for_each(VtLinearRing::reverse)
+            }
+            VtGeometry::GeometryCollection(gc) => {
+                gc.iter_mut().for_each(VtGeometry::reverse_vertex_order)
+            }
+        }
+    }
+    /// Center of mass of the geometry: the area-weighted centroid of its polygons, holes
+    /// subtracted, else the length-weighted centroid of its lines, else the mean of its points.
+    /// Collections use their members of the highest dimension, and polygons without area or
+    /// lines without length fall back to their points. `None` for an empty geometry.
+    pub fn centroid(&self) -> Option<VtPoint> {
+        let mut sums = CentroidSums::default();
+        sums.add_geometry(self);
+        sums.centroid()
+    }
+    /// Circle around all the points of the geometry, as its center and radius: the center is
+    /// the mean of the points, the closing point of rings counted once, and the radius the
+    /// distance to the farthest of them. Larger than the minimum enclosing circle, but enough
+    /// for culling. `None` for an empty geometry.
+    pub fn bounding_circle(&self) -> Option<(VtPoint, f64)> {
+        let mut points = Vec::new();
+        self.collect_points(&mut points);
+        if points.is_empty() {
+            return None;
+        }
+        let n = points.len() as f64;
+        let x = points.iter().map(|p| p.x).sum::<f64>() / n;
+        let y = points.iter().map(|p| p.y).sum::<f64>() / n;
+        let radius = points
+            .iter()
+            .map(|p| (p.x - x).hypot(p.y - y))
+            .fold(0., f64::max);
+        Some((VtPoint::from_xy(x, y), radius))
+    }
+    /// Adds the points of the geometry to `points`, leaving out the closing point of rings.
+    fn collect_points<'a>(&'a self, points: &mut Vec<&'a VtPoint>) {
+        let ring = |ring: &'a VtLinearRing| match ring.elements.split_last() {
+            Some((last, rest)) if rest.first() == Some(last) => rest,
+            _ => &ring.elements[..],
+        };
+        match self {
+            VtGeometry::Point(p) => points.push(p),
+            VtGeometry::MultiPoint(ps) => points.extend(&ps.elements),
+            VtGeometry::LineString(ls) => points.extend(&ls.elements),
+            VtGeometry::MultiLineString(mls) => {
+                points.extend(mls.iter().flat_map(|ls| &ls.elements))
+            }
+            VtGeometry::Polygon(poly) => points.extend(poly.iter().flat_map(ring)),
+            VtGeometry::MultiPolygon(mpoly) => points.extend(mpoly.iter().flatten().flat_map(ring)),
+            VtGeometry::GeometryCollection(gc) => gc.iter().for_each(|g| g.collect_points(points)),
+        }
+    }
+    /// Returns a copy of the geometry shifted by `dx` and `dy`.
+    #[must_use = "`translated` returns a shifted copy, use `translate` to shift in place"]
+    pub fn translated(&self, dx: f64, dy: f64) -> VtGeometry {
+        let mut geometry = self.clone();
+        geometry.translate(dx, dy);
+        geometry
+    }
+    /// Whether the geometry lies within `distance` of `(x, y)`, in projected units. Polygons
+    /// also match when they contain the position, by the even-odd rule over all their rings so
+    /// that holes are left out.
+    pub fn is_within_distance(&self, x: f64, y: f64, distance: f64) -> bool {
+        let p = VtPoint::from_xy(x, y);
+        let sq_distance = distance * distance;
+        let near_point = |q: &VtPoint| (q.x - x).hypot(q.y - y) <= distance;
+        let near_line = |points: &[VtPoint]| match points {
+            [only] => near_point(only),
+            _ => points
+                .windows(2)
+                .any(|w| point_to_segment_sq_dist(&p, &w[0], &w[1]) <= sq_distance),
+        };
+        let near_polygon = |rings: &VtPolygon| {
+            let containing = rings.iter().filter(|r| r.contains_point(x, y)).count();
+            containing % 2 == 1 || rings.iter().any(|ring| near_line(&ring.elements))
+        };
+        match self {
+            VtGeometry::Point(point) => near_point(point),
+            VtGeometry::MultiPoint(points) => points.elements.iter().any(near_point),
+            VtGeometry::LineString(line) => near_line(&line.elements),
+            VtGeometry::MultiLineString(lines) => lines.iter().any(|l| near_line(&l.elements)),
+            VtGeometry::Polygon(rings) => near_polygon(rings),
+            VtGeometry::MultiPolygon(polygons) => polygons.iter().any(near_polygon),
+            VtGeometry::GeometryCollection(geometries) => geometries
+                .iter()
+                .any(|geometry| geometry.is_within_distance(x, y, distance)),
+        }
+    }
+}
+
+/// Weighted sums of x and y, and total weight, for points, lines and areas, see
+/// [`VtGeometry::centroid`].
+#[derive(Default)]
+struct CentroidSums([(f64, f64, f64); 3]);
+
+impl CentroidSums {
+    fn add(&mut self, dimension: usize, x: f64, y: f64, weight: f64) {
+        let (sx, sy, sw) = &mut self.0[dimension];
+        (*sx, *sy, *sw) = (*sx + x * weight, *sy + y * weight, *sw + weight);
+    }
+    fn add_line(&mut self, points: &[VtPoint]) {
+        for p in points {
+            self.add(0, p.x, p.y, 1.);
+        }
+        for w in points.windows(2) {
+            let length = (w[1].x - w[0].x).hypot(w[1].y - w[0].y);
+            self.add(1, (w[0].x + w[1].x) / 2., (w[0].y + w[1].y) / 2., length);
+        }
+    }
+    fn add_polygon(&mut self, rings: &VtPolygon) {
+        for (i, ring) in rings.iter().enumerate() {
+            self.add_line(&ring.elements);
+            let area = ring.signed_area();
+            if area == 0. {
+                continue;
+            }
+            let (cx, cy) = ring.elements.windows(2).fold((0., 0.), |(cx, cy), w| {
+                let cross = w[0].x * w[1].y - w[1].x * w[0].y;
+                (
+                    cx + (w[0].x + w[1].x) * cross,
+                    cy + (w[0].y + w[1].y) * cross,
+                )
+            });
+            // Holes are subtracted, whatever the winding of the rings.
+            let weight = if i == 0 { area.abs() } else { -area.abs() };
+            self.add(2, cx / (6. * area), cy / (6. * area), weight);
+        }
+    }
+    fn add_geometry(&mut self, geometry: &VtGeometry) {
+        match geometry {
+            VtGeometry::Point(p) => self.add(0, p.x, p.y, 1.),
+            VtGeometry::MultiPoint(ps) => {
+                ps.elements.iter().for_each(|p| self.add(0, p.x, p.y, 1.))
+            }
+            VtGeometry::LineString(ls) => self.add_line(&ls.elements),
+            VtGeometry::MultiLineString(mls) => {
+                mls.iter().for_each(|ls| self.add_line(&ls.elements))
+            }
+            VtGeometry::Polygon(poly) => self.add_polygon(poly),
+            VtGeometry::MultiPolygon(mpoly) => mpoly.iter().for_each(|poly| self.add_polygon(poly)),
+            VtGeometry::GeometryCollection(gc) => gc.iter().for_each(|g| self.add_geometry(g)),
+        }
+    }
+    /// The centroid of the highest dimension with a positive weight.
+    fn centroid(&self) -> Option<VtPoint> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(_, _, weight)| *weight > 0.)
+            .map(|(x, y, weight)| VtPoint::from_xy(x / weight, y / weight))
+    }
 }
 
+/// Length of the equator in EPSG:3857 meters.
+pub(crate) const WEB_MERCATOR_CIRCUMFERENCE: f64 = 2. * std::f64::consts::PI * 6378137.;
+
+/// A point in projected Web Mercator space, where `x` and `y` range from 0 to 1 across the
+/// world (top-left origin).
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
 pub struct VtPoint {
     pub x: f64,
     pub y: f64,
+    /// Simplification importance, in one of four forms:
+    ///
+    /// - `0.0`: an original point no simplification kept, dropped from simplified output.
+    /// - `1.0`: a synthetic point inserted where clipping cut the geometry at a tile or mask
+    ///   boundary, kept at every zoom.
+    /// - `f64::MAX`: a line or ring endpoint, or a point forced by
+    ///   [`crate::simplify_with_forced`], kept at every zoom.
+    /// - any other value above `0.0`: the squared distance computed by Douglas-Peucker, the
+    ///   point is kept in tiles whose squared tolerance is below it.
     pub z: f64,
 }
 impl VtPoint {
@@ -117,8 +473,75 @@ impl VtPoint {
     pub fn from_xy(x: f64, y: f64) -> Self {
         Self { x, y, z: 0. }
     }
+    /// Whether the point was inserted by clipping, see [`VtPoint::z`].
+    ///
+    /// Simplification can only give an original point a `z` of `1.0` at a squared distance of
+    /// exactly 1 from its simplified line, the width of the world.
+    pub fn is_synthetic(&self) -> bool {
+        self.z == 1.
+    }
+    /// Whether the point comes from the input without any simplification importance, see
+    /// [`VtPoint::z`].
+    pub fn is_original(&self) -> bool {
+        self.z == 0.
+    }
+    /// Converts the point to EPSG:3857 meters, with `y` pointing north.
+    #[must_use = "`to_web_mercator_meters` returns the converted coordinates"]
+    pub fn to_web_mercator_meters(&self) -> (f64, f64) {
+        (
+            (self.x - 0.5) * WEB_MERCATOR_CIRCUMFERENCE,
+            (0.5 - self.y) * WEB_MERCATOR_CIRCUMFERENCE,
+        )
+    }
+    /// Creates a point from EPSG:3857 meters.
+    #[must_use = "`from_web_mercator_meters` returns a new point"]
+    pub fn from_web_mercator_meters(x: f64, y: f64) -> VtPoint {
+        VtPoint::from_xy(
+            x / WEB_MERCATOR_CIRCUMFERENCE + 0.5,
+            0.5 - y / WEB_MERCATOR_CIRCUMFERENCE,
+        )
+    }
 }
+/// The points of a `MultiPoint`, with the position of each within the source `MultiPoint`,
+/// kept through clipping for [`crate::Options::explode_multipoints`].
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
+pub struct VtMultiPoint {
+    pub elements: Vec<VtPoint>,
+    /// Position of each of `elements` in the source `MultiPoint`.
+    pub indices: Vec<u32>,
+}
+impl VtMultiPoint {
+    /// Creates a `MultiPoint` from projected points, numbered in order.
+    pub fn new(elements: Vec<VtPoint>) -> Self {
+        let indices = (0..elements.len() as u32).collect();
+        Self { elements, indices }
+    }
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+    /// The points `keep` returns true for, with their positions.
+    pub(crate) fn filtered(&self, mut keep: impl FnMut(&VtPoint) -> bool) -> VtMultiPoint {
+        let (elements, indices) = self
+            .elements
+            .iter()
+            .zip(&self.indices)
+            .filter(|(point, _)| keep(point))
+            .unzip();
+        VtMultiPoint { elements, indices }
+    }
+}
+impl FromIterator<VtPoint> for VtMultiPoint {
+    fn from_iter<T: IntoIterator<Item = VtPoint>>(iter: T) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
 pub struct VtLineString {
     pub elements: Vec<VtPoint>,
     pub dist: f64,
@@ -126,6 +549,21 @@ pub struct VtLineString {
     pub seg_end: f64,
 }
 impl VtLineString {
+    /// Creates a line from projected points, computing its length.
+    pub fn new(elements: Vec<VtPoint>) -> Self {
+        Self {
+            dist: Self::length(&elements),
+            elements,
+            seg_start: 0.,
+            seg_end: 0.,
+        }
+    }
+    fn length(elements: &[VtPoint]) -> f64 {
+        elements
+            .windows(2)
+            .map(|w| (w[1].x - w[0].x).hypot(w[1].y - w[0].y))
+            .sum()
+    }
     #[cfg(test)]
     pub fn from_slice(slice: &[VtPoint]) -> Self {
         Self {
@@ -137,22 +575,195 @@ impl VtLineString {
     }
 }
 #[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "persist", derive(serde::Serialize, serde::Deserialize))]
 pub struct VtLinearRing {
     pub elements: Vec<VtPoint>,
+    /// Absolute area of the ring. Rings clipped into tiles keep the area of their source ring,
+    /// as in geojson-vt, unless [`crate::Options::remove_slivers`] is set.
     pub area: f64,
+    /// Perimeter of the source ring, only measured with [`crate::Options::ring_metrics`].
+    #[cfg_attr(feature = "persist", serde(default))]
+    pub dist: f64,
+    /// Distances along the perimeter of the source ring where the part kept by clipping
+    /// starts and ends, as in [`VtLineString`]. The part runs through the first point of the
+    /// source ring when `seg_end` is below `seg_start`, both are zero if the ring was not cut
+    /// or keeps none of that perimeter.
+    #[cfg_attr(feature = "persist", serde(default))]
+    pub seg_start: f64,
+    #[cfg_attr(feature = "persist", serde(default))]
+    pub seg_end: f64,
+    /// Distance of each point along the perimeter of the source ring, measured with
+    /// [`crate::Options::ring_metrics`]. `None` for the points clipping adds off that
+    /// perimeter, such as the corners of a tile.
+    #[cfg_attr(feature = "persist", serde(default))]
+    pub measures: Vec<Option<f64>>,
+}
+impl VtLinearRing {
+    /// Creates a ring from projected points, computing its absolute area.
+    pub fn new(elements: Vec<VtPoint>) -> Self {
+        let mut ring = Self {
+            elements,
+            ..Default::default()
+        };
+        ring.area = ring.signed_area().abs();
+        ring
+    }
+    fn recompute_metrics(&mut self) {
+        self.area = self.signed_area().abs();
+        if self.dist > 0. {
+            self.measure();
+        }
+    }
+    /// Measures the perimeter of the ring and the distance of each point along it, for
+    /// [`crate::Options::ring_metrics`].
+    pub(crate) fn measure(&mut self) {
+        let mut dist = 0.;
+        self.measures = Vec::with_capacity(self.elements.len());
+        for (i, p) in self.elements.iter().enumerate() {
+            if let Some(prev) = i.checked_sub(1).map(|i| self.elements[i]) {
+                dist += (p.x - prev.x).hypot(p.y - prev.y);
+            }
+            self.measures.push(Some(dist));
+        }
+        self.dist = dist;
+    }
+    /// Distance along the perimeter of the source ring at `t` along the edge from point `i` to
+    /// the next, or `None` if that edge was added by clipping.
+    pub(crate) fn measure_at(&self, i: usize, t: f64) -> Option<f64> {
+        let (a, b) = (self.elements.get(i)?, self.elements.get(i + 1)?);
+        let (ma, mb) = (
+            self.measures.get(i).copied()??,
+            self.measures.get(i + 1).copied()??,
+        );
+        edge_measure(self.dist, (a, ma), (b, mb), t)
+    }
+    /// Where the part of the source perimeter kept by clipping starts and ends, as held in
+    /// `seg_start` and `seg_end`, or `None` if the ring keeps all of it or none.
+    pub(crate) fn kept_range(&self) -> Option<(f64, f64)> {
+        let edges = self.elements.len().saturating_sub(1);
+        let kept: Vec<bool> = (0..edges)
+            .map(|i| self.measure_at(i, 0.).is_some())
+            .collect();
+        // Runs of kept edges, scanned from an edge added by clipping.
+        let added = kept.iter().position(|kept| !kept)?;
+        let mut runs: Vec<(f64, f64)> = Vec::new();
+        let mut run: Option<(f64, f64)> = None;
+        for i in (added + 1..=added + edges).map(|i| i % edges) {
+            if kept[i] {
+                let start = run.map_or(self.measures[i]?, |(start, _)| start);
+                run = Some((start, self.measures[i + 1]?));
+            } else if let Some(run) = run.take() {
+                runs.push(run);
+            }
+        }
+        // The part starts with the run through the first point of the source ring, if any,
+        // and ends with the run before it along the ring.
+        let first = runs
+            .iter()
+            .position(|(start, end)| end < start)
+            .or_else(|| (0..runs.len()).min_by(|&i, &j| runs[i].0.total_cmp(&runs[j].0)))?;
+        let last = (first + runs.len() - 1) % runs.len();
+        Some((
+            runs[first].0.rem_euclid(self.dist),
+            runs[last].1.rem_euclid(self.dist),
+        ))
+    }
+    /// Reverses the points of the ring, measuring them again from the new first point.
+    fn reverse(&mut self) {
+        self.elements.reverse();
+        if self.dist > 0. {
+            self.measure();
+        }
+    }
+    /// Length of the ring's edges in projected space.
+    pub fn perimeter(&self) -> f64 {
+        VtLineString::length(&self.elements)
+    }
+    /// Shoelace area of the ring in projected space. As `y` points down, the area is positive
+    /// for rings that run clockwise on a north-up map.
+    pub fn signed_area(&self) -> f64 {
+        self.elements
+            .windows(2)
+            .map(|w| w[0].x * w[1].y - w[1].x * w[0].y)
+            .sum::<f64>()
+            / 2.
+    }
+    /// Whether the ring runs clockwise on a north-up map, as GeoJSON holes do.
+    pub fn is_clockwise(&self) -> bool {
+        self.signed_area() > 0.
+    }
+    /// Whether `(x, y)` lies inside the ring or on its edges, by the even-odd rule so that
+    /// self-intersecting rings are handled consistently. The ring need not be closed.
+    pub fn contains_point(&self, x: f64, y: f64) -> bool {
+        let p = VtPoint::from_xy(x, y);
+        let mut inside = false;
+        let mut prev = match self.elements.last() {
+            Some(last) => last,
+            None => return false,
+        };
+        for point in &self.elements {
+            let (a, b) = (prev, point);
+            prev = point;
+            if point_to_segment_sq_dist(&p, a, b) == 0. {
+                return true;
+            }
+            if (a.y > y) != (b.y > y) && x < a.x + (y - a.y) / (b.y - a.y) * (b.x - a.x) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+    /// Splits the ring along the vertical line at `x` into the parts to its left and to its
+    /// right, each closed along the line and with its own area. A side the ring does not reach
+    /// is empty.
+    ///
+    /// This is how [`crate::GeoJSONVT`] cuts polygons crossing the antimeridian before shifting
+    /// the part beyond it back into the world.
+    #[must_use = "`split_at_x` returns the parts, leaving the ring unchanged"]
+    pub fn split_at_x(&self, x: f64) -> (Vec<VtLinearRing>, Vec<VtLinearRing>) {
+        let part = |k1, k2| {
+            clip_ring::<0>(self, k1, k2)
+                .filter(|ring| ring.elements.len() >= 4)
+                .into_iter()
+                .collect()
+        };
+        (part(f64::NEG_INFINITY, x), part(x, f64::INFINITY))
+    }
 }
 #[cfg(test)]
 impl VtLinearRing {
     pub fn from_slice(points: &[VtPoint]) -> Self {
         Self {
             elements: Vec::from(points),
-            area: 0.0,
+            ..Default::default()
         }
     }
 }
 
+/// Distance along the perimeter of a source ring `dist` long at `t` along the edge from `a` to
+/// `b`, given their own distances, or `None` if the edge is not part of that perimeter.
+///
+/// An edge added by clipping joins two points whose distance apart along the perimeter is
+/// longer than the edge itself, unless the perimeter runs straight between them.
+pub(crate) fn edge_measure(
+    dist: f64,
+    (a, ma): (&VtPoint, f64),
+    (b, mb): (&VtPoint, f64),
+    t: f64,
+) -> Option<f64> {
+    if dist <= 0. {
+        return None;
+    }
+    let epsilon = dist * 1e-9;
+    let len = (b.x - a.x).hypot(b.y - a.y);
+    let mut along = (mb - ma).rem_euclid(dist);
+    if along > dist - epsilon {
+        along -= dist;
+    }
+    ((along - len).abs() <= epsilon).then_some(ma + along * t)
+}
+
 pub type VtPolygon = Vec<VtLinearRing>;
-pub type VtMultiPoint = Vec<VtPoint>;
 pub type VtMultiLineString = Vec<VtLineString>;
 pub type VtMultiPolygon = Vec<VtPolygon>;
 pub type VtGeometryCollection = Vec<VtGeometry>;
@@ -181,6 +792,8 @@ pub fn calc_progress<const I: usize>(a: &VtPoint, b: &VtPoint, v: f64) -> f64 {
     }
 }
 
+/// Returns the point at progress `t` along `a`-`b`, where its coordinate `I` equals `v`, marked
+/// as synthetic (see [`VtPoint::is_synthetic`]).
 pub fn intersect<const I: usize>(a: &VtPoint, b: &VtPoint, v: f64, t: f64) -> VtPoint {
     match I {
         0 => VtPoint::new(v, a.y + t * (b.y - a.y), 1.),
@@ -188,3 +801,423 @@ pub fn intersect<const I: usize>(a: &VtPoint, b: &VtPoint, v: f64, t: f64) -> Vt
         _ => panic!("intersect is only implemented for I = 0 and I = 1"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_web_mercator_meters() {
+        let half = std::f64::consts::PI * 6378137.;
+        assert_eq!(
+            VtPoint::from_xy(0.5, 0.5).to_web_mercator_meters(),
+            (0., 0.)
+        );
+        assert_eq!(
+            VtPoint::from_xy(0., 0.).to_web_mercator_meters(),
+            (-half, half)
+        );
+        assert_eq!(
+            VtPoint::from_xy(1., 1.).to_web_mercator_meters(),
+            (half, -half)
+        );
+        let point = VtPoint::from_xy(0.3, 0.7);
+        let (x, y) = point.to_web_mercator_meters();
+        let back = VtPoint::from_web_mercator_meters(x, y);
+        assert!((back.x - point.x).abs() < 1e-12 && (back.y - point.y).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_translate() {
+        let geometry = VtGeometry::GeometryCollection(vec![
+            VtGeometry::Point(VtPoint::new(0.25, 0.5, 1.)),
+            VtGeometry::LineString(VtLineString::new(vec![
+                VtPoint::from_xy(0., 0.),
+                VtPoint::from_xy(0.5, 0.5),
+            ])),
+        ]);
+        let mut translated = geometry.translated(1., -0.25);
+        let VtGeometry::GeometryCollection(parts) = &translated else {
+            panic!("not a geometry collection");
+        };
+        assert_eq!(parts[0], VtGeometry::Point(VtPoint::new(1.25, 0.25, 1.)));
+        let VtGeometry::LineString(line) = &parts[1] else {
+            panic!("not a line string");
+        };
+        assert_eq!(
+            line.elements,
+            vec![VtPoint::from_xy(1., -0.25), VtPoint::from_xy(1.5, 0.25)]
+        );
+        translated.translate(-1., 0.25);
+        assert_eq!(translated, geometry);
+    }
+
+    #[test]
+    fn test_bbox() {
+        let bbox = BBox {
+            min_x: 0.25,
+            min_y: 0.5,
+            max_x: 0.75,
+            max_y: 0.625,
+        };
+        assert_eq!((bbox.width(), bbox.height()), (0.5, 0.125));
+        assert!(bbox.contains_point(0.25, 0.625));
+        assert!(!bbox.contains_point(0.8, 0.6));
+        let touching = BBox {
+            min_x: 0.75,
+            min_y: 0.,
+            max_x: 1.,
+            max_y: 0.5,
+        };
+        assert!(bbox.intersects(&touching) && touching.intersects(&bbox));
+        let apart = BBox {
+            min_y: 0.7,
+            max_y: 0.8,
+            ..touching
+        };
+        assert!(!bbox.intersects(&apart));
+        assert!(!bbox.intersects(&BBox::default()));
+    }
+
+    #[test]
+    fn test_transform() {
+        let mut geometry = VtGeometry::MultiPolygon(vec![vec![VtLinearRing::new(vec![
+            VtPoint::new(0., 0., 1.),
+            VtPoint::new(0., 0.5, 0.),
+            VtPoint::new(0.5, 0.5, 0.),
+            VtPoint::new(0., 0., 1.),
+        ])]]);
+        geometry.transform(|x, y| (x * 2., y * 2.));
+        let VtGeometry::MultiPolygon(polygons) = &geometry else {
+            panic!("not a multi polygon");
+        };
+        let ring = &polygons[0][0];
+        assert_eq!(ring.elements[2], VtPoint::new(1., 1., 0.));
+        assert_eq!(ring.elements[0].z, 1.);
+        assert_eq!(ring.area, 0.125);
+
+        geometry.recompute_metrics();
+        let VtGeometry::MultiPolygon(polygons) = &geometry else {
+            panic!("not a multi polygon");
+        };
+        assert_eq!(polygons[0][0].area, 0.5);
+
+        let mut line = VtGeometry::LineString(VtLineString::new(vec![
+            VtPoint::from_xy(0., 0.),
+            VtPoint::from_xy(0.3, 0.4),
+        ]));
+        line.transform(|x, y| (y, x * 10.));
+        line.recompute_metrics();
+        let VtGeometry::LineString(line) = line else {
+            panic!("not a line string");
+        };
+        assert_eq!(line.elements[1], VtPoint::from_xy(0.4, 3.));
+        assert!((line.dist - 0.4f64.hypot(3.)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_mvt_geometry_type() {
+        let point = VtPoint::new(0., 0., 0.);
+        let line = VtLineString::new(vec![point, point]);
+        let ring = VtLinearRing::new(vec![point, point, point, point]);
+        let geometries = [
+            (VtGeometry::Point(point), 1),
+            (VtGeometry::MultiPoint(VtMultiPoint::new(vec![point])), 1),
+            (VtGeometry::LineString(line.clone()), 2),
+            (VtGeometry::MultiLineString(vec![line]), 2),
+            (VtGeometry::Polygon(vec![ring.clone()]), 3),
+            (VtGeometry::MultiPolygon(vec![vec![ring]]), 3),
+            (
+                VtGeometry::GeometryCollection(vec![VtGeometry::Point(point)]),
+                0,
+            ),
+        ];
+        for (geometry, expected) in geometries {
+            assert_eq!(geometry.mvt_geometry_type(), expected, "{geometry:?}");
+        }
+    }
+    #[test]
+    fn test_bounding_circle() {
+        let points = VtGeometry::MultiPoint(VtMultiPoint::new(vec![
+            VtPoint::from_xy(0., 0.),
+            VtPoint::from_xy(4., 0.),
+            VtPoint::from_xy(2., 3.),
+        ]));
+        let (center, radius) = points.bounding_circle().unwrap();
+        assert_eq!((center.x, center.y), (2., 1.));
+        assert_eq!(radius, 5f64.sqrt());
+        let collection = VtGeometry::GeometryCollection(vec![
+            points,
+            VtGeometry::Point(VtPoint::from_xy(2., -9.)),
+        ]);
+        let (center, radius) = collection.bounding_circle().unwrap();
+        assert_eq!((center.x, center.y), (2., -1.5));
+        assert_eq!(radius, 7.5);
+
+        // The closing point of a ring is counted once.
+        let ring = VtLinearRing::from_slice(&[
+            VtPoint::from_xy(0., 0.),
+            VtPoint::from_xy(2., 0.),
+            VtPoint::from_xy(2., 2.),
+            VtPoint::from_xy(0., 2.),
+            VtPoint::from_xy(0., 0.),
+        ]);
+        let (center, radius) = VtGeometry::Polygon(vec![ring]).bounding_circle().unwrap();
+        assert_eq!((center.x, center.y), (1., 1.));
+        assert_eq!(radius, 2f64.sqrt());
+
+        assert_eq!(
+            VtGeometry::MultiPoint(VtMultiPoint::default()).bounding_circle(),
+            None
+        );
+        assert_eq!(
+            VtGeometry::GeometryCollection(Vec::new()).bounding_circle(),
+            None
+        );
+    }
+    #[test]
+    fn test_centroid() {
+        let points = |points: &[(f64, f64)]| {
+            points
+                .iter()
+                .map(|&(x, y)| VtPoint::from_xy(x, y))
+                .collect::<Vec<_>>()
+        };
+        let square = |min: f64, max: f64| {
+            VtLinearRing::new(points(&[
+                (min, min),
+                (max, min),
+                (max, max),
+                (min, max),
+                (min, min),
+            ]))
+        };
+        let line = VtLineString::new(points(&[(0., 0.), (3., 0.), (3., 1.)]));
+        let geometries = [
+            (
+                VtGeometry::MultiPoint(VtMultiPoint::new(points(&[(0., 0.), (2., 1.)]))),
+                (1., 0.5),
+            ),
+            (VtGeometry::LineString(line.clone()), (1.875, 0.125)),
+            (VtGeometry::Polygon(vec![square(0., 4.)]), (2., 2.)),
+            // The hole shifts the centroid away from it.
+            (
+                VtGeometry::Polygon(vec![square(0., 4.), square(0., 2.)]),
+                (7. / 3., 7. / 3.),
+            ),
+            // Lines are left out next to a polygon, and degenerate rings fall back to points.
+            (
+                VtGeometry::GeometryCollection(vec![
+                    VtGeometry::LineString(line),
+                    VtGeometry::Polygon(vec![square(0., 4.)]),
+                ]),
+                (2., 2.),
+            ),
+            (VtGeometry::Polygon(vec![square(1., 1.)]), (1., 1.)),
+        ];
+        for (geometry, (x, y)) in geometries {
+            let centroid = geometry.centroid().unwrap();
+            assert!((centroid.x - x).abs() < 1e-12, "{geometry:?}: {centroid:?}");
+            assert!((centroid.y - y).abs() < 1e-12, "{geometry:?}: {centroid:?}");
+        }
+        assert_eq!(
+            VtGeometry::MultiPoint(VtMultiPoint::default()).centroid(),
+            None
+        );
+    }
+    #[test]
+    fn test_reverse_vertex_order() {
+        let ring = |points: &[(f64, f64)]| {
+            VtLinearRing::new(
+                points
+                    .iter()
+                    .map(|&(x, y)| VtPoint::from_xy(x, y))
+                    .collect(),
+            )
+        };
+        let outer = ring(&[(0., 0.), (1., 0.), (1., 1.), (0., 1.), (0., 0.)]);
+        let hole = ring(&[(0.25, 0.25), (0.25, 0.75), (0.75, 0.75), (0.25, 0.25)]);
+        assert!(outer.is_clockwise() && !hole.is_clockwise());
+        let original = VtGeometry::MultiPolygon(vec![vec![outer, hole]]);
+
+        let mut geometry = original.clone();
+        geometry.reverse_vertex_order();
+        let VtGeometry::MultiPolygon(polygons) = &geometry else {
+            panic!("not a multi polygon");
+        };
+        assert!(!polygons[0][0].is_clockwise() && polygons[0][1].is_clockwise());
+        assert_eq!(polygons[0][0].elements[1], VtPoint::from_xy(0., 1.));
+        geometry.reverse_vertex_order();
+        assert_eq!(geometry, original);
+    }
+
+    #[test]
+    fn test_signed_area() {
+        // (0, 0) -> (0, 10) -> (10, 10) -> (10, 0) in degrees: north, east, then south.
+        let (west, east) = (0.5, 0.5 + 10. / 360.);
+        let (south, north) = (0.5, 0.47);
+        let clockwise = VtLinearRing::new(vec![
+            VtPoint::from_xy(west, south),
+            VtPoint::from_xy(west, north),
+            VtPoint::from_xy(east, north),
+            VtPoint::from_xy(east, south),
+            VtPoint::from_xy(west, south),
+        ]);
+        let mut points = clockwise.elements.clone();
+        points.reverse();
+        let counter_clockwise = VtLinearRing::new(points);
+
+        assert!(clockwise.is_clockwise());
+        assert!(!counter_clockwise.is_clockwise());
+        assert!(clockwise.signed_area() > 0.);
+        assert_eq!(counter_clockwise.signed_area(), -clockwise.signed_area());
+        assert_eq!(clockwise.area, clockwise.signed_area());
+        assert_eq!(counter_clockwise.area, clockwise.area);
+    }
+
+    #[test]
+    fn test_cmp_by_id() {
+        let feature = |id: Option<Id>| {
+            let point = VtGeometry::Point(VtPoint::from_xy(0.5, 0.5));
+            VtFeature::new(point, Arc::new(None), id)
+        };
+        let number = |n: serde_json::Number| feature(Some(Id::Number(n)));
+        let string = |s: &str| feature(Some(Id::String(s.to_string())));
+        let mut features = vec![
+            string("b"),
+            number(10.into()),
+            feature(None),
+            number(serde_json::Number::from_f64(2.5).unwrap()),
+            string("a"),
+            number((-3).into()),
+            number(u64::MAX.into()),
+            string("B"),
+            number(2.into()),
+        ];
+        features.sort_by(VtFeature::cmp_by_id);
+        let ids: Vec<_> = features.iter().map(|f| f.id.clone()).collect();
+        assert_eq!(
+            ids,
+            [
+                None,
+                Some(Id::Number((-3).into())),
+                Some(Id::Number(2.into())),
+                Some(Id::Number(serde_json::Number::from_f64(2.5).unwrap())),
+                Some(Id::Number(10.into())),
+                Some(Id::Number(u64::MAX.into())),
+                Some(Id::String("B".to_string())),
+                Some(Id::String("a".to_string())),
+                Some(Id::String("b".to_string())),
+            ]
+        );
+        assert_eq!(string("a").cmp_by_id(&string("a")), Ordering::Equal);
+        assert_eq!(
+            number(9.into()).cmp_by_id(&number(10.into())),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_bbox_display() {
+        let bbox = BBox {
+            min_x: 0.25,
+            min_y: 0.25,
+            max_x: 0.75,
+            max_y: 0.5,
+        };
+        let (west, south, east, north) = bbox.to_wgs84();
+        assert_eq!((west, south, east), (-90., 0., 90.));
+        assert!((north - 66.51326044311186).abs() < 1e-9);
+        assert_eq!(
+            bbox.display_wgs84().to_string(),
+            format!("BBOX(-90, 0, 90, {north})")
+        );
+        assert_eq!(
+            format!("{:.2}", bbox.display_wgs84()),
+            "BBOX(-90.00, 0.00, 90.00, 66.51)"
+        );
+        assert_eq!(
+            bbox.display_mercator().to_string(),
+            "BBOX(0.25, 0.25, 0.75, 0.5)"
+        );
+        let expected = if cfg!(feature = "wgs84_display") {
+            bbox.display_wgs84().to_string()
+        } else {
+            bbox.display_mercator().to_string()
+        };
+        assert_eq!(bbox.to_string(), expected);
+    }
+
+    #[test]
+    fn test_ring_contains_point() {
+        let square = [[0., 0.], [4., 0.], [4., 4.], [0., 4.], [0., 0.]];
+        let ring = VtLinearRing::new(
+            square
+                .iter()
+                .map(|p| VtPoint::from_xy(p[0], p[1]))
+                .collect(),
+        );
+        assert!(ring.contains_point(1., 3.));
+        assert!(!ring.contains_point(5., 3.));
+        assert!(!ring.contains_point(2., -1.));
+        // Edges and vertices are inside.
+        for (x, y) in [(0., 0.), (4., 0.), (4., 4.), (0., 4.), (2., 0.), (4., 2.)] {
+            assert!(ring.contains_point(x, y), "{x} {y}");
+        }
+        // Without the closing point.
+        let open = VtLinearRing::new(ring.elements[..4].to_vec());
+        assert!(open.contains_point(1., 3.) && open.contains_point(0., 2.));
+        assert!(!open.contains_point(-1., 2.));
+        assert!(!VtLinearRing::default().contains_point(0., 0.));
+    }
+    #[test]
+    fn test_split_at_x() {
+        // A square spanning the antimeridian, projected beyond the right edge of the world.
+        let ring = VtLinearRing::new(vec![
+            VtPoint::from_xy(0.9, 0.4),
+            VtPoint::from_xy(1.2, 0.4),
+            VtPoint::from_xy(1.2, 0.6),
+            VtPoint::from_xy(0.9, 0.6),
+            VtPoint::from_xy(0.9, 0.4),
+        ]);
+        let (left, right) = ring.split_at_x(1.);
+        assert_eq!((left.len(), right.len()), (1, 1));
+        for part in left.iter().chain(&right) {
+            assert_eq!(part.elements.first(), part.elements.last());
+            assert_eq!(part.area, part.signed_area().abs());
+        }
+        let xs = |ring: &VtLinearRing| {
+            let xs = ring.elements.iter().map(|p| p.x);
+            (
+                xs.clone().fold(f64::INFINITY, f64::min),
+                xs.fold(f64::NEG_INFINITY, f64::max),
+            )
+        };
+        assert_eq!(xs(&left[0]), (0.9, 1.));
+        assert_eq!(xs(&right[0]), (1., 1.2));
+        assert!((left[0].area - 0.02).abs() < 1e-12);
+        assert!((right[0].area - 0.04).abs() < 1e-12);
+        assert!((left[0].area + right[0].area - ring.area).abs() < 1e-12);
+
+        let (whole, none) = ring.split_at_x(2.);
+        assert_eq!((whole.len(), none.len()), (1, 0));
+        assert_eq!(xs(&whole[0]), (0.9, 1.2));
+
+        // Wrapping cuts the same halves, shifting the right one back into the world.
+        let polygon = VtGeometry::Polygon(vec![ring.clone()]);
+        let feature = Arc::new(VtFeature::new(polygon, Arc::new(None), None));
+        let wrapped = crate::wrap::wrap(&[feature], 0., false, false, crate::Boundary::Both);
+        let rings: Vec<_> = wrapped
+            .iter()
+            .map(|feature| match &feature.geometry {
+                VtGeometry::Polygon(rings) => xs(&rings[0]),
+                _ => panic!("expected a polygon"),
+            })
+            .collect();
+        assert_eq!(rings.len(), 2);
+        assert_eq!(rings[0], xs(&left[0]));
+        let (min_x, max_x) = xs(&right[0]);
+        assert!((rings[1].0 - (min_x - 1.)).abs() < 1e-12);
+        assert!((rings[1].1 - (max_x - 1.)).abs() < 1e-12);
+    }
+}
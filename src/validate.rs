@@ -0,0 +1,201 @@
+//! Structural checks for generated tiles and indexes.
+//!
+//! These complement fixture comparisons: they do not know what a tile should contain, only
+//! which properties every tile and index must satisfy.
+use std::{collections::HashSet, sync::Arc};
+
+use geojson::{Position, Value, feature::Id};
+
+use crate::{
+    geojson_vt::{GeoJSONVT, Options, QueryError},
+    tile::Tile,
+    types::{VtFeature, VtGeometry},
+};
+
+/// An invariant broken by a tile, `feature` being the index of the offending feature in the
+/// tile's feature collection.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    MissingGeometry { feature: usize },
+    NotANumber { feature: usize },
+    OutOfRange { feature: usize, position: Position },
+    EmptyGeometry { feature: usize },
+    RingNotClosed { feature: usize },
+    DuplicateId { feature: usize, id: Id },
+}
+
+/// Checks that every coordinate of `tile` is a number within `[-buffer, extent + buffer]`,
+/// that polygons are made of non-empty groups of closed rings and that no geometry is empty.
+pub fn check_tile_invariants(tile: &Tile, extent: u32, buffer: u32) -> Vec<Violation> {
+    let min = -(buffer as f64);
+    let max = extent as f64 + buffer as f64;
+    let mut violations = Vec::new();
+    for (feature, f) in tile.features().enumerate() {
+        let Some(geometry) = &f.geometry else {
+            violations.push(Violation::MissingGeometry { feature });
+            continue;
+        };
+        let mut positions: Vec<&Position> = Vec::new();
+        let mut rings: Vec<&Vec<Position>> = Vec::new();
+        let empty = match &geometry.value {
+            Value::Point(position) => {
+                positions.push(position);
+                false
+            }
+            Value::MultiPoint(line) | Value::LineString(line) => {
+                positions.extend(line);
+                line.is_empty()
+            }
+            Value::MultiLineString(lines) => {
+                positions.extend(lines.iter().flatten());
+                lines.is_empty() || lines.iter().any(Vec::is_empty)
+            }
+            Value::Polygon(polygon) => {
+                rings.extend(polygon);
+                polygon.is_empty()
+            }
+            Value::MultiPolygon(polygons) => {
+                rings.extend(polygons.iter().flatten());
+                polygons.is_empty() || polygons.iter().any(Vec::is_empty)
+            }
+            Value::GeometryCollection(geometries) => geometries.is_empty(),
+        };
+        for ring in rings {
+            positions.extend(ring);
+            if ring.len() < 2 || ring.first() != ring.last() {
+                violations.push(Violation::RingNotClosed { feature });
+            }
+        }
+        for position in positions {
+            if position.iter().any(|v| v.is_nan()) {
+                violations.push(Violation::NotANumber { feature });
+            } else if position.iter().take(2).any(|v| *v < min || *v > max) {
+                violations.push(Violation::OutOfRange {
+                    feature,
+                    position: position.clone(),
+                });
+            }
+        }
+        if empty {
+            violations.push(Violation::EmptyGeometry { feature });
+        }
+    }
+    violations
+}
+
+/// Checks that no two features of `tile` share an id, which holds for indexes built with
+/// `generate_id` unless features are split by `line_metrics` or, at the lowest zooms, copied
+/// across the antimeridian.
+pub fn check_unique_ids(tile: &Tile) -> Vec<Violation> {
+    let mut seen = HashSet::new();
+    tile.features()
+        .enumerate()
+        .filter_map(|(feature, f)| {
+            let id = f.id.as_ref()?;
+            let key = match id {
+                Id::String(s) => format!("s{s}"),
+                Id::Number(n) => format!("n{n}"),
+            };
+            (!seen.insert(key)).then(|| Violation::DuplicateId {
+                feature,
+                id: id.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Outcome of [`check_coverage`], feature indices referring to the order of the source features.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoverageReport {
+    pub zoom: u8,
+    /// Features present in at least one tile.
+    pub covered: usize,
+    /// Features absent because they are smaller than the simplification tolerance of the zoom.
+    pub filtered: Vec<usize>,
+    /// Features absent from every tile for no valid reason.
+    pub missing: Vec<usize>,
+}
+impl CoverageReport {
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Checks that every source feature of `index` appears in at least one tile at zoom `z`, unless
+/// it is filtered out by the tolerance of that zoom.
+///
+/// The tiles are generated from a copy of the index, so `index` itself is left untouched.
+///
+/// Fails with [`QueryError::NoSources`] if the index does not keep its sources, see
+/// [`crate::Options::retain_sources`].
+pub fn check_coverage(index: &GeoJSONVT, z: u8) -> Result<CoverageReport, QueryError> {
+    if !index.has_sources() {
+        return Err(QueryError::NoSources);
+    }
+    let options = index.options().clone();
+    let z = z.min(options.max_zoom);
+    let tagged = index
+        .source()
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            Arc::new(VtFeature {
+                id: Some(Id::Number(i.into())),
+                ..(**f).clone()
+            })
+        })
+        .collect();
+    let tagged = GeoJSONVT::from_source(
+        tagged,
+        Options {
+            min_zoom: 0,
+            ..options.clone()
+        },
+    );
+    let seen: HashSet<u64> = tagged
+        .tiles_at_zoom(z + options.tile_size.zoom_offset())
+        .flat_map(|(_, tile)| {
+            tile.features()
+                .filter_map(|f| match &f.id {
+                    Some(Id::Number(n)) => n.as_u64(),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    let mut report = CoverageReport {
+        zoom: z,
+        ..Default::default()
+    };
+    for (i, feature) in index.source().iter().enumerate() {
+        if seen.contains(&(i as u64)) {
+            report.covered += 1;
+        } else if is_below_tolerance(&feature.geometry, z, &options) {
+            report.filtered.push(i);
+        } else {
+            report.missing.push(i);
+        }
+    }
+    Ok(report)
+}
+
+fn is_below_tolerance(geometry: &VtGeometry, z: u8, options: &Options) -> bool {
+    let (line_tolerance, polygon_tolerance) = options.tile_tolerances(z);
+    if line_tolerance == 0. && polygon_tolerance == 0. {
+        return false;
+    }
+    let sq_tolerance = polygon_tolerance.powi(2);
+    match geometry {
+        VtGeometry::Point(_) | VtGeometry::MultiPoint(_) => false,
+        VtGeometry::LineString(line) => line.dist <= line_tolerance,
+        VtGeometry::MultiLineString(lines) => lines.iter().all(|l| l.dist <= line_tolerance),
+        VtGeometry::Polygon(rings) => rings.iter().all(|r| r.area <= sq_tolerance),
+        VtGeometry::MultiPolygon(polygons) => {
+            polygons.iter().flatten().all(|r| r.area <= sq_tolerance)
+        }
+        VtGeometry::GeometryCollection(geometries) => {
+            geometries.iter().all(|g| is_below_tolerance(g, z, options))
+        }
+    }
+}
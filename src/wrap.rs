@@ -1,28 +1,66 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
-use crate::{clip::clip, types::VtFeature};
+use crate::{
+    clip::{Boundary, clip},
+    types::VtFeature,
+};
 
-fn into_rc_features(features: Vec<VtFeature>) -> Vec<Rc<VtFeature>> {
-    features.into_iter().map(Rc::new).collect::<Vec<_>>()
+fn into_rc_features(features: Vec<VtFeature>) -> Vec<Arc<VtFeature>> {
+    features.into_iter().map(Arc::new).collect::<Vec<_>>()
 }
-fn into_owned_features(features: Vec<Rc<VtFeature>>) -> Vec<VtFeature> {
+fn into_owned_features(features: Vec<Arc<VtFeature>>) -> Vec<VtFeature> {
     features
         .into_iter()
         .map(|f| (*f).clone())
         .collect::<Vec<_>>()
 }
-pub fn wrap(features: Vec<VtFeature>, buffer: f64, line_metrics: bool) -> Vec<Rc<VtFeature>> {
-    let features = into_rc_features(features);
-    let left = clip::<0>(&features, -1. - buffer, buffer, -1., 2., line_metrics);
-    let right = clip::<0>(&features, 1. - buffer, 2. + buffer, -1., 2., line_metrics);
+pub fn wrap(
+    features: &[Arc<VtFeature>],
+    buffer: f64,
+    line_metrics: bool,
+    clipped_area: bool,
+    point_boundary: Boundary,
+) -> Vec<Arc<VtFeature>> {
+    let (k1, k2) = (-1. - buffer, buffer);
+    let left = clip::<0>(
+        features,
+        k1,
+        k2,
+        -1.,
+        2.,
+        line_metrics,
+        clipped_area,
+        point_boundary,
+    );
+    let (k1, k2) = (1. - buffer, 2. + buffer);
+    let right = clip::<0>(
+        features,
+        k1,
+        k2,
+        -1.,
+        2.,
+        line_metrics,
+        clipped_area,
+        point_boundary,
+    );
     let mut left = into_owned_features(left);
     let mut right = into_owned_features(right);
 
     if left.is_empty() && right.is_empty() {
-        return features;
+        return features.to_vec();
     };
 
-    let mut merged = clip::<0>(&features, -buffer, 1. + buffer, 1., 2., line_metrics);
+    let (k1, k2) = (-buffer, 1. + buffer);
+    let mut merged = clip::<0>(
+        features,
+        k1,
+        k2,
+        1.,
+        2.,
+        line_metrics,
+        clipped_area,
+        point_boundary,
+    );
 
     if !left.is_empty() {
         shift_coords(&mut left, 1.0);
@@ -38,6 +76,8 @@ pub fn wrap(features: Vec<VtFeature>, buffer: f64, line_metrics: bool) -> Vec<Rc
     merged
 }
 
+/// Shifts the features by `offset` along x. Distances along lines and rings, including the
+/// `seg_start` and `seg_end` of their clipped parts, do not change.
 pub fn shift_coords(features: &mut [VtFeature], offset: f64) {
     features.iter_mut().for_each(|f| {
         // f.bbox
@@ -45,7 +85,53 @@ pub fn shift_coords(features: &mut [VtFeature], offset: f64) {
             bbox.min_x += offset;
             bbox.max_x += offset;
         }
-        f.geometry.iter_each_point(|p| p.x += offset);
+        f.geometry.translate(offset, 0.);
     });
     //
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GeoJSONVT, Options, convert::convert_feature, types::VtGeometry};
+    use geojson::{Feature, GeoJson, Geometry, Value};
+
+    #[test]
+    fn test_wrapped_line_metrics() {
+        let line = Geometry::new(Value::LineString(vec![vec![170., 10.], vec![190., 10.]]));
+        let feature = convert_feature(Feature::from(line.clone()), 0., 0., None, None).unwrap();
+        let dist = match &feature.geometry {
+            VtGeometry::LineString(line) => line.dist,
+            _ => unreachable!(),
+        };
+        let wrapped = wrap(&[Arc::new(feature)], 0., true, false, Boundary::Both);
+        let segments: Vec<_> = wrapped
+            .iter()
+            .map(|feature| match &feature.geometry {
+                VtGeometry::LineString(line) => (line.seg_start / dist, line.seg_end / dist),
+                _ => unreachable!(),
+            })
+            .collect();
+        // The part beyond the antimeridian is shifted back to the west of the world, keeping
+        // its distances along the line.
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].0, 0.);
+        assert!((segments[0].1 - 0.5).abs() < 1e-12);
+        assert_eq!(segments[1], (segments[0].1, 1.));
+
+        let options = Options {
+            buffer: 0,
+            line_metrics: true,
+            ..Options::default()
+        };
+        let mut geojsonvt = GeoJSONVT::from_geojson(&GeoJson::from(line), &options);
+        let mut metrics = |x| {
+            let tile = geojsonvt.tile(1, x, 0);
+            let properties = tile.feature_collection.features[0].properties.as_ref();
+            let value = |key: &str| properties.unwrap()[key].as_f64().unwrap();
+            (value("mapbox_clip_start"), value("mapbox_clip_end"))
+        };
+        assert_eq!(metrics(1), segments[0]);
+        assert_eq!(metrics(0), segments[1]);
+    }
+}
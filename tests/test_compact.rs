@@ -0,0 +1,36 @@
+use geojson::GeoJson;
+use geojsonvt::{CompactTile, GeoJSONVT, Options};
+use std::fs::File;
+use std::io::BufReader;
+
+#[test]
+fn test_compact_tiles_us_states() {
+    let geojson = GeoJson::from_reader(BufReader::new(
+        File::open("tests/fixtures/us-states.json").unwrap(),
+    ))
+    .unwrap();
+    let mut index = GeoJSONVT::from_geojson(&geojson, &Options::default());
+
+    let tile = index.tile(4, 3, 6).clone();
+    let compact = index.compact_tile(4, 3, 6);
+    assert_eq!(compact.to_tile(), Ok(tile.clone()));
+    assert_eq!(
+        CompactTile::from_bytes(compact.into_bytes()).and_then(|c| c.to_tile()),
+        Ok(tile)
+    );
+
+    // Every tile of the indexed zooms round-trips, at a fraction of the size of its JSON.
+    let (mut json_bytes, mut compact_bytes) = (0, 0);
+    for z in 0..=index.options().index_max_zoom {
+        for (_, tile) in index.tiles_at_zoom(z) {
+            let compact = tile.to_compact();
+            assert_eq!(compact.to_tile().as_ref(), Ok(&tile));
+            json_bytes += tile.to_geojson_string().len();
+            compact_bytes += compact.len();
+        }
+    }
+    assert!(
+        json_bytes >= 5 * compact_bytes,
+        "{json_bytes} bytes of JSON, {compact_bytes} compact"
+    );
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "compress")]
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use geojson::GeoJson;
+use geojsonvt::{Encoding, GeoJSONVT, Options};
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+fn load(path: &str) -> GeoJson {
+    GeoJson::from_reader(BufReader::new(File::open(path).unwrap())).unwrap()
+}
+
+fn decompress(mut decoder: impl Read) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    decoder.read_to_end(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn test_compressed_tile() {
+    let mut geojsonvt =
+        GeoJSONVT::from_geojson(&load("tests/fixtures/us-states.json"), &Options::default());
+    let tile = geojsonvt.tile(7, 37, 48).clone();
+    let json = tile.to_geojson_bytes();
+    assert!(!tile.feature_collection.features.is_empty());
+
+    let gzip = tile.to_json_gzip(6);
+    assert!(gzip.len() < json.len());
+    assert_eq!(decompress(GzDecoder::new(&gzip[..])), json);
+    let deflate = tile.to_json_deflate(9);
+    assert!(deflate.len() < json.len());
+    assert_eq!(decompress(ZlibDecoder::new(&deflate[..])), json);
+
+    let gzip_encoding = Encoding::JsonGzip { level: 6 };
+    assert_eq!(geojsonvt.tile_bytes(7, 37, 48, gzip_encoding), gzip);
+    assert_eq!(geojsonvt.tile_bytes(7, 37, 48, Encoding::Json), json);
+    assert_eq!(geojsonvt.encoded_payloads(), 2);
+}
+
+#[test]
+fn test_tile_bytes_cache() {
+    let encoding = Encoding::JsonDeflate { level: 6 };
+    for capacity in [None, Some(4)] {
+        let options = Options {
+            tile_cache_capacity: capacity,
+            ..Options::default()
+        };
+        let mut geojsonvt =
+            GeoJSONVT::from_geojson(&load("tests/fixtures/us-states.json"), &options);
+        let first = geojsonvt.tile_bytes(7, 37, 48, encoding);
+        assert_eq!(geojsonvt.tile_bytes(7, 37, 48, encoding), first);
+        assert_eq!(geojsonvt.encoded_payloads(), 1);
+
+        // A different level is another payload.
+        let _ = geojsonvt.tile_bytes(7, 37, 48, Encoding::JsonDeflate { level: 1 });
+        assert_eq!(geojsonvt.encoded_payloads(), 2);
+
+        // Evicting the tile drops its payloads, which are encoded again once it is rebuilt.
+        assert!(geojsonvt.evict_tile(7, 37, 48));
+        assert_eq!(geojsonvt.tile_bytes(7, 37, 48, encoding), first);
+        assert_eq!(geojsonvt.encoded_payloads(), 3);
+    }
+
+    // Empty tiles are not cached.
+    let mut geojsonvt =
+        GeoJSONVT::from_geojson(&load("tests/fixtures/us-states.json"), &Options::default());
+    let empty = geojsonvt.tile_bytes(7, 0, 0, Encoding::Json);
+    assert_eq!(empty, geojsonvt.tile(7, 0, 0).to_geojson_bytes());
+    assert_eq!(geojsonvt.encoded_payloads(), 0);
+}
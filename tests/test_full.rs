@@ -3,7 +3,7 @@ use geojson::feature::Id;
 use geojson::{
     Feature, FeatureCollection, GeoJson, Geometry, JsonValue, PointType, PolygonType, Position,
 };
-use geojsonvt::{GeoJSONVT, Options};
+use geojsonvt::{GeoJSONVT, Options, TileCoord, YAxis};
 use serde_json::{Number, Value};
 use std::collections::HashMap;
 use std::f64::consts::PI;
@@ -23,6 +23,25 @@ fn test_get_tile_us_state() {
             .unwrap(),
     );
     assert_eq!(features, &expected);
+
+    // Unclipped features carry no line metrics, so the tile is unchanged with them enabled.
+    let line_metrics = Options {
+        line_metrics: true,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &line_metrics);
+    assert_eq!(&geojsonvt.tile(7, 37, 48).feature_collection, &expected);
+
+    let line =
+        GeoJson::from_str(r#"{"type": "LineString", "coordinates": [[-100, 40], [-90, 41]]}"#)
+            .unwrap();
+    let mut geojsonvt = GeoJSONVT::from_geojson(&line, &line_metrics);
+    let features = &geojsonvt.tile(0, 0, 0).feature_collection.features;
+    assert_eq!(features[0].properties, None);
+    let features = &geojsonvt.tile(7, 30, 48).feature_collection.features;
+    let properties = features[0].properties.as_ref().unwrap();
+    assert!(properties.contains_key("mapbox_clip_start"));
+    assert!(properties.contains_key("mapbox_clip_end"));
 }
 #[test]
 fn test_get_tile_generated_ids() {
@@ -101,6 +120,31 @@ fn test_get_tile_polygon_clipping_bug() {
         .as_ref()
         .unwrap();
     assert_eq!(actual, &expected);
+    let corner = tile.pixel_to_lnglat(3072., 5120.);
+
+    // With y up, the same square is flipped around the middle of the tile.
+    let mut geojsonvt = GeoJSONVT::from_geojson(
+        &geojson,
+        &Options {
+            buffer: 1024,
+            y_axis: YAxis::CartesianCoords,
+            ..Options::default()
+        },
+    );
+    let tile = geojsonvt.tile(5, 19, 9);
+    let expected = Geometry::new(geojson::Value::Polygon(PolygonType::from(&[vec![
+        PointType::from(&[3072., 1024.]),
+        PointType::from(&[5120., 1024.]),
+        PointType::from(&[5120., -1024.]),
+        PointType::from(&[3072., -1024.]),
+        PointType::from(&[3072., 1024.]),
+    ]])));
+    let actual = tile.feature_collection.features[0]
+        .geometry
+        .as_ref()
+        .unwrap();
+    assert_eq!(actual, &expected);
+    assert_eq!(tile.pixel_to_lnglat(3072., -1024.), corner);
 }
 
 #[test]
@@ -160,13 +204,13 @@ fn test_get_tile_projection() {
         let total_features = (1u32 << z) as f64 * 8192.;
         let to_web_mercator_lon = |point: &Position| {
             let x0 = 8192.0 * x as f64;
-            return (x0 + point[0]) * 360.0 / total_features - 180.0;
+            (x0 + point[0]) * 360.0 / total_features - 180.0
         };
 
         let to_web_mercator_lat = |point: &Position| {
             let y0 = 8192.0 * y as f64;
             let y2 = 180.0 - (y0 + point[1]) * 360.0 / total_features;
-            return 360.0 / PI * (y2 * PI / 180.0).exp().atan() - 90.0;
+            360.0 / PI * (y2 * PI / 180.0).exp().atan() - 90.0
         };
         let tolerance = 0.1 / (1. + z as f64);
         assert!(
@@ -180,7 +224,21 @@ fn test_get_tile_projection() {
         assert!(
             37.780424620898664f64.abs_diff_eq(&to_web_mercator_lat(&line_string[1]), tolerance)
         );
+
+        assert_eq!(tile.coord, Some(TileCoord::new(x, y, z)));
+        assert_eq!(tile.extent, 8192);
+        let (lng, lat) = tile
+            .pixel_to_lnglat(line_string[1][0], line_string[1][1])
+            .unwrap();
+        assert!((-122.41707086563109f64).abs_diff_eq(&lng, tolerance));
+        assert!(37.780424620898664f64.abs_diff_eq(&lat, tolerance));
+        let (px, py) = tile.lnglat_to_pixel(lng, lat).unwrap();
+        assert!(px.abs_diff_eq(&line_string[1][0], 1e-6));
+        assert!(py.abs_diff_eq(&line_string[1][1], 1e-6));
     }
+    let empty = geojsonvt.tile(20, 0, 0);
+    assert_eq!(empty.coord, None);
+    assert_eq!(empty.pixel_to_lnglat(0., 0.), None);
 }
 
 #[test]
@@ -234,7 +292,7 @@ fn test_tiles() {
         let mut actual = gen_tiles(&data, max_zoom, max_points, line_metrics);
         let expected =
             parse_json_tiles(serde_json::from_reader(File::open(expected_file).unwrap()).unwrap());
-        for (_key, value) in &mut actual {
+        for value in actual.values_mut() {
             value.features = value
                 .features
                 .iter()
@@ -280,11 +338,12 @@ fn gen_tiles(
     let mut output = HashMap::new();
     let tile_coords: Vec<_> = geojsonvt
         .internal_tiles()
-        .iter()
-        .map(|(_key, tile)| (tile.z, tile.x, tile.y))
+        .values()
+        .map(|tile| tile.coord())
         .collect();
 
-    for (z, x, y) in tile_coords {
+    for coord in tile_coords {
+        let (z, x, y) = (coord.z(), coord.x(), coord.y());
         let key = format!("z{}-{}-{}", z, x, y);
         output.insert(key, geojsonvt.tile(z, x, y).feature_collection.clone());
     }
@@ -325,75 +384,52 @@ fn parse_json_tile(tile: JsonValue) -> FeatureCollection {
         if let (Some(JsonValue::Number(tile_type)), Some(JsonValue::Array(tile_geom))) =
             (feature.get("type"), feature.get("geometry"))
         {
-            let geom_type = tile_type.as_u64().unwrap();
-            if geom_type == 1 {
-                if tile_geom.len() == 1 {
-                    let pt = tile_geom.first().unwrap();
-                    assert_eq!(pt.as_array().unwrap().len(), 2);
-                    feat.geometry = Some(Geometry::new(geojson::Value::Point(PointType::from(&[
-                        pt.get(0).unwrap().as_f64().unwrap(),
-                        pt.get(1).unwrap().as_f64().unwrap(),
-                    ]))))
-                } else {
-                    let mut points = vec![];
-                    for pt in tile_geom {
-                        points.push(PointType::from(&[
-                            pt.get(0).unwrap().as_f64().unwrap(),
-                            pt.get(1).unwrap().as_f64().unwrap(),
-                        ]))
-                    }
-                    feat.geometry = Some(Geometry::new(geojson::Value::MultiPoint(points)))
-                }
-            } else if geom_type == 2 {
-                // linestring geometry
-                let mut multi_line: Vec<Vec<Position>> = Vec::new();
-                let is_multi = tile_geom.len() > 1;
-                for part in tile_geom {
-                    let mut line_string: Vec<Position> = Vec::new();
-                    for pt in part.as_array().unwrap() {
-                        assert_eq!(pt.as_array().unwrap().len(), 2);
-                        line_string.push(PointType::from(&[
-                            pt.get(0).unwrap().as_f64().unwrap(),
-                            pt.get(1).unwrap().as_f64().unwrap(),
-                        ]));
-                    }
-                    if !is_multi {
-                        feat.geometry =
-                            Some(Geometry::new(geojson::Value::LineString(line_string)));
-                        break;
-                    } else {
-                        multi_line.push(line_string);
-                    }
-                }
-
-                if is_multi {
-                    feat.geometry =
-                        Some(Geometry::new(geojson::Value::MultiLineString(multi_line)));
-                }
-            } else if geom_type == 3 {
-                // polygon geometry
-                let mut poly: PolygonType = Vec::new();
-                for ring in tile_geom {
-                    let mut linear_ring: Vec<PointType> = Vec::new();
-                    for pt in ring.as_array().unwrap() {
-                        assert_eq!(pt.as_array().unwrap().len(), 2);
-                        linear_ring.push(PointType::from(&[
-                            pt.get(0).unwrap().as_f64().unwrap(),
-                            pt.get(1).unwrap().as_f64().unwrap(),
-                        ]));
-                    }
-                    poly.push(linear_ring);
-                }
-                feat.geometry = Some(Geometry::new(geojson::Value::Polygon(poly)));
-            } else {
-                panic!("unknown geometry type")
-            }
+            // Geometry types of the Mapbox Vector Tile specification, as returned by
+            // `VtGeometry::mvt_geometry_type`.
+            let geom_type = u8::try_from(tile_type.as_u64().unwrap()).unwrap();
+            feat.geometry = Some(Geometry::new(match geom_type {
+                1 => point_value(tile_geom),
+                2 => line_value(tile_geom),
+                3 => polygon_value(tile_geom),
+                _ => panic!("unknown geometry type {geom_type}"),
+            }));
         }
         features.push(feat);
     }
-    return FeatureCollection {
+    FeatureCollection {
         bbox: None,
         features,
         foreign_members: None,
-    };
+    }
+}
+fn position(point: &JsonValue) -> Position {
+    assert_eq!(point.as_array().unwrap().len(), 2);
+    PointType::from(&[
+        point.get(0).unwrap().as_f64().unwrap(),
+        point.get(1).unwrap().as_f64().unwrap(),
+    ])
+}
+fn point_value(geometry: &[JsonValue]) -> geojson::Value {
+    match geometry {
+        [point] => geojson::Value::Point(position(point)),
+        points => geojson::Value::MultiPoint(points.iter().map(position).collect()),
+    }
+}
+fn line_value(geometry: &[JsonValue]) -> geojson::Value {
+    let mut lines: Vec<Vec<Position>> = geometry
+        .iter()
+        .map(|line| line.as_array().unwrap().iter().map(position).collect())
+        .collect();
+    if lines.len() == 1 {
+        geojson::Value::LineString(lines.pop().unwrap())
+    } else {
+        geojson::Value::MultiLineString(lines)
+    }
+}
+fn polygon_value(geometry: &[JsonValue]) -> geojson::Value {
+    let polygon: PolygonType = geometry
+        .iter()
+        .map(|ring| ring.as_array().unwrap().iter().map(position).collect())
+        .collect();
+    geojson::Value::Polygon(polygon)
 }
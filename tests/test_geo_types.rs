@@ -0,0 +1,101 @@
+#![cfg(feature = "geo-types")]
+
+use approx::assert_abs_diff_eq;
+use geo_types::{
+    Coord, Geometry, GeometryCollection, Line, LineString, MultiLineString, MultiPoint,
+    MultiPolygon, Point, Polygon, Rect, Triangle, coord,
+};
+use geojsonvt::{VtGeometry, VtPoint};
+
+fn ring(x: f64, y: f64, size: f64) -> LineString<f64> {
+    LineString::from(vec![
+        (x, y),
+        (x + size, y),
+        (x + size, y + size),
+        (x, y + size),
+        (x, y),
+    ])
+}
+
+fn geometries() -> Vec<Geometry<f64>> {
+    let polygon = Polygon::new(ring(0.1, 0.1, 0.4), vec![ring(0.2, 0.2, 0.1)]);
+    let line = LineString::from(vec![(0.1, 0.2), (0.3, 0.4), (0.5, 0.2)]);
+    let simple = vec![
+        Point::new(0.25, 0.75).into(),
+        MultiPoint::from(vec![(0.1, 0.2), (0.3, 0.4)]).into(),
+        line.clone().into(),
+        MultiLineString::new(vec![line.clone(), ring(0.5, 0.5, 0.1)]).into(),
+        polygon.clone().into(),
+        MultiPolygon::new(vec![polygon, Polygon::new(ring(0.6, 0.6, 0.2), vec![])]).into(),
+    ];
+    let collection = GeometryCollection::from(simple.clone());
+    simple
+        .into_iter()
+        .chain([Geometry::GeometryCollection(collection)])
+        .collect()
+}
+
+#[test]
+fn test_geo_types_round_trip() {
+    for geometry in geometries() {
+        let vt_geometry = VtGeometry::from(&geometry);
+        assert_eq!(Geometry::from(&vt_geometry), geometry);
+        // Lengths and areas are computed, so the round trip the other way is exact too.
+        let back = VtGeometry::from(&Geometry::from(&vt_geometry));
+        assert_eq!(back, vt_geometry);
+    }
+
+    let point = VtPoint::from(coord! { x: 0.25, y: 0.5 });
+    assert_eq!(point, VtPoint::from_xy(0.25, 0.5));
+    assert_eq!(Coord::from(point), coord! { x: 0.25, y: 0.5 });
+
+    let VtGeometry::Polygon(rings) = VtGeometry::from(&geometries()[4]) else {
+        panic!("expected a polygon");
+    };
+    assert_abs_diff_eq!(rings[0].area, 0.16, epsilon = 1e-12);
+    assert_abs_diff_eq!(rings[1].area, 0.01, epsilon = 1e-12);
+}
+
+#[test]
+fn test_geo_types_shapes() {
+    let line = Line::new((0., 0.), (0.5, 0.5));
+    let VtGeometry::LineString(vt_line) = VtGeometry::from(&Geometry::Line(line)) else {
+        panic!("expected a line string");
+    };
+    assert_eq!(vt_line.elements.len(), 2);
+    assert_abs_diff_eq!(vt_line.dist, 0.5f64.hypot(0.5), epsilon = 1e-12);
+
+    let rect = Rect::new((0.1, 0.1), (0.5, 0.3));
+    let triangle = Triangle::new((0., 0.).into(), (0.5, 0.).into(), (0., 0.5).into());
+    for (geometry, area) in [(Geometry::Rect(rect), 0.08), (triangle.into(), 0.125)] {
+        let VtGeometry::Polygon(rings) = VtGeometry::from(&geometry) else {
+            panic!("expected a polygon");
+        };
+        assert_eq!(rings.len(), 1);
+        assert_eq!(rings[0].elements.first(), rings[0].elements.last());
+        assert_abs_diff_eq!(rings[0].area, area, epsilon = 1e-12);
+    }
+}
+
+#[test]
+fn test_geo_types_lnglat() {
+    let lnglat = Geometry::from(LineString::from(vec![(-120., 45.), (0., 0.), (150., -60.)]));
+    let vt_geometry = VtGeometry::from_geo_lnglat(&lnglat);
+    let VtGeometry::LineString(line) = &vt_geometry else {
+        panic!("expected a line string");
+    };
+    assert_abs_diff_eq!(line.elements[1].x, 0.5);
+    assert_abs_diff_eq!(line.elements[1].y, 0.5);
+    assert!(line.elements[0].y < 0.5 && line.elements[2].y > 0.5);
+
+    let Geometry::LineString(back) = vt_geometry.to_geo_lnglat() else {
+        panic!("expected a line string");
+    };
+    let Geometry::LineString(expected) = lnglat else {
+        unreachable!()
+    };
+    for (a, b) in back.coords().zip(expected.coords()) {
+        assert_abs_diff_eq!(a.x, b.x, epsilon = 1e-9);
+        assert_abs_diff_eq!(a.y, b.y, epsilon = 1e-9);
+    }
+}
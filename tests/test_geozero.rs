@@ -0,0 +1,162 @@
+#![cfg(feature = "geozero")]
+
+use flatgeobuf::FgbReader;
+use geojson::{FeatureCollection, GeoJson};
+use geojsonvt::{ConvertWarning, GeoJSONVT, Options, Tile, TileOrigin};
+use geozero::ProcessToJson;
+use std::fs::File;
+use std::io::BufReader;
+use std::str::FromStr;
+
+fn load(path: &str) -> GeoJson {
+    GeoJson::from_reader(BufReader::new(File::open(path).unwrap())).unwrap()
+}
+
+fn from_fgb(path: &str, options: Options) -> GeoJSONVT {
+    let reader = FgbReader::open(File::open(path).unwrap()).unwrap();
+    GeoJSONVT::from_geozero(reader.select_all().unwrap(), options).unwrap()
+}
+
+/// Features of a tile as sorted JSON strings, since FlatGeobuf reorders features along its index.
+fn sorted_features(tile: &Tile) -> Vec<String> {
+    let mut features: Vec<String> = tile
+        .feature_collection
+        .features
+        .iter()
+        .map(|f| serde_json::to_string(f).unwrap())
+        .collect();
+    features.sort();
+    features
+}
+
+#[test]
+fn test_from_flatgeobuf_matches_geojson() {
+    let options = Options {
+        index_max_points: 0,
+        ..Options::default()
+    };
+    let mut fgb = from_fgb("tests/fixtures/us-states.fgb", options.clone());
+    // FlatGeobuf has no feature ids and stores `density` as a double column.
+    let GeoJson::FeatureCollection(mut fc) = load("tests/fixtures/us-states.json") else {
+        panic!("not a feature collection");
+    };
+    for feature in &mut fc.features {
+        feature.id = None;
+        let density = feature.property("density").unwrap().as_f64().unwrap();
+        feature.set_property("density", density);
+    }
+    let mut geojson = GeoJSONVT::new(fc, options);
+
+    assert_eq!(
+        fgb.index_stats().total_tiles,
+        geojson.index_stats().total_tiles
+    );
+    let coords = geojson.tile_coords().clone();
+    for coord in coords {
+        let expected = sorted_features(geojson.tile(coord.z(), coord.x(), coord.y()));
+        let actual = sorted_features(fgb.tile(coord.z(), coord.x(), coord.y()));
+        assert_eq!(
+            actual,
+            expected,
+            "tile {}/{}/{}",
+            coord.z(),
+            coord.x(),
+            coord.y()
+        );
+    }
+}
+
+#[test]
+fn test_tile_as_geozero_datasource() {
+    let mut geojsonvt = GeoJSONVT::from_geojson(
+        &load("tests/fixtures/us-states.json"),
+        &Options {
+            generate_id: true,
+            ..Options::default()
+        },
+    );
+    let mut tile = geojsonvt.tile(7, 37, 48).clone();
+    let json = tile.to_json().unwrap();
+    let written = FeatureCollection::from_str(&json).unwrap();
+
+    assert_eq!(
+        written.features.len(),
+        tile.feature_collection.features.len()
+    );
+    for (written, feature) in written
+        .features
+        .iter()
+        .zip(&tile.feature_collection.features)
+    {
+        assert_eq!(written.geometry, feature.geometry);
+        assert_eq!(written.properties, feature.properties);
+    }
+
+    tile.origin = TileOrigin::Center;
+    assert!(tile.to_json().is_err());
+}
+
+#[cfg(feature = "flatgeobuf")]
+#[test]
+fn test_from_flatgeobuf_bbox() {
+    let options = Options {
+        index_max_points: 0,
+        ..Options::default()
+    };
+    let open = || File::open("tests/fixtures/us-states.fgb").unwrap();
+    let full = GeoJSONVT::from_flatgeobuf(open(), options.clone(), None).unwrap();
+    assert_eq!(
+        full.index_stats().total_tiles,
+        from_fgb("tests/fixtures/us-states.fgb", options.clone())
+            .index_stats()
+            .total_tiles
+    );
+
+    // Around Colorado, anything the R-tree selects beyond the box is clipped away.
+    let bbox = [-109.05, 36.99, -102.04, 41.];
+    let restricted = GeoJSONVT::from_flatgeobuf(
+        open(),
+        Options {
+            clip_to_bbox: Some(bbox),
+            ..options.clone()
+        },
+        Some(bbox),
+    )
+    .unwrap();
+    assert!(restricted.index_stats().total_tiles < full.index_stats().total_tiles);
+
+    let (x1, x2) = (bbox[0] / 360. + 0.5, bbox[2] / 360. + 0.5);
+    let p = options.buffer as f64 / options.extent as f64;
+    for (coord, tile) in restricted.tiles_at_zoom(5) {
+        let x = coord.x() as f64;
+        assert!((x - p) / 32. <= x2 && (x + 1. + p) / 32. >= x1);
+        assert!(!tile.feature_collection.features.is_empty());
+    }
+    let names: Vec<_> = restricted
+        .tiles_at_zoom(0)
+        .flat_map(|(_, tile)| tile.feature_collection.features)
+        .map(|f| f.property("name").unwrap().as_str().unwrap().to_string())
+        .collect();
+    assert!(names.contains(&"Colorado".to_string()));
+    assert!(!names.contains(&"California".to_string()));
+}
+
+#[test]
+fn test_from_geozero_feature_limit() {
+    let options = Options {
+        feature_limit: Some(5),
+        ..Options::default()
+    };
+    let index = from_fgb("tests/fixtures/us-states.fgb", options);
+    let GeoJson::FeatureCollection(fc) = load("tests/fixtures/us-states.json") else {
+        panic!("not a feature collection");
+    };
+    assert_eq!(
+        index.warnings().last(),
+        Some(&ConvertWarning::FeatureLimitReached {
+            total_input_features: fc.features.len()
+        })
+    );
+    let source = &index.internal_tiles()[&0].source_features();
+    assert!(source.len() >= 5 && source.len() < fc.features.len());
+}
@@ -0,0 +1,2859 @@
+use geojson::{FeatureCollection, GeoJson, Geometry, Value, feature::Id};
+use geojsonvt::{
+    Boundary, BuildError, CompactTile, ConvertError, ConvertOptions, ConvertWarning, DedupePolicy,
+    EmptyReason, FeatureOrder, GeoJSONVT, IdPolicy, NumberStyle, OnTileCreated, Options,
+    OptionsError, QueryError, SkipReason, SkippedFeature, SortDirection, TileCoord, TileError,
+    TileOrigin, TilePropertyHook, TileStatus, VtFeature, VtGeometry, VtLineString, VtLinearRing,
+    VtPoint, convert, split_once,
+};
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::BufReader;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+fn load(path: &str) -> GeoJson {
+    GeoJson::from_reader(BufReader::new(File::open(path).unwrap())).unwrap()
+}
+
+#[test]
+fn test_coverage_ratio() {
+    let geojsonvt = GeoJSONVT::from_geojson(
+        &load("tests/fixtures/us-states.json"),
+        &Options {
+            index_max_points: 0,
+            ..Options::default()
+        },
+    );
+    assert_eq!(geojsonvt.coverage_ratio(0), 1.);
+    let stats = geojsonvt.index_stats();
+    for z in 1..=5 {
+        let expected = stats.non_empty_tiles_per_zoom[&z] as f64 / 4f64.powi(z as i32);
+        assert_eq!(geojsonvt.coverage_ratio(z), expected);
+        assert!(geojsonvt.coverage_ratio(z) <= geojsonvt.coverage_ratio(z - 1));
+    }
+    assert_eq!(geojsonvt.coverage_ratio(6), 0.);
+    assert_eq!(geojsonvt.coverage_ratio(u8::MAX), 0.);
+
+    // Tiles only reached by the buffer of their neighbours are left empty once clipped to the
+    // extent, and are not counted.
+    let clipped = GeoJSONVT::from_geojson(
+        &load("tests/fixtures/us-states.json"),
+        &Options {
+            index_max_points: 0,
+            clip_output_to_extent: true,
+            ..Options::default()
+        },
+    );
+    let stats = clipped.index_stats();
+    let z = (1..=5)
+        .find(|z| stats.non_empty_tiles_per_zoom[z] < stats.tiles_per_zoom[z])
+        .unwrap();
+    let indexed = stats.tiles_per_zoom[&z] as f64 / 4f64.powi(z as i32);
+    assert!(clipped.coverage_ratio(z) < indexed);
+}
+
+#[test]
+fn test_from_vt_features_matches_geojson() {
+    let geojson = load("tests/fixtures/linestring.json");
+    let options = Options {
+        max_zoom: 14,
+        ..Options::default()
+    };
+    let GeoJson::FeatureCollection(fc) = &geojson else {
+        panic!("not a feature collection");
+    };
+    let coords = match &fc.features[0].geometry.as_ref().unwrap().value {
+        Value::LineString(coords) => coords,
+        _ => panic!("not a line string"),
+    };
+    let points = coords
+        .iter()
+        .map(|c| {
+            let sin = c[1].to_radians().sin();
+            let y = 0.5 - 0.25 * ((1. + sin) / (1. - sin)).ln() / PI;
+            VtPoint::new(c[0] / 360. + 0.5, y, 1.)
+        })
+        .collect();
+    let feature = VtFeature::new(
+        VtGeometry::LineString(VtLineString::new(points)),
+        Arc::new(fc.features[0].properties.clone()),
+        None,
+    );
+
+    let mut from_geojson = GeoJSONVT::from_geojson(&geojson, &options);
+    let mut from_features = GeoJSONVT::from_vt_features(vec![feature], options);
+    for (z, x, y) in [(0, 0, 0), (5, 5, 12), (14, 2620, 6332)] {
+        assert_eq!(from_features.tile(z, x, y), from_geojson.tile(z, x, y));
+    }
+}
+
+#[test]
+fn test_convert_is_public() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let GeoJson::FeatureCollection(fc) = geojson else {
+        panic!("not a feature collection");
+    };
+    let count = fc.features.len();
+    let options = ConvertOptions {
+        id_policy: IdPolicy::GenerateAll,
+        ..ConvertOptions::default()
+    };
+    let features = convert(fc, &options);
+    assert_eq!(features.len(), count);
+    for (i, feature) in features.iter().enumerate() {
+        let bbox = feature.bbox.unwrap();
+        assert!(bbox.min_y >= 0. && bbox.max_y <= 1.);
+        assert_eq!(feature.id, Some(Id::Number(i.into())));
+    }
+}
+
+#[test]
+fn test_feature_centroids() {
+    let GeoJson::FeatureCollection(fc) = load("tests/fixtures/us-states.json") else {
+        panic!("not a feature collection");
+    };
+    for feature in convert(fc, &ConvertOptions::default()) {
+        let bbox = feature.bbox.unwrap();
+        let centroid = feature.compute_centroid_mercator().unwrap();
+        assert!(
+            bbox.contains_point(centroid.x, centroid.y),
+            "{:?}",
+            feature.properties
+        );
+    }
+
+    // A feature wrapped across the antimeridian keeps its centroid within its own bounds.
+    let line = GeoJson::from(Geometry::new(Value::LineString(vec![
+        vec![170., 5.],
+        vec![190., 15.],
+    ])));
+    let mut geojsonvt = GeoJSONVT::from_geojson(&line, &Options::default());
+    let features: Vec<_> = geojsonvt.features_in_tile(0, 0, 0).cloned().collect();
+    assert!(
+        features
+            .iter()
+            .any(|feature| feature.bbox.unwrap().min_x < 0.)
+    );
+    for feature in features {
+        let bbox = feature.bbox.unwrap();
+        let centroid = feature.compute_centroid_mercator().unwrap();
+        assert!(bbox.contains_point(centroid.x, centroid.y));
+    }
+}
+
+#[test]
+fn test_builder_matches_from_geojson() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let GeoJson::FeatureCollection(fc) = &geojson else {
+        panic!("not a feature collection");
+    };
+    let mut builder = GeoJSONVT::builder();
+    let (first, rest) = fc.features.split_first().unwrap();
+    builder.add_feature(first.clone());
+    assert_eq!(builder.len(), 1);
+    builder.add_feature_collection(FeatureCollection::from_iter(rest.to_vec()));
+    assert_eq!(builder.len(), fc.features.len());
+
+    let mut built = builder.build(Options::default());
+    let mut expected = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    assert_eq!(
+        built.index_stats().total_tiles,
+        expected.index_stats().total_tiles
+    );
+    assert_eq!(built.tile(7, 37, 48), expected.tile(7, 37, 48));
+}
+
+#[test]
+fn test_retile_matches_fresh_build() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let retain = Options {
+        retain_sources: true,
+        ..Options::default()
+    };
+    let geojsonvt = GeoJSONVT::from_geojson(&geojson, &retain);
+    assert!(geojsonvt.has_sources());
+    let without_sources = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    assert!(!without_sources.has_sources());
+    assert_eq!(
+        without_sources.retile(Options::default()).err(),
+        Some(QueryError::NoSources)
+    );
+    for options in [
+        Options {
+            extent: 8192,
+            ..Options::default()
+        },
+        Options {
+            buffer: 256,
+            index_max_points: 1000,
+            ..Options::default()
+        },
+        Options {
+            max_zoom: 12,
+            tolerance: 10.,
+            ..Options::default()
+        },
+    ] {
+        let mut retiled = geojsonvt.retile(options.clone()).unwrap();
+        let mut expected = GeoJSONVT::from_geojson(&geojson, &options);
+        assert_eq!(
+            retiled.index_stats().total_tiles,
+            expected.index_stats().total_tiles
+        );
+        for (z, x, y) in [(0, 0, 0), (3, 2, 3), (7, 37, 48), (9, 150, 195)] {
+            assert_eq!(retiled.tile(z, x, y), expected.tile(z, x, y));
+        }
+    }
+}
+
+#[test]
+fn test_min_zoom_skips_low_zoom_content() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let options = Options {
+        min_zoom: 3,
+        index_max_points: 1000,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let mut expected = GeoJSONVT::from_geojson(
+        &geojson,
+        &Options {
+            min_zoom: 0,
+            ..options
+        },
+    );
+
+    for tile in geojsonvt.internal_tiles().values() {
+        if tile.coord().z() < 3 {
+            assert_eq!(tile.feature_count(), 0);
+        }
+    }
+    assert!(
+        geojsonvt
+            .index_stats()
+            .tiles_per_zoom
+            .keys()
+            .all(|z| *z >= 3)
+    );
+    assert_eq!(
+        geojsonvt.index_stats().total_tiles,
+        geojsonvt.index_stats().tiles_per_zoom.values().sum::<u32>()
+    );
+    assert!(
+        geojsonvt
+            .tile(0, 0, 0)
+            .feature_collection
+            .features
+            .is_empty()
+    );
+    assert!(
+        geojsonvt
+            .tile(2, 0, 1)
+            .feature_collection
+            .features
+            .is_empty()
+    );
+    for (z, x, y) in [(3, 2, 3), (7, 37, 48), (9, 150, 195)] {
+        assert_eq!(geojsonvt.tile(z, x, y), expected.tile(z, x, y));
+    }
+}
+
+#[test]
+fn test_zoom_range_errors() {
+    let try_new = |options: Options| {
+        let geojson = load("tests/fixtures/linestring.json");
+        let GeoJson::FeatureCollection(features) = geojson else {
+            panic!("not a feature collection");
+        };
+        GeoJSONVT::try_new(features, options).err()
+    };
+    assert_eq!(
+        try_new(Options {
+            min_zoom: 8,
+            index_max_zoom: 5,
+            ..Options::default()
+        }),
+        Some(BuildError::InvalidOptions(
+            OptionsError::MinZoomAboveIndexMaxZoom {
+                min_zoom: 8,
+                index_max_zoom: 5
+            }
+        ))
+    );
+    assert_eq!(
+        try_new(Options {
+            max_zoom: 4,
+            ..Options::default()
+        }),
+        Some(BuildError::InvalidOptions(
+            OptionsError::IndexMaxZoomAboveMaxZoom {
+                index_max_zoom: 5,
+                max_zoom: 4
+            }
+        ))
+    );
+    for max_zoom in [0, 25] {
+        assert_eq!(
+            try_new(Options {
+                max_zoom,
+                index_max_zoom: 0,
+                ..Options::default()
+            }),
+            Some(BuildError::InvalidOptions(
+                OptionsError::MaxZoomOutOfRange { max_zoom }
+            ))
+        );
+    }
+    assert_eq!(
+        try_new(Options {
+            max_zoom: 24,
+            ..Options::default()
+        }),
+        None
+    );
+}
+
+#[test]
+fn test_zero_tile_cache_capacity_error() {
+    let GeoJson::FeatureCollection(features) = load("tests/fixtures/linestring.json") else {
+        panic!("not a feature collection");
+    };
+    let options = Options {
+        tile_cache_capacity: Some(0),
+        ..Options::default()
+    };
+    assert_eq!(options.validate(), Err(OptionsError::ZeroTileCacheCapacity));
+    assert_eq!(
+        GeoJSONVT::try_new(features, options).err(),
+        Some(BuildError::InvalidOptions(
+            OptionsError::ZeroTileCacheCapacity
+        ))
+    );
+}
+
+#[test]
+fn test_tile_to_geojson() {
+    let mut geojsonvt =
+        GeoJSONVT::from_geojson(&load("tests/fixtures/us-states.json"), &Options::default());
+    let tile = geojsonvt.tile(7, 37, 48);
+    let string = tile.to_geojson_string();
+    let parsed = GeoJson::from_str(&string).unwrap();
+    assert_eq!(
+        parsed,
+        GeoJson::FeatureCollection(tile.feature_collection.clone())
+    );
+    assert_eq!(tile.to_geojson_bytes(), string.as_bytes());
+    let mut buffer = Vec::new();
+    tile.to_geojson_writer(&mut buffer).unwrap();
+    assert_eq!(buffer, string.as_bytes());
+}
+
+#[test]
+fn test_eager_index() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let lazy = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    assert_eq!(lazy.index_stats().tiles_per_zoom.len(), 1);
+
+    let options = Options {
+        eager: true,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let stats = geojsonvt.index_stats();
+    let mut zooms = Vec::from_iter(stats.tiles_per_zoom.keys().copied());
+    zooms.sort();
+    assert_eq!(zooms, [0, 1, 2, 3, 4, 5]);
+    // Tiles down to `index_max_zoom` are served without generating anything.
+    assert!(
+        !geojsonvt
+            .tile(5, 9, 12)
+            .feature_collection
+            .features
+            .is_empty()
+    );
+    assert_eq!(geojsonvt.index_stats().total_tiles, stats.total_tiles);
+}
+
+#[test]
+fn test_deferred_tile_output() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let options = Options {
+        index_max_points: 1000,
+        ..Options::default()
+    };
+    let mut lazy = GeoJSONVT::from_geojson(&geojson, &options);
+    let mut eager = GeoJSONVT::from_geojson(
+        &geojson,
+        &Options {
+            eager: true,
+            ..options.clone()
+        },
+    );
+    let generated = |geojsonvt: &GeoJSONVT| {
+        let tiles = geojsonvt.internal_tiles().values();
+        tiles.filter(|tile| tile.feature_count() > 0).count()
+    };
+    // The tiles where the split stops above `index_max_zoom` have no output until requested.
+    let stopped: Vec<_> = lazy
+        .internal_tiles()
+        .values()
+        .filter(|tile| tile.source_feature_count() > 0 && tile.coord().z() < 5)
+        .collect();
+    assert!(!stopped.is_empty());
+    assert!(stopped.iter().all(|tile| tile.feature_count() == 0));
+    assert!(generated(&lazy) < generated(&eager));
+
+    // They are counted as non-empty once generated.
+    let coord = stopped[0].coord();
+    let non_empty = lazy.index_stats().non_empty_tiles;
+    lazy.tile(coord.z(), coord.x(), coord.y());
+    assert_eq!(lazy.index_stats().non_empty_tiles, non_empty + 1);
+
+    let mut coords = lazy.tile_coords().to_vec();
+    coords.extend([(7, 37, 48), (9, 148, 194)].map(|(z, x, y)| TileCoord::new(x, y, z)));
+    for coord in coords {
+        let (z, x, y) = (coord.z(), coord.x(), coord.y());
+        assert_eq!(lazy.tile(z, x, y), eager.tile(z, x, y), "z{z}-{x}-{y}");
+    }
+}
+
+#[test]
+fn test_empty_children_are_not_indexed() {
+    let point = GeoJson::from_str(
+        r#"{"type":"Feature","properties":{},"geometry":{"type":"Point","coordinates":[10.3,47.1]}}"#,
+    )
+    .unwrap();
+    let mut geojsonvt = GeoJSONVT::from_geojson(
+        &point,
+        &Options {
+            index_max_points: 0,
+            index_max_zoom: 10,
+            ..Options::default()
+        },
+    );
+    assert_eq!(geojsonvt.index_stats().total_tiles, 11);
+    assert_eq!(geojsonvt.internal_tiles().len(), 11);
+    assert!(
+        geojsonvt
+            .index_stats()
+            .tiles_per_zoom
+            .values()
+            .all(|count| *count == 1)
+    );
+
+    assert!(
+        geojsonvt
+            .tile(3, 0, 0)
+            .feature_collection
+            .features
+            .is_empty()
+    );
+    assert!(
+        geojsonvt
+            .tile(12, 0, 0)
+            .feature_collection
+            .features
+            .is_empty()
+    );
+    let sin = 47.1f64.to_radians().sin();
+    let y = 0.5 - 0.25 * ((1. + sin) / (1. - sin)).ln() / PI;
+    let (x, y) = (((10.3 / 360. + 0.5) * 4096.) as u32, (y * 4096.) as u32);
+    assert_eq!(
+        geojsonvt.tile(12, x, y).feature_collection.features.len(),
+        1
+    );
+}
+
+#[test]
+fn test_generate_all_then_drop_sources() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let options = Options {
+        max_zoom: 10,
+        retain_sources: true,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let mut expected = GeoJSONVT::from_geojson(&geojson, &options);
+    geojsonvt.generate_all(10);
+    assert!(geojsonvt.index_stats().tiles_per_zoom.contains_key(&10));
+    assert!(geojsonvt.index_stats().total_tiles > expected.index_stats().total_tiles);
+
+    let before = geojsonvt.estimate_memory_bytes();
+    geojsonvt.drop_sources();
+    assert!(geojsonvt.is_read_only());
+    assert!(geojsonvt.estimate_memory_bytes() < before);
+    assert!(
+        geojsonvt
+            .internal_tiles()
+            .values()
+            .all(|tile| tile.source_feature_count() == 0)
+    );
+    for (z, x, y) in [(0, 0, 0), (7, 37, 48), (10, 300, 390)] {
+        assert_eq!(geojsonvt.tile(z, x, y), expected.tile(z, x, y));
+    }
+    assert!(
+        geojsonvt
+            .tile(10, 0, 0)
+            .feature_collection
+            .features
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_tiles_at_zoom_matches_tile() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    geojsonvt.tile(7, 37, 48);
+    let total = geojsonvt.index_stats().total_tiles;
+    let mut actual = HashMap::new();
+    for (coord, tile) in geojsonvt.tiles_at_zoom(7) {
+        assert_eq!(coord.z(), 7);
+        assert!(actual.insert((coord.x(), coord.y()), tile).is_none());
+    }
+    assert_eq!(geojsonvt.index_stats().total_tiles, total);
+
+    let mut expected = HashMap::new();
+    for x in 0..128 {
+        for y in 0..128 {
+            let tile = geojsonvt.tile(7, x, y);
+            if !tile.feature_collection.features.is_empty() {
+                expected.insert((x, y), tile.clone());
+            }
+        }
+    }
+    assert!(!expected.is_empty());
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_tile_grid_as_geojson() {
+    let geojsonvt = GeoJSONVT::from_geojson(
+        &load("tests/fixtures/us-states.json"),
+        &Options {
+            index_max_points: 0,
+            ..Options::default()
+        },
+    );
+    let grid = geojsonvt.tile_grid_as_geojson(1);
+    assert_eq!(
+        grid.features.len() as u32,
+        geojsonvt.index_stats().tiles_per_zoom[&1]
+    );
+    let tile = grid
+        .features
+        .iter()
+        .find(|f| f.property("x") == Some(&0.into()) && f.property("y") == Some(&0.into()))
+        .unwrap();
+    assert_eq!(tile.property("z"), Some(&1.into()));
+    assert!(tile.property("point_count").is_some());
+    let Value::Polygon(rings) = &tile.geometry.as_ref().unwrap().value else {
+        panic!("not a polygon");
+    };
+    let ring = &rings[0];
+    assert_eq!(ring.len(), 5);
+    assert_eq!(ring[0], ring[4]);
+    assert_eq!(ring[0][0], -180.);
+    assert!(ring[0][1].abs() < 1e-9);
+    assert_eq!(ring[2][0], 0.);
+    assert!((ring[2][1] - 85.0511287798066).abs() < 1e-9);
+}
+
+#[test]
+fn test_estimate_memory_bytes() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    let empty = GeoJSONVT::from_geojson(&load("tests/fixtures/empty.json"), &Options::default());
+    let initial = geojsonvt.estimate_memory_bytes();
+    assert!(empty.estimate_memory_bytes() < initial);
+    geojsonvt.tile(7, 37, 48);
+    assert!(geojsonvt.estimate_memory_bytes() > initial);
+}
+
+#[test]
+fn test_index_size_bytes() {
+    let geojson =
+        GeoJson::from_str(r#"{"type":"LineString","coordinates":[[-100,40],[-90,45],[-80,40]]}"#)
+            .unwrap();
+    let options = Options {
+        index_max_zoom: 0,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let initial = geojsonvt.index_size_bytes();
+    assert!(initial >= 3 * size_of::<VtPoint>() + size_of::<VtFeature>());
+    assert!(initial > geojsonvt.estimate_memory_bytes());
+
+    // Generating a tile adds its coordinates and the features kept to split it further.
+    geojsonvt.tile(3, 2, 2);
+    let generated = geojsonvt.index_size_bytes();
+    assert!(generated > initial + 3 * 2 * size_of::<f64>());
+    assert_eq!(geojsonvt.index_size_bytes(), generated);
+
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    let before = geojsonvt.index_size_bytes();
+    geojsonvt.drop_sources();
+    assert!(geojsonvt.index_size_bytes() < before);
+}
+
+#[test]
+fn test_clip_to_bbox() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let bbox = [-109.05, 36.99, -102.04, 41.];
+    let options = Options {
+        index_max_points: 0,
+        ..Options::default()
+    };
+    let full = GeoJSONVT::from_geojson(&geojson, &options);
+    let clipped = GeoJSONVT::from_geojson(
+        &geojson,
+        &Options {
+            clip_to_bbox: Some(bbox),
+            ..options
+        },
+    );
+    assert!(clipped.index_stats().total_tiles < full.index_stats().total_tiles);
+
+    let mercator_y = |lat: f64| {
+        let sin = lat.to_radians().sin();
+        0.5 - 0.25 * ((1. + sin) / (1. - sin)).ln() / PI
+    };
+    let (min_x, max_x) = (bbox[0] / 360. + 0.5, bbox[2] / 360. + 0.5);
+    let (min_y, max_y) = (mercator_y(bbox[3]), mercator_y(bbox[1]));
+    let p = options.buffer as f64 / options.extent as f64;
+    for z in 0..=5 {
+        let z2 = (1u32 << z) as f64;
+        let mut count = 0;
+        for (coord, _) in clipped.tiles_at_zoom(z) {
+            let (x, y) = (coord.x() as f64, coord.y() as f64);
+            assert!((x - p) / z2 <= max_x && (x + 1. + p) / z2 >= min_x);
+            assert!((y - p) / z2 <= max_y && (y + 1. + p) / z2 >= min_y);
+            count += 1;
+        }
+        assert!(count > 0);
+        assert!(count <= full.tiles_at_zoom(z).count());
+    }
+}
+
+#[test]
+fn test_mask() {
+    let (center, radius) = ((-105.5f64, 39f64), 3f64);
+    let ring: Vec<Vec<f64>> = (0..=32)
+        .map(|i| {
+            let a = (i % 32) as f64 / 32. * 2. * PI;
+            vec![center.0 + radius * a.cos(), center.1 + radius * a.sin()]
+        })
+        .collect();
+    let mut geojsonvt = GeoJSONVT::from_geojson(
+        &load("tests/fixtures/us-states.json"),
+        &Options {
+            mask: Some(Geometry::new(Value::Polygon(vec![ring]))),
+            ..Options::default()
+        },
+    );
+
+    for (z, x, y) in [(7, 37, 48), (4, 1, 5)] {
+        assert!(
+            geojsonvt
+                .tile(z, x, y)
+                .feature_collection
+                .features
+                .is_empty()
+        );
+        assert!(
+            !geojsonvt
+                .tile_coords()
+                .iter()
+                .any(|c| (c.z(), c.x(), c.y()) == (z, x, y))
+        );
+    }
+
+    let extent = geojsonvt.options().extent as f64;
+    let (mut names, mut max_dist) = (HashSet::new(), 0f64);
+    for (coord, tile) in geojsonvt.tiles_at_zoom(5) {
+        let z2 = (1u32 << coord.z()) as f64;
+        for feature in tile.feature_collection.features {
+            names.insert(feature.property("name").unwrap().to_string());
+            let mut positions = Vec::new();
+            match feature.geometry.unwrap().value {
+                Value::Polygon(rings) => positions.extend(rings.into_iter().flatten()),
+                Value::MultiPolygon(polygons) => {
+                    positions.extend(polygons.into_iter().flatten().flatten())
+                }
+                _ => panic!("unexpected geometry"),
+            }
+            for p in positions {
+                let x = (coord.x() as f64 + p[0] / extent) / z2;
+                let y = (coord.y() as f64 + p[1] / extent) / z2;
+                let lng = x * 360. - 180.;
+                let lat = ((1. - 2. * y) * PI).sinh().atan().to_degrees();
+                max_dist = max_dist.max((lng - center.0).hypot(lat - center.1));
+            }
+        }
+    }
+    assert!(names.contains("\"Colorado\""));
+    assert!(!names.contains("\"California\""));
+    // Clipped along the mask edge, up to Mercator distortion and rounding.
+    assert!(
+        max_dist > radius - 0.05 && max_dist < radius + 0.05,
+        "{max_dist}"
+    );
+
+    // Masks that clipping cannot handle are rejected by `Options::validate`.
+    let mask = |value: Value| Options {
+        mask: Some(Geometry::new(value)),
+        ..Options::default()
+    };
+    let l_shape = [
+        [0., 0.],
+        [2., 0.],
+        [2., 1.],
+        [1., 1.],
+        [1., 2.],
+        [0., 2.],
+        [0., 0.],
+    ];
+    let l_shape = vec![l_shape.iter().map(|p| p.to_vec()).collect()];
+    assert_eq!(
+        mask(Value::Polygon(l_shape)).validate(),
+        Err(OptionsError::NonConvexMask)
+    );
+    assert_eq!(
+        mask(Value::Point(vec![0., 0.])).validate(),
+        Err(OptionsError::MaskNotPolygon)
+    );
+}
+
+#[test]
+fn test_tile_cache_capacity() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut unbounded = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    let mut bounded = GeoJSONVT::from_geojson(
+        &geojson,
+        &Options {
+            tile_cache_capacity: Some(2),
+            ..Options::default()
+        },
+    );
+    let stats = bounded.index_stats();
+    let coords = [(7, 37, 48), (9, 150, 195), (8, 74, 97), (7, 37, 48)];
+    for (z, x, y) in coords {
+        assert_eq!(bounded.tile(z, x, y), unbounded.tile(z, x, y));
+        assert!(bounded.tile_cache_len() <= 2);
+    }
+    assert_eq!(bounded.tile_cache_len(), 2);
+    // Generated tiles live in the cache only, the index keeps its sources. They are counted
+    // while the cache holds them.
+    let cached = bounded.index_stats();
+    assert_eq!(cached.total_tiles, stats.total_tiles + 2);
+    assert_eq!(cached.non_empty_tiles, stats.non_empty_tiles + 2);
+    assert_eq!(
+        cached.tiles_per_zoom[&7],
+        stats.tiles_per_zoom.get(&7).unwrap_or(&0) + 1
+    );
+    assert!(bounded.evict_tile(7, 37, 48));
+    assert_eq!(bounded.index_stats().total_tiles, stats.total_tiles + 1);
+    assert_eq!(
+        bounded.index_stats().non_empty_tiles,
+        stats.non_empty_tiles + 1
+    );
+    assert!(unbounded.index_stats().total_tiles > cached.total_tiles);
+    assert_eq!(bounded.tile(0, 0, 0), unbounded.tile(0, 0, 0));
+    assert!(bounded.tile(9, 0, 0).feature_collection.features.is_empty());
+}
+
+#[test]
+fn test_remove_collinear() {
+    // A rectangle whose edges carry a vertex every 1/1024 of the world, all kept at every zoom.
+    let corners: [(f64, f64); 5] = [
+        (0.25, 0.375),
+        (0.75, 0.375),
+        (0.75, 0.625),
+        (0.25, 0.625),
+        (0.25, 0.375),
+    ];
+    let mut points = vec![];
+    for w in corners.windows(2) {
+        let ((x0, y0), (x1, y1)) = (w[0], w[1]);
+        let steps = ((x1 - x0).abs().max((y1 - y0).abs()) * 1024.) as usize;
+        for i in 0..steps {
+            let t = i as f64 / steps as f64;
+            points.push(VtPoint::new(x0 + t * (x1 - x0), y0 + t * (y1 - y0), 1.));
+        }
+    }
+    points.push(points[0]);
+    let feature = VtFeature::new(
+        VtGeometry::Polygon(vec![VtLinearRing::new(points)]),
+        Arc::new(None),
+        None,
+    );
+    let mut kept = GeoJSONVT::from_vt_features(vec![feature.clone()], Options::default());
+    let mut removed = GeoJSONVT::from_vt_features(
+        vec![feature],
+        Options {
+            remove_collinear: true,
+            ..Options::default()
+        },
+    );
+
+    // The whole rectangle, an edge, a corner and the inside of the rectangle.
+    for (z, x, y) in [(0, 0, 0), (1, 0, 0), (3, 2, 4), (4, 4, 6), (5, 10, 13)] {
+        let tile = removed.tile(z, x, y).clone();
+        let Some(Value::Polygon(rings)) = tile.feature_collection.features[0]
+            .geometry
+            .as_ref()
+            .map(|g| g.value.clone())
+        else {
+            panic!("not a polygon");
+        };
+        assert!(rings[0].len() <= 5, "{z}/{x}/{y}: {:?}", rings[0]);
+        assert_eq!(rings[0].first(), rings[0].last());
+        assert_eq!(tile.simplified_count as usize, rings[0].len());
+
+        let before = kept.tile(z, x, y).simplified_count;
+        if z < 5 {
+            assert!(before > 5, "{z}/{x}/{y}: {before}");
+        }
+    }
+}
+
+#[test]
+fn test_remove_collinear_keeps_turning_points() {
+    // A line running east along the equator, then turning back west over itself.
+    let line = Geometry::new(Value::LineString(vec![
+        vec![-90., 0.],
+        vec![90., 0.],
+        vec![0., 0.],
+    ]));
+    let options = Options {
+        remove_collinear: true,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&GeoJson::Geometry(line), &options);
+    let tile = geojsonvt.tile(0, 0, 0);
+    let Some(Value::LineString(coords)) = tile.feature_collection.features[0]
+        .geometry
+        .as_ref()
+        .map(|g| g.value.clone())
+    else {
+        panic!("not a line");
+    };
+    assert_eq!(coords.len(), 3, "{coords:?}");
+}
+
+#[test]
+fn test_zoom_for_feature_size() {
+    // Squares 1/64 and 1/1024 of the world wide around the equator.
+    let square = |size: f64| {
+        let half = 180. * size;
+        Geometry::new(Value::Polygon(vec![vec![
+            vec![-half, -half],
+            vec![half, -half],
+            vec![half, half],
+            vec![-half, half],
+            vec![-half, -half],
+        ]]))
+    };
+    let large = FeatureCollection::from_iter([geojson::Feature::from(square(1. / 64.))]);
+    let index = GeoJSONVT::new(large.clone(), Options::default());
+    assert_eq!(index.zoom_for_feature_size(4., 256), 0);
+    assert_eq!(index.zoom_for_feature_size(64., 256), 4);
+    assert_eq!(index.zoom_for_feature_size(64., 4096), 0);
+
+    let mut both = large;
+    both.features
+        .push(geojson::Feature::from(square(1. / 1024.)));
+    let index = GeoJSONVT::new(both, Options::default());
+    assert_eq!(index.zoom_for_feature_size(64., 256), 8);
+
+    let points = GeoJson::from_str(r#"{"type":"Point","coordinates":[0,0]}"#).unwrap();
+    let index = GeoJSONVT::from_geojson(&points, &Options::default());
+    assert_eq!(index.zoom_for_feature_size(64., 256), 0);
+}
+
+#[test]
+fn test_zoom_for_tolerance() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let index = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    // Tolerance 3 in a 4096 extent, halved at each zoom.
+    let tolerance = 3. / 4096.;
+    assert_eq!(index.tolerance_at_zoom(0), tolerance);
+    assert_eq!(index.tolerance_at_zoom(3), tolerance / 8.);
+    assert_eq!(index.tolerance_at_zoom(18), 0.);
+    assert_eq!(index.tolerance_at_zoom(u8::MAX), 0.);
+    assert_eq!(index.zoom_for_tolerance(tolerance), 0);
+    assert_eq!(index.zoom_for_tolerance(tolerance / 8.), 3);
+    assert_eq!(index.zoom_for_tolerance(tolerance / 7.), 3);
+    assert_eq!(index.zoom_for_tolerance(0.), 18);
+    for z in 0..18 {
+        let zoom = index.zoom_for_tolerance(index.tolerance_at_zoom(z));
+        assert_eq!(zoom, z);
+    }
+
+    let options = Options {
+        tolerance_schedule: Some(vec![8., 1.]),
+        ..Options::default()
+    };
+    let index = GeoJSONVT::from_geojson(&geojson, &options);
+    assert_eq!(index.tolerance_at_zoom(1), 1. / 8192.);
+    assert_eq!(index.zoom_for_tolerance(1. / 8192.), 1);
+}
+
+#[test]
+fn test_from_geojson_str_and_bytes() {
+    let contents = std::fs::read_to_string("tests/fixtures/us-states.json").unwrap();
+    let options = Options::default();
+    let mut expected = GeoJSONVT::from_geojson(&GeoJson::from_str(&contents).unwrap(), &options);
+
+    let mut from_str = GeoJSONVT::from_geojson_str(&contents, &options).unwrap();
+    let mut from_bytes = GeoJSONVT::from_geojson_bytes(contents.as_bytes(), &options).unwrap();
+    for (z, x, y) in [(0, 0, 0), (4, 3, 6), (7, 37, 48)] {
+        let tile = expected.tile(z, x, y).clone();
+        assert_eq!(from_str.tile(z, x, y), &tile);
+        assert_eq!(from_bytes.tile(z, x, y), &tile);
+    }
+
+    assert!(GeoJSONVT::from_geojson_str("{\"type\":", &options).is_err());
+    assert!(GeoJSONVT::from_geojson_bytes(b"[1, 2]", &options).is_err());
+}
+
+#[test]
+fn test_feature_limit() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let GeoJson::FeatureCollection(fc) = &geojson else {
+        panic!("not a feature collection");
+    };
+    let index = GeoJSONVT::from_geojson(
+        &geojson,
+        &Options {
+            feature_limit: Some(10),
+            generate_id: true,
+            ..Options::default()
+        },
+    );
+    // Features crossing the antimeridian are copied by wrapping.
+    let mut ids = index.internal_tiles()[&0]
+        .source_features()
+        .iter()
+        .filter_map(|f| match &f.id {
+            Some(Id::Number(n)) => n.as_u64(),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids, (0..10).collect::<Vec<_>>());
+    assert_eq!(
+        index.warnings().last(),
+        Some(&ConvertWarning::FeatureLimitReached {
+            total_input_features: fc.features.len()
+        })
+    );
+
+    let unlimited = GeoJSONVT::from_geojson(
+        &geojson,
+        &Options {
+            feature_limit: Some(fc.features.len()),
+            ..Options::default()
+        },
+    );
+    assert!(
+        !unlimited
+            .warnings()
+            .iter()
+            .any(|w| matches!(w, ConvertWarning::FeatureLimitReached { .. }))
+    );
+}
+
+#[test]
+fn test_tile_extent_wgs84() {
+    let max_lat = (PI.sinh()).atan().to_degrees();
+    assert_eq!(
+        GeoJSONVT::tile_extent_wgs84(0, 0, 0),
+        Some((-180., -max_lat, 180., max_lat))
+    );
+    let (west, south, east, north) = GeoJSONVT::tile_extent_wgs84(1, 1, 0).unwrap();
+    assert_eq!((west, east, north), (0., 180., max_lat));
+    assert!(south.abs() < 1e-12);
+
+    // Neighbouring tiles share their edges.
+    let (_, south, east, _) = GeoJSONVT::tile_extent_wgs84(12, 2000, 1500).unwrap();
+    let (west, _, _, _) = GeoJSONVT::tile_extent_wgs84(12, 2001, 1500).unwrap();
+    let (_, _, _, north) = GeoJSONVT::tile_extent_wgs84(12, 2000, 1501).unwrap();
+    assert_eq!((east, south), (west, north));
+
+    // The last tile of the largest grid, and coordinates outside the grid.
+    let (_, _, east, _) = GeoJSONVT::tile_extent_wgs84(32, u32::MAX, u32::MAX).unwrap();
+    assert_eq!(east, 180.);
+    assert_eq!(GeoJSONVT::tile_extent_wgs84(33, 0, 0), None);
+    assert_eq!(GeoJSONVT::tile_extent_wgs84(u8::MAX, 0, 0), None);
+    assert_eq!(GeoJSONVT::tile_extent_wgs84(2, 4, 0), None);
+    assert_eq!(GeoJSONVT::tile_extent_wgs84(2, 0, 4), None);
+}
+
+#[test]
+fn test_tile_ancestry() {
+    let coords = |tiles: Vec<TileCoord>| {
+        tiles
+            .iter()
+            .map(|t| (t.z(), t.x(), t.y()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(coords(GeoJSONVT::tile_ancestry(0, 0, 0)), [(0, 0, 0)]);
+    assert_eq!(
+        coords(GeoJSONVT::tile_ancestry(4, 9, 6)),
+        [(0, 0, 0), (1, 1, 0), (2, 2, 1), (3, 4, 3), (4, 9, 6)]
+    );
+
+    // Tiles are generated on demand down the chain from its deepest indexed ancestor.
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    let is_indexed = |geojsonvt: &GeoJSONVT, coord: &TileCoord| {
+        let tiles = geojsonvt.internal_tiles();
+        tiles.values().any(|tile| tile.coord() == *coord)
+    };
+    let ancestry = GeoJSONVT::tile_ancestry(9, 148, 191);
+    let indexed = ancestry
+        .iter()
+        .take_while(|t| is_indexed(&geojsonvt, t))
+        .count();
+    assert!((1..ancestry.len()).contains(&indexed));
+    assert!(
+        !ancestry[indexed..]
+            .iter()
+            .any(|t| is_indexed(&geojsonvt, t))
+    );
+    geojsonvt.tile(9, 148, 191);
+    assert!(ancestry.iter().all(|t| is_indexed(&geojsonvt, t)));
+}
+
+#[test]
+fn test_tile_status() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    let expected = geojsonvt.tile(0, 0, 0).clone();
+    assert_eq!(
+        geojsonvt.tile_status(0, 0, 0),
+        TileStatus::Generated(&expected)
+    );
+    assert_eq!(geojsonvt.tile_status(25, 0, 0), TileStatus::OutOfRange);
+    assert_eq!(geojsonvt.tile_status(2, 0, 4), TileStatus::OutOfRange);
+    let outside = TileStatus::Empty {
+        reason: EmptyReason::OutsideData,
+    };
+    assert_eq!(geojsonvt.tile_status(4, 1, 10), outside);
+    assert_eq!(geojsonvt.tile_status(8, 10, 160), outside);
+
+    geojsonvt.drop_sources();
+    assert_eq!(
+        geojsonvt.tile_status(12, 1000, 1500),
+        TileStatus::Empty {
+            reason: EmptyReason::NoSourceFeatures
+        }
+    );
+    // Never generated, but far from every feature of its indexed ancestors.
+    assert_eq!(geojsonvt.tile_status(12, 3000, 3000), outside);
+    assert_eq!(geojsonvt.tile_status(12, 100, 100), outside);
+
+    let tiny = GeoJson::from_str(
+        r#"{"type":"Polygon","coordinates":[[[10,10],[10.001,10],[10.001,10.001],[10,10]]]}"#,
+    )
+    .unwrap();
+    let mut geojsonvt = GeoJSONVT::from_geojson(&tiny, &Options::default());
+    assert_eq!(
+        geojsonvt.tile_status(0, 0, 0),
+        TileStatus::Empty {
+            reason: EmptyReason::FilteredByTolerance
+        }
+    );
+
+    // A point just east of the prime meridian, within the buffer of the tiles west of it.
+    let point = GeoJson::from_str(r#"{"type": "Point", "coordinates": [0.01, -0.01]}"#).unwrap();
+    let options = Options {
+        clip_output_to_extent: true,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&point, &options);
+    assert!(matches!(
+        geojsonvt.tile_status(1, 1, 1),
+        TileStatus::Generated(_)
+    ));
+    let clipped = TileStatus::Empty {
+        reason: EmptyReason::ClippedToExtent,
+    };
+    assert_eq!(geojsonvt.tile_status(1, 0, 1), clipped);
+    assert_eq!(geojsonvt.tile_status(1, 1, 0), clipped);
+    assert_eq!(geojsonvt.tile_status(2, 0, 0), outside);
+
+    let options = Options {
+        min_zoom: 2,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    assert_eq!(
+        geojsonvt.tile_status(1, 0, 0),
+        TileStatus::Empty {
+            reason: EmptyReason::BelowMinZoom
+        }
+    );
+}
+
+#[test]
+fn test_on_tile_created() {
+    let created = Arc::new(Mutex::new(Vec::new()));
+    let options = Options {
+        on_tile_created: Some(OnTileCreated::new({
+            let created = created.clone();
+            move |coord: &TileCoord, metrics| created.lock().unwrap().push((*coord, *metrics))
+        })),
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&load("tests/fixtures/us-states.json"), &options);
+    assert_eq!(
+        created.lock().unwrap().len() as u32,
+        geojsonvt.index_stats().total_tiles
+    );
+    let (root, metrics) = created.lock().unwrap()[0];
+    assert_eq!(root, TileCoord::new(0, 0, 0));
+    assert_eq!(metrics.output_features, 52);
+    assert_eq!(metrics.point_count, geojsonvt.tile(0, 0, 0).point_count);
+
+    geojsonvt.tile(7, 37, 48);
+    let created = created.lock().unwrap();
+    let (_, metrics) = created
+        .iter()
+        .find(|(coord, _)| *coord == TileCoord::new(37, 48, 7))
+        .unwrap();
+    assert!(metrics.clip_time > Duration::ZERO);
+    assert!(metrics.input_features >= metrics.output_features);
+
+    let slowest = geojsonvt.slowest_tiles();
+    assert_eq!(slowest.len(), 10);
+    assert!(
+        slowest
+            .windows(2)
+            .all(|w| w[0].1.total_time() >= w[1].1.total_time())
+    );
+    let max = created.iter().map(|(_, m)| m.total_time()).max().unwrap();
+    assert_eq!(slowest[0].1.total_time(), max);
+
+    let geojsonvt =
+        GeoJSONVT::from_geojson(&load("tests/fixtures/us-states.json"), &Options::default());
+    assert!(geojsonvt.slowest_tiles().is_empty());
+}
+
+#[test]
+fn test_point_boundary() {
+    let points = GeoJson::from_str(
+        r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":1,"properties":null,"geometry":{"type":"Point","coordinates":[0,0]}},
+            {"type":"Feature","id":2,"properties":null,"geometry":{"type":"Point","coordinates":[0,45]}},
+            {"type":"Feature","id":3,"properties":null,"geometry":{"type":"Point","coordinates":[180,-45]}},
+            {"type":"Feature","id":4,"properties":null,"geometry":{"type":"Point","coordinates":[-180,-45]}}
+        ]}"#,
+    )
+    .unwrap();
+    let tile_ids = |point_boundary| {
+        let options = Options {
+            buffer: 0,
+            point_boundary,
+            ..Options::default()
+        };
+        let mut geojsonvt = GeoJSONVT::from_geojson(&points, &options);
+        let mut tiles = Vec::new();
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            let mut ids = geojsonvt
+                .tile(1, x, y)
+                .feature_collection
+                .features
+                .iter()
+                .map(|f| match &f.id {
+                    Some(Id::Number(n)) => n.as_u64().unwrap(),
+                    _ => panic!("missing id"),
+                })
+                .collect::<Vec<_>>();
+            ids.sort();
+            tiles.push(ids);
+        }
+        tiles
+    };
+    assert_eq!(
+        tile_ids(Boundary::Both),
+        [vec![1, 2], vec![1, 2], vec![1, 3, 4], vec![1, 3, 4]]
+    );
+    assert_eq!(
+        tile_ids(Boundary::LowerInclusive),
+        [vec![], vec![2], vec![3, 4], vec![1]]
+    );
+    assert_eq!(
+        tile_ids(Boundary::UpperInclusive),
+        [vec![1, 2], vec![], vec![], vec![3, 4]]
+    );
+}
+
+#[test]
+fn test_watch_and_evict_tile() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    let expected = geojsonvt.tile(7, 37, 48).clone();
+    let receiver = geojsonvt.watch_tile(7, 37, 48);
+    let other = geojsonvt.watch_tile(7, 38, 48);
+    drop(geojsonvt.watch_tile(7, 37, 48));
+
+    assert!(geojsonvt.evict_tile(7, 37, 48));
+    assert_eq!(receiver.try_recv(), Ok(()));
+    assert!(receiver.try_recv().is_err());
+    assert!(other.try_recv().is_err());
+    assert_eq!(geojsonvt.tile(7, 37, 48), &expected);
+    let (_, tile) = geojsonvt
+        .tiles_at_zoom(7)
+        .find(|(coord, _)| *coord == TileCoord::new(37, 48, 7))
+        .unwrap();
+    assert_eq!(tile, expected);
+
+    // Tiles already split into their children keep no features to rebuild from.
+    let root = geojsonvt.watch_tile(0, 0, 0);
+    assert!(!geojsonvt.evict_tile(0, 0, 0));
+    assert!(root.try_recv().is_err());
+
+    let options = Options {
+        tile_cache_capacity: Some(4),
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    geojsonvt.tile(7, 37, 48);
+    let receiver = geojsonvt.watch_tile(7, 37, 48);
+    assert!(geojsonvt.evict_tile(7, 37, 48));
+    assert_eq!(geojsonvt.tile_cache_len(), 0);
+    assert_eq!(receiver.try_recv(), Ok(()));
+    assert_eq!(geojsonvt.tile(7, 37, 48), &expected);
+}
+
+#[test]
+fn test_dedupe_features() {
+    let GeoJson::FeatureCollection(states) = load("tests/fixtures/us-states.json") else {
+        panic!("not a feature collection");
+    };
+    let mut exact = states.clone();
+    exact.features.extend(states.features.clone());
+    // Same ids with other properties, as repeated per region by some exports.
+    let mut same_ids = states.clone();
+    same_ids
+        .features
+        .extend(states.features.iter().cloned().map(|mut feature| {
+            feature.set_property("density", 0);
+            feature
+        }));
+
+    let count = |fc: &FeatureCollection, dedupe_features| {
+        let options = Options {
+            dedupe_features,
+            ..Options::default()
+        };
+        let mut geojsonvt = GeoJSONVT::new(fc.clone(), options);
+        let count = geojsonvt.tile(0, 0, 0).feature_collection.features.len();
+        (count, geojsonvt.warnings().to_vec())
+    };
+    let (single, _) = count(&states, DedupePolicy::Off);
+    let (doubled, warnings) = count(&exact, DedupePolicy::Off);
+    assert_eq!(doubled, single * 2);
+    assert!(
+        !warnings
+            .iter()
+            .any(|w| matches!(w, ConvertWarning::DuplicateFeatures { .. }))
+    );
+
+    let dropped = vec![ConvertWarning::DuplicateFeatures { dropped: 52 }];
+    for dedupe in [DedupePolicy::ById, DedupePolicy::ByGeometryAndProps] {
+        let (deduped, warnings) = count(&exact, dedupe);
+        assert_eq!(deduped, single);
+        assert!(warnings.ends_with(&dropped));
+    }
+    let (deduped, warnings) = count(&same_ids, DedupePolicy::ById);
+    assert_eq!(deduped, single);
+    assert!(warnings.ends_with(&dropped));
+    assert_eq!(
+        count(&same_ids, DedupePolicy::ByGeometryAndProps).0,
+        doubled
+    );
+}
+
+#[test]
+fn test_index_stats() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    let stats = geojsonvt.index_stats();
+    assert_eq!(stats.total_input_features, 52);
+    assert_eq!(
+        stats.tiles_per_zoom.values().sum::<u32>(),
+        stats.total_tiles
+    );
+    assert!(stats.total_simplified_points < stats.total_original_points);
+    assert!(stats.build_duration.is_some());
+
+    geojsonvt.tile(7, 37, 48);
+    let after = geojsonvt.index_stats();
+    assert!(after.total_tiles > stats.total_tiles);
+    assert!(after.total_original_points > stats.total_original_points);
+    assert_eq!(after.build_duration, stats.build_duration);
+}
+
+#[test]
+fn test_feature_order() {
+    let mixed = GeoJson::from_str(
+        r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":1,"properties":{"rank":3},"geometry":{"type":"Point","coordinates":[10,10]}},
+            {"type":"Feature","id":2,"properties":{"rank":1},"geometry":{"type":"LineString","coordinates":[[-50,0],[-40,0]]}},
+            {"type":"Feature","id":3,"properties":{"rank":5},"geometry":{"type":"Polygon","coordinates":[[[0,0],[10,0],[10,10],[0,0]]]}},
+            {"type":"Feature","id":4,"properties":{"rank":"high"},"geometry":{"type":"LineString","coordinates":[[-50,20],[0,20]]}},
+            {"type":"Feature","id":5,"properties":{},"geometry":{"type":"Point","coordinates":[20,10]}},
+            {"type":"Feature","id":6,"properties":{"rank":2},"geometry":{"type":"Polygon","coordinates":[[[-100,-40],[-60,-40],[-60,0],[-100,-40]]]}}
+        ]}"#,
+    )
+    .unwrap();
+    let ids = |feature_order| {
+        let options = Options {
+            feature_order,
+            ..Options::default()
+        };
+        let mut geojsonvt = GeoJSONVT::from_geojson(&mixed, &options);
+        geojsonvt
+            .tile(0, 0, 0)
+            .feature_collection
+            .features
+            .iter()
+            .map(|f| match &f.id {
+                Some(Id::Number(n)) => n.as_u64().unwrap(),
+                _ => panic!("missing id"),
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(FeatureOrder::InputOrder), [1, 2, 3, 4, 5, 6]);
+    assert_eq!(ids(FeatureOrder::GeometryTypeThenSize), [6, 3, 4, 2, 1, 5]);
+    let rank = |direction| FeatureOrder::ByProperty("rank".to_string(), direction);
+    assert_eq!(ids(rank(SortDirection::Ascending)), [2, 6, 1, 3, 4, 5]);
+    assert_eq!(ids(rank(SortDirection::Descending)), [3, 1, 6, 2, 4, 5]);
+}
+
+#[test]
+fn test_clip_output_to_extent() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let buffered = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    let mut clipped = GeoJSONVT::from_geojson(
+        &geojson,
+        &Options {
+            clip_output_to_extent: true,
+            ..Options::default()
+        },
+    );
+    let (buffered_stats, clipped_stats) = (buffered.index_stats(), clipped.index_stats());
+    assert_eq!(buffered_stats.tiles_per_zoom, clipped_stats.tiles_per_zoom);
+    assert_eq!(
+        buffered_stats.total_original_points,
+        clipped_stats.total_original_points
+    );
+
+    let names = |features: &[geojson::Feature]| {
+        features
+            .iter()
+            .map(|f| f.property("name").unwrap().to_string())
+            .collect::<HashSet<_>>()
+    };
+    let mut bled = false;
+    for z in 0..=6 {
+        for (coord, tile) in buffered.tiles_at_zoom(z) {
+            let expected = names(&tile.feature_collection.features);
+            let features = &clipped
+                .tile(z, coord.x(), coord.y())
+                .feature_collection
+                .features;
+            assert!(names(features).is_subset(&expected));
+            bled |= tile
+                .feature_collection
+                .features
+                .iter()
+                .any(|feature| outside_extent(feature.geometry.as_ref().unwrap(), 4096.));
+            for feature in features {
+                let geometry = feature.geometry.as_ref().unwrap();
+                assert!(!outside_extent(geometry, 4096.), "{z}/{coord:?}");
+                let rings = match &geometry.value {
+                    Value::Polygon(rings) => rings.clone(),
+                    Value::MultiPolygon(polygons) => polygons.concat(),
+                    _ => vec![],
+                };
+                for ring in rings {
+                    assert!(ring.len() >= 4);
+                    assert_eq!(ring.first(), ring.last());
+                }
+            }
+        }
+    }
+    assert!(bled);
+}
+
+fn outside_extent(geometry: &Geometry, extent: f64) -> bool {
+    let outside = |p: &Vec<f64>| p.iter().any(|&v| !(0. ..=extent).contains(&v));
+    match &geometry.value {
+        Value::Point(p) => outside(p),
+        Value::MultiPoint(points) | Value::LineString(points) => points.iter().any(outside),
+        Value::MultiLineString(lines) | Value::Polygon(lines) => {
+            lines.iter().flatten().any(outside)
+        }
+        Value::MultiPolygon(polygons) => polygons.iter().flatten().flatten().any(outside),
+        Value::GeometryCollection(geometries) => {
+            geometries.iter().any(|g| outside_extent(g, extent))
+        }
+    }
+}
+
+#[test]
+fn test_from_multiple_geojson() {
+    let circle = |lng: f64, name: &str| {
+        let mut coords = (0..256)
+            .map(|i| {
+                let angle = i as f64 / 256. * std::f64::consts::TAU;
+                vec![lng + 20. * angle.cos(), 20. * angle.sin()]
+            })
+            .collect::<Vec<_>>();
+        coords.push(coords[0].clone());
+        let mut feature = geojson::Feature::from(Geometry::new(Value::LineString(coords)));
+        feature.set_property("name", name);
+        GeoJson::from(FeatureCollection::from_iter([feature]))
+    };
+    let (roads, boundaries) = (circle(-60., "road"), circle(60., "boundary"));
+    let global = Options {
+        max_zoom: 2,
+        index_max_zoom: 2,
+        ..Options::default()
+    };
+    let detailed = Options {
+        tolerance: 0.,
+        ..global.clone()
+    };
+    let coarse = Options {
+        tolerance: 200.,
+        ..global.clone()
+    };
+    let mut combined =
+        GeoJSONVT::from_multiple_geojson(&[(&roads, &detailed), (&boundaries, &coarse)], &global);
+    assert_eq!(combined.index_stats().total_input_features, 2);
+    for (x, y) in [(0, 1), (2, 1), (1, 1)] {
+        let mut expected = GeoJSONVT::from_geojson(&roads, &detailed)
+            .tile(2, x, y)
+            .clone();
+        let other = GeoJSONVT::from_geojson(&boundaries, &coarse)
+            .tile(2, x, y)
+            .clone();
+        expected
+            .feature_collection
+            .features
+            .extend(other.feature_collection.features);
+        expected.point_count += other.point_count;
+        expected.simplified_count += other.simplified_count;
+        if expected.coord.is_none() {
+            expected.extent = other.extent;
+            expected.coord = other.coord;
+        }
+        assert_eq!(combined.tile(2, x, y), &expected);
+    }
+    let points = |tile: &geojsonvt::Tile, name: &str| {
+        tile.feature_collection
+            .features
+            .iter()
+            .filter(|f| f.property("name").unwrap() == name)
+            .map(|f| match &f.geometry.as_ref().unwrap().value {
+                Value::LineString(coords) => coords.len(),
+                Value::MultiLineString(lines) => lines.iter().map(Vec::len).sum(),
+                _ => 0,
+            })
+            .sum::<usize>()
+    };
+    let road = points(&combined.tile(2, 1, 1).clone(), "road");
+    let boundary = points(&combined.tile(2, 2, 1).clone(), "boundary");
+    assert!(road > 2 * boundary, "road: {road}, boundary: {boundary}");
+}
+
+#[test]
+fn test_from_multiple_geojson_ids_and_tolerance_floor() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let global = Options {
+        generate_id: true,
+        ..Options::default()
+    };
+    let detailed = Options {
+        tolerance: 0.,
+        ..Options::default()
+    };
+    let mut combined =
+        GeoJSONVT::from_multiple_geojson(&[(&geojson, &detailed), (&geojson, &detailed)], &global);
+    let mut ids: Vec<_> = combined
+        .tile(0, 0, 0)
+        .feature_collection
+        .features
+        .iter()
+        .map(|feature| match &feature.id {
+            Some(Id::Number(n)) => n.as_u64().unwrap(),
+            id => panic!("{id:?}"),
+        })
+        .collect();
+    ids.sort();
+    ids.dedup();
+    // Each input has 52 features, two of them too small for this tile; the ids of the second
+    // input follow those of the first.
+    assert_eq!(ids.len(), 102);
+    assert_eq!(ids.last(), Some(&103));
+
+    // Below max_zoom the tolerance of the global options applies to every input.
+    let single = GeoJSONVT::from_multiple_geojson(&[(&geojson, &detailed)], &Options::default())
+        .tile(3, 2, 3)
+        .clone();
+    let mut expected = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    assert_eq!(
+        single.feature_collection,
+        expected.tile(3, 2, 3).feature_collection
+    );
+}
+
+#[test]
+fn test_sort_features() {
+    let features = GeoJson::from_str(
+        r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":"b","properties":{},"geometry":{"type":"Point","coordinates":[10,10]}},
+            {"type":"Feature","id":12,"properties":{},"geometry":{"type":"Point","coordinates":[20,10]}},
+            {"type":"Feature","properties":{},"geometry":{"type":"Point","coordinates":[30,10]}},
+            {"type":"Feature","id":"a","properties":{},"geometry":{"type":"Polygon","coordinates":[[[0,0],[10,0],[10,10],[0,0]]]}},
+            {"type":"Feature","id":3,"properties":{},"geometry":{"type":"LineString","coordinates":[[-50,0],[-40,0]]}}
+        ]}"#,
+    )
+    .unwrap();
+    let ids = |options: Options| {
+        let mut geojsonvt = GeoJSONVT::from_geojson(&features, &options);
+        let tile = geojsonvt.tile(0, 0, 0);
+        tile.feature_collection
+            .features
+            .iter()
+            .map(|f| f.id.clone())
+            .collect::<Vec<_>>()
+    };
+    let number = |n: u64| Some(Id::Number(n.into()));
+    let string = |s: &str| Some(Id::String(s.to_string()));
+    let sorted = Options {
+        sort_features: true,
+        ..Options::default()
+    };
+    assert_eq!(
+        ids(sorted.clone()),
+        [None, number(3), number(12), string("a"), string("b")]
+    );
+    // `feature_order` applies on top, keeping the id order among equal keys.
+    let by_type = Options {
+        feature_order: FeatureOrder::GeometryTypeThenSize,
+        ..sorted
+    };
+    assert_eq!(
+        ids(by_type),
+        [string("a"), number(3), None, number(12), string("b")]
+    );
+    assert_eq!(
+        ids(Options::default()),
+        [string("b"), number(12), None, string("a"), number(3)]
+    );
+}
+
+#[test]
+fn test_features_below_tolerance() {
+    let geojson = GeoJson::from_str(
+        r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":"road","properties":{},"geometry":{"type":"LineString","coordinates":[[0,0],[10,10]]}},
+            {"type":"Feature","id":"stream","properties":{},"geometry":{"type":"LineString","coordinates":[[20,20],[20.001,20.001]]}},
+            {"type":"Feature","id":"pond","properties":{},"geometry":{"type":"Polygon","coordinates":[[[30,30],[30.001,30],[30.001,30.001],[30,30.001],[30,30]]]}},
+            {"type":"Feature","id":"dot","properties":{},"geometry":{"type":"MultiLineString","coordinates":[[[40,40],[40,40]],[[41,41],[41,41]]]}}
+        ]}"#,
+    )
+    .unwrap();
+    let options = Options {
+        max_zoom: 4,
+        index_max_zoom: 4,
+        ..Options::default()
+    };
+    let ids = |geojsonvt: &mut GeoJSONVT| {
+        geojsonvt
+            .tile(0, 0, 0)
+            .feature_collection
+            .features
+            .iter()
+            .map(|f| {
+                let Some(Id::String(id)) = &f.id else {
+                    panic!("missing id")
+                };
+                let value = &f.geometry.as_ref().unwrap().value;
+                (id.clone(), value.type_name())
+            })
+            .collect::<Vec<_>>()
+    };
+    let string = |s: &str| Some(Id::String(s.to_string()));
+
+    let mut dropped = GeoJSONVT::from_geojson(&geojson, &options);
+    assert_eq!(ids(&mut dropped), [("road".to_string(), "LineString")]);
+    let skipped = dropped
+        .conversion_report()
+        .skipped
+        .iter()
+        .map(|s| (s.index, s.id.clone(), s.reason))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        skipped,
+        [
+            (1, string("stream"), SkipReason::BelowTolerance),
+            (2, string("pond"), SkipReason::BelowTolerance),
+            (3, string("dot"), SkipReason::BelowTolerance),
+        ]
+    );
+
+    // Features too small to show do not fail strict mode.
+    let strict = Options {
+        strict: true,
+        ..options.clone()
+    };
+    let GeoJson::FeatureCollection(collection) = geojson.clone() else {
+        unreachable!()
+    };
+    let strict = GeoJSONVT::try_new(collection, strict).unwrap();
+    assert_eq!(strict.conversion_report().skipped.len(), 3);
+
+    // A line of zero length is dropped even without any tolerance.
+    let exact = Options {
+        tolerance: 0.,
+        ..options.clone()
+    };
+    let exact = GeoJSONVT::from_geojson(&geojson, &exact);
+    let skipped = &exact.conversion_report().skipped;
+    assert_eq!(skipped.len(), 1);
+    assert_eq!(skipped[0].id, string("dot"));
+
+    let lines = Options {
+        collapse_small_lines_to_points: true,
+        ..options.clone()
+    };
+    let mut lines = GeoJSONVT::from_geojson(&geojson, &lines);
+    assert_eq!(
+        ids(&mut lines),
+        [
+            ("road".to_string(), "LineString"),
+            ("stream".to_string(), "Point"),
+            ("dot".to_string(), "MultiPoint"),
+        ]
+    );
+    assert_eq!(lines.conversion_report().skipped.len(), 1);
+
+    let polygons = Options {
+        collapse_small_polygons_to_points: true,
+        ..options
+    };
+    let mut polygons = GeoJSONVT::from_geojson(&geojson, &polygons);
+    assert_eq!(
+        ids(&mut polygons),
+        [
+            ("road".to_string(), "LineString"),
+            ("pond".to_string(), "Point"),
+        ]
+    );
+    let Some(Value::Point(center)) = polygons.tile(4, 9, 6).feature_collection.features[0]
+        .geometry
+        .as_ref()
+        .map(|g| g.value.clone())
+    else {
+        panic!("not a point");
+    };
+    let (lng, lat) = polygons
+        .tile(4, 9, 6)
+        .pixel_to_lnglat(center[0], center[1])
+        .unwrap();
+    assert!((lng - 30.0005).abs() < 0.01 && (lat - 30.0005).abs() < 0.01);
+}
+
+#[test]
+fn test_conversion_report() {
+    let detailed_line = (0..100)
+        .map(|i| format!("[{},{}]", i as f64 * 0.1, (i as f64 * 0.5).sin() * 1e-4))
+        .collect::<Vec<_>>()
+        .join(",");
+    let messy = format!(
+        r#"{{"type":"FeatureCollection","features":[
+            {{"type":"Feature","id":"ok","properties":{{}},"geometry":{{"type":"Point","coordinates":[10,10]}}}},
+            {{"type":"Feature","id":"null","properties":{{}},"geometry":null}},
+            {{"type":"Feature","id":2,"properties":{{}},"geometry":{{"type":"MultiPoint","coordinates":[]}}}},
+            {{"type":"Feature","properties":{{}},"geometry":{{"type":"GeometryCollection","geometries":[
+                {{"type":"LineString","coordinates":[]}}]}}}},
+            {{"type":"Feature","id":"dot","properties":{{}},"geometry":{{"type":"LineString","coordinates":[[5,5],[5,5]]}}}},
+            {{"type":"Feature","id":"sliver","properties":{{}},"geometry":{{"type":"Polygon","coordinates":[[[0,0],[1,1],[0,0]]]}}}},
+            {{"type":"Feature","id":"wiggly","properties":{{}},"geometry":{{"type":"LineString","coordinates":[{detailed_line}]}}}}
+        ]}}"#
+    );
+    let geojson = GeoJson::from_str(&messy).unwrap();
+    let options = Options {
+        remove_duplicate_coordinates: true,
+        max_zoom: 4,
+        index_max_zoom: 4,
+        ..Options::default()
+    };
+    let geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let report = geojsonvt.conversion_report();
+    let skipped = report
+        .skipped
+        .iter()
+        .map(|s| (s.index, s.id.clone(), s.reason))
+        .collect::<Vec<_>>();
+    let string = |s: &str| Some(Id::String(s.to_string()));
+    assert_eq!(
+        skipped,
+        [
+            (1, string("null"), SkipReason::NoGeometry),
+            (2, Some(Id::Number(2.into())), SkipReason::EmptyGeometry),
+            (3, None, SkipReason::EmptyGeometry),
+            (4, string("dot"), SkipReason::Collapsed),
+            (5, string("sliver"), SkipReason::Collapsed),
+        ]
+    );
+    assert_eq!(report.simplified.len(), 1);
+    let wiggly = &report.simplified[0];
+    assert_eq!((wiggly.index, wiggly.id.clone()), (6, string("wiggly")));
+    assert_eq!(
+        (wiggly.points, wiggly.rings, wiggly.kept_rings),
+        (100, 0, 0)
+    );
+    assert!(wiggly.kept_points >= 2 && wiggly.kept_points < 25);
+    assert_eq!(geojsonvt.index_stats().total_input_features, 2);
+
+    let strict = Options {
+        strict: true,
+        ..options
+    };
+    let GeoJson::FeatureCollection(collection) = geojson else {
+        unreachable!()
+    };
+    let error = GeoJSONVT::try_new(collection, strict).err().unwrap();
+    assert_eq!(
+        error,
+        BuildError::SkippedFeature(SkippedFeature {
+            index: 1,
+            id: string("null"),
+            reason: SkipReason::NoGeometry,
+        })
+    );
+    assert_eq!(
+        error.to_string(),
+        r#"strict mode: feature 1 (id "null") has no geometry"#
+    );
+}
+
+#[test]
+fn test_strict_coordinates() {
+    let geojson = GeoJson::from_str(
+        r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":"ok","properties":{},"geometry":{"type":"Point","coordinates":[-890,10]}},
+            {"type":"Feature","id":"lat","properties":{},"geometry":{"type":"LineString","coordinates":[[0,0],[10,95]]}},
+            {"type":"Feature","id":"lng","properties":{},"geometry":{"type":"GeometryCollection","geometries":[
+                {"type":"Point","coordinates":[0,0]},
+                {"type":"Polygon","coordinates":[[[0,0],[950,0],[0,10],[0,0]]]}]}}
+        ]}"#,
+    )
+    .unwrap();
+    let lenient = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    assert!(lenient.conversion_errors().is_empty());
+    assert!(lenient.conversion_report().is_empty());
+
+    let options = Options {
+        strict_coordinates: true,
+        ..Options::default()
+    };
+    let geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let string = |s: &str| Some(Id::String(s.to_string()));
+    assert_eq!(
+        geojsonvt.conversion_errors(),
+        [
+            ConvertError::CoordinateOutOfRange {
+                index: 1,
+                id: string("lat"),
+                lng: 10.,
+                lat: 95.
+            },
+            ConvertError::CoordinateOutOfRange {
+                index: 2,
+                id: string("lng"),
+                lng: 950.,
+                lat: 0.
+            },
+        ]
+    );
+    assert_eq!(
+        geojsonvt.conversion_errors()[0].to_string(),
+        "feature 1 has coordinate [10, 95] out of range"
+    );
+    let skipped = &geojsonvt.conversion_report().skipped;
+    assert_eq!(skipped.len(), 2);
+    assert!(
+        skipped
+            .iter()
+            .all(|skipped| skipped.reason == SkipReason::CoordinateOutOfRange)
+    );
+
+    // A single world either side rejects the point as well.
+    let options = Options {
+        lng_wrap_allowance: 1,
+        ..options
+    };
+    let geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    assert_eq!(geojsonvt.conversion_errors().len(), 3);
+
+    // Coordinates that are not numbers are out of range too.
+    let nan = Geometry::new(Value::Point(vec![f64::NAN, 0.]));
+    let features = FeatureCollection::from_iter([geojson::Feature::from(nan)]);
+    let geojsonvt = GeoJSONVT::new(features, options);
+    assert_eq!(geojsonvt.conversion_errors().len(), 1);
+}
+
+#[test]
+fn test_query_point() {
+    let features = FeatureCollection::from_str(
+        r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","id":"donut","properties":{},"geometry":{"type":"MultiPolygon","coordinates":[
+                [[[0,0],[1000,0],[1000,1000],[0,1000],[0,0]],[[400,400],[400,600],[600,600],[600,400],[400,400]]],
+                [[[2000,2000],[2100,2000],[2100,2100],[2000,2000]]]]}},
+            {"type":"Feature","id":"road","properties":{},"geometry":{"type":"LineString","coordinates":[[500,0],[500,2000],[3000,2000]]}},
+            {"type":"Feature","id":"poi","properties":{},"geometry":{"type":"Point","coordinates":[3000,3000]}}
+        ]}"#,
+    )
+    .unwrap();
+    let tile = geojsonvt::Tile {
+        feature_collection: features,
+        ..Default::default()
+    };
+    let ids = |px, py, radius| {
+        tile.query_point(px, py, radius)
+            .iter()
+            .map(|f| match &f.id {
+                Some(Id::String(id)) => id.as_str(),
+                _ => panic!("missing id"),
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(ids(200., 200., 0.), ["donut"]);
+    // Inside the hole, then near its edge.
+    assert_eq!(ids(550., 500., 10.), Vec::<&str>::new());
+    assert_eq!(ids(595., 500., 10.), ["donut"]);
+    assert_eq!(ids(2080., 2020., 0.), ["donut"]);
+    // On a line vertex, inside the donut's ring and beyond both.
+    assert_eq!(ids(500., 2000., 0.), ["road"]);
+    assert_eq!(ids(500., 300., 1.), ["donut", "road"]);
+    assert_eq!(ids(1500., 2003., 2.), Vec::<&str>::new());
+    // Around the point.
+    assert_eq!(ids(3003., 3004., 5.), ["poi"]);
+    assert_eq!(ids(3003., 3004., 4.9), Vec::<&str>::new());
+}
+
+#[test]
+fn test_query_lnglat() {
+    let geojson = GeoJson::from_str(
+        r#"{"type":"Feature","properties":{"name":"square"},"geometry":{"type":"Polygon","coordinates":[[[10,10],[20,10],[20,20],[10,20],[10,10]]]}}"#,
+    )
+    .unwrap();
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    assert_eq!(geojsonvt.query_lnglat(15., 15., 5, 0.).len(), 1);
+    assert!(geojsonvt.query_lnglat(21., 15., 5, 0.).is_empty());
+    // 1 degree of longitude is 0.7 pixels at zoom 0, 22.8 at zoom 5.
+    assert_eq!(geojsonvt.query_lnglat(21., 15., 0, 1.).len(), 1);
+    assert!(geojsonvt.query_lnglat(21., 15., 5, 20.).is_empty());
+    assert_eq!(geojsonvt.query_lnglat(21., 15., 5, 25.).len(), 1);
+}
+
+#[test]
+fn test_query_bbox() {
+    let options = Options {
+        retain_sources: true,
+        ..Options::default()
+    };
+    let geojsonvt = GeoJSONVT::from_geojson(&load("tests/fixtures/us-states.json"), &options);
+    let names = |bbox| {
+        let mut names = geojsonvt
+            .query_bbox(bbox)
+            .unwrap()
+            .iter()
+            .map(|f| f.property("name").unwrap().as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    // Across the border between Colorado and Kansas.
+    assert_eq!(names([-102.5, 38., -101.5, 39.]), ["Colorado", "Kansas"]);
+    assert_eq!(names([-104., 38., -103., 39.]), ["Colorado"]);
+    assert!(names([-40., 30., -30., 40.]).is_empty());
+
+    // Whole geometries are returned, in WGS-84.
+    let kansas = geojsonvt.query_bbox([-99., 38., -98., 39.]).unwrap();
+    let kansas = kansas.into_iter().next().unwrap();
+    let Value::Polygon(rings) = &kansas.geometry.unwrap().value else {
+        panic!("expected a polygon");
+    };
+    let min_lng = rings[0].iter().map(|p| p[0]).fold(f64::INFINITY, f64::min);
+    assert!((min_lng - -102.05).abs() < 0.01, "{min_lng}");
+
+    // Alaska's Aleutians cross the antimeridian.
+    let alaska = names([170., 50., -170., 55.]);
+    assert_eq!(alaska, ["Alaska"]);
+
+    // The sources are filed down to `index_max_zoom`, without changing the results.
+    for index_max_zoom in [0, 9] {
+        let options = Options {
+            index_max_zoom,
+            ..options.clone()
+        };
+        let other = GeoJSONVT::from_geojson(&load("tests/fixtures/us-states.json"), &options);
+        for bbox in [
+            [-102.5, 38., -101.5, 39.],
+            [170., 50., -170., 55.],
+            [-80., 25., -70., 45.],
+        ] {
+            assert_eq!(other.query_bbox(bbox), geojsonvt.query_bbox(bbox));
+        }
+    }
+
+    let bbox = [-104., 38., -103., 39.];
+    let mut dropped = geojsonvt.clone();
+    dropped.drop_sources();
+    assert_eq!(dropped.query_bbox(bbox), Err(QueryError::NoSources));
+    let default =
+        GeoJSONVT::from_geojson(&load("tests/fixtures/us-states.json"), &Options::default());
+    assert_eq!(default.query_bbox(bbox), Err(QueryError::NoSources));
+}
+
+#[test]
+fn test_feature_by_id() {
+    let geojson = GeoJson::from_str(
+        r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "id": 7, "properties": {"name": "seven"},
+             "geometry": {"type": "Point", "coordinates": [10, 20]}},
+            {"type": "Feature", "id": "7", "properties": {"name": "string seven"},
+             "geometry": {"type": "LineString", "coordinates": [[-10, 0], [10, 5]]}},
+            {"type": "Feature", "id": "twin", "properties": {"name": "first"},
+             "geometry": {"type": "Point", "coordinates": [0, 0]}},
+            {"type": "Feature", "id": "twin", "properties": {"name": "second"},
+             "geometry": {"type": "Point", "coordinates": [1, 1]}}
+        ]}"#,
+    )
+    .unwrap();
+    let options = Options {
+        index_feature_ids: true,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let name = |feature: &geojson::Feature| feature.property("name").unwrap().clone();
+
+    let seven = geojsonvt.feature_by_id(&Id::Number(7.into())).unwrap();
+    assert_eq!(name(&seven), "seven");
+    let Value::Point(point) = seven.geometry.unwrap().value else {
+        panic!("expected a point");
+    };
+    assert!(
+        (point[0] - 10.).abs() < 1e-6 && (point[1] - 20.).abs() < 1e-6,
+        "{point:?}"
+    );
+
+    // String and numeric ids are told apart.
+    let string_seven = geojsonvt.feature_by_id(&Id::String("7".into())).unwrap();
+    assert_eq!(name(&string_seven), "string seven");
+    assert!(matches!(
+        string_seven.geometry.unwrap().value,
+        Value::LineString(_)
+    ));
+
+    // Duplicated ids return the first feature, or all of them in input order.
+    let twin = Id::String("twin".into());
+    assert_eq!(name(&geojsonvt.feature_by_id(&twin).unwrap()), "first");
+    let twins = geojsonvt.features_by_id(&twin);
+    assert_eq!(
+        twins.iter().map(name).collect::<Vec<_>>(),
+        ["first", "second"]
+    );
+
+    assert!(geojsonvt.feature_by_id(&Id::Number(8.into())).is_none());
+    assert!(geojsonvt.features_by_id(&Id::Number(8.into())).is_empty());
+
+    // The ids stay indexed once the sources are dropped.
+    geojsonvt.drop_sources();
+    assert!(geojsonvt.feature_by_id(&Id::Number(7.into())).is_some());
+
+    // Nothing is indexed by default.
+    let geojsonvt = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    assert!(geojsonvt.feature_by_id(&Id::Number(7.into())).is_none());
+}
+
+#[test]
+fn test_index_stats_non_empty_tiles() {
+    // A point just off the origin, within the buffer of the three tiles around it at each zoom.
+    let point = GeoJson::from_str(r#"{"type": "Point", "coordinates": [0.01, -0.01]}"#).unwrap();
+    let options = Options {
+        index_max_points: 0,
+        index_max_zoom: 4,
+        max_zoom: 4,
+        ..Options::default()
+    };
+    let buffered = GeoJSONVT::from_geojson(&point, &options).index_stats();
+    let clipped = GeoJSONVT::from_geojson(
+        &point,
+        &Options {
+            clip_output_to_extent: true,
+            ..options
+        },
+    )
+    .index_stats();
+    for stats in [&buffered, &clipped] {
+        assert_eq!(stats.total_tiles, 1 + 4 * 4);
+        assert!((1..=4).all(|z| stats.tiles_per_zoom[&z] == 4));
+    }
+    assert_eq!(buffered.non_empty_tiles, buffered.total_tiles);
+    assert_eq!(buffered.non_empty_tiles_per_zoom, buffered.tiles_per_zoom);
+
+    // Only the tile holding the point has it once the buffer is clipped away.
+    assert_eq!(clipped.non_empty_tiles, 5);
+    assert!((0..=4).all(|z| clipped.non_empty_tiles_per_zoom[&z] == 1));
+}
+
+#[test]
+fn test_clone_shares_tiles() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let options = Options {
+        index_max_points: 1000,
+        ..Options::default()
+    };
+    let mut original = GeoJSONVT::from_geojson(&geojson, &options);
+    let indexed = original.internal_tiles().len();
+    let mut snapshot = original.clone();
+    let shared = |a: &GeoJSONVT, b: &GeoJSONVT| {
+        let tiles = b.internal_tiles();
+        a.internal_tiles()
+            .iter()
+            .filter(|(id, tile)| {
+                tiles.get(id).is_some_and(|other| {
+                    std::ptr::eq(tile.tile(), other.tile())
+                        && std::ptr::eq(tile.source_features(), other.source_features())
+                })
+            })
+            .count()
+    };
+    assert_eq!(shared(&original, &snapshot), indexed);
+
+    let deep = original.tile(9, 148, 194).clone();
+    let other = snapshot.tile(8, 60, 96).clone();
+    assert!(!deep.feature_collection.features.is_empty());
+    assert!(!other.feature_collection.features.is_empty());
+
+    // Each clone only holds the tiles it generated.
+    let id = |geojsonvt: &GeoJSONVT, z, x, y| {
+        let tiles = geojsonvt.internal_tiles();
+        tiles
+            .values()
+            .any(|tile| tile.coord() == TileCoord::new(x, y, z))
+    };
+    assert!(id(&original, 9, 148, 194) && !id(&original, 8, 60, 96));
+    assert!(id(&snapshot, 8, 60, 96) && !id(&snapshot, 9, 148, 194));
+    assert_eq!(original.tile(9, 148, 194), &deep);
+    assert_eq!(snapshot.tile(9, 148, 194), &deep);
+
+    // Tiles neither clone changed are still shared.
+    let shared = shared(&original, &snapshot);
+    assert!(shared > indexed / 2, "{shared} of {indexed}");
+    assert!(shared < indexed);
+}
+
+#[test]
+fn test_index_is_send() {
+    fn assert_send<T: Send>(_: T) {}
+    let geojsonvt = GeoJSONVT::from_geojson(
+        &load("tests/fixtures/us-states.json"),
+        &Options {
+            tile_property_hook: Some(TilePropertyHook::new(|_, _, _, _| None)),
+            on_tile_created: Some(OnTileCreated::new(|_, _| {})),
+            ..Options::default()
+        },
+    );
+    let mut snapshot = geojsonvt.clone();
+    let tile = std::thread::spawn(move || snapshot.tile(0, 0, 0).clone())
+        .join()
+        .unwrap();
+    assert_eq!(tile.feature_collection.features.len(), 52);
+    assert_send(geojsonvt);
+}
+
+#[test]
+fn test_tile_is_fully_covered() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let options = Options {
+        max_zoom: 10,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    // Within Kansas.
+    assert!(geojsonvt.tile_is_fully_covered(10, 231, 393));
+    // Across the border between Colorado and Kansas, and over the Atlantic.
+    assert!(!geojsonvt.tile_is_fully_covered(10, 221, 393));
+    assert!(!geojsonvt.tile_is_fully_covered(10, 400, 393));
+    // Kansas only fills part of the tile at lower zooms.
+    assert!(!geojsonvt.tile_is_fully_covered(7, 28, 49));
+
+    // A hole within the tile leaves it uncovered.
+    let ring = |min: f64, max: f64| {
+        vec![
+            vec![min, min],
+            vec![max, min],
+            vec![max, max],
+            vec![min, max],
+            vec![min, min],
+        ]
+    };
+    let polygon = |rings| GeoJson::from(Geometry::new(Value::Polygon(rings)));
+    let mut solid = GeoJSONVT::from_geojson(&polygon(vec![ring(-10., 10.)]), &options);
+    assert!(solid.tile_is_fully_covered(10, 512, 511));
+    let mut holed =
+        GeoJSONVT::from_geojson(&polygon(vec![ring(-10., 10.), ring(0.01, 0.02)]), &options);
+    assert!(!holed.tile_is_fully_covered(10, 512, 511));
+    assert!(holed.tile_is_fully_covered(10, 520, 511));
+}
+
+#[test]
+fn test_center_origin() {
+    // A square centered on the root tile.
+    let ring = [
+        [-10., -10.],
+        [10., -10.],
+        [10., 10.],
+        [-10., 10.],
+        [-10., -10.],
+    ];
+    let ring = ring.iter().map(|p| p.to_vec()).collect();
+    let geojson = GeoJson::Geometry(Geometry::new(Value::Polygon(vec![ring])));
+    let options = Options {
+        origin: TileOrigin::Center,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let tile = geojsonvt.tile(0, 0, 0).clone();
+    let Value::Polygon(rings) = &tile.feature_collection.features[0]
+        .geometry
+        .as_ref()
+        .unwrap()
+        .value
+    else {
+        panic!("expected a polygon");
+    };
+    let (xs, ys): (Vec<_>, Vec<_>) = rings[0].iter().map(|p| (p[0], p[1])).unzip();
+    for values in [xs, ys] {
+        let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        assert_eq!(min, -max);
+    }
+    assert_eq!(tile.pixel_to_lnglat(0., 0.), Some((0., 0.)));
+    assert_eq!(tile.lnglat_to_pixel(0., 0.), Some((0., 0.)));
+
+    // The same geometry as with the default origin, shifted by half the extent.
+    let mut top_left = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    let Value::Polygon(expected) = &top_left.tile(0, 0, 0).feature_collection.features[0]
+        .geometry
+        .as_ref()
+        .unwrap()
+        .value
+    else {
+        panic!("expected a polygon");
+    };
+    let shifted: Vec<Vec<Vec<f64>>> = expected
+        .iter()
+        .map(|ring| {
+            ring.iter()
+                .map(|p| vec![p[0] - 2048., p[1] - 2048.])
+                .collect()
+        })
+        .collect();
+    assert_eq!(rings, &shifted);
+
+    assert_eq!(geojsonvt.query_lnglat(5., 5., 0, 0.).len(), 1);
+    assert!(geojsonvt.query_lnglat(15., 5., 0, 0.).is_empty());
+    assert!(geojsonvt.tile_is_fully_covered(10, 512, 511));
+}
+
+#[test]
+fn test_corner_sliver_dropped() {
+    // A polygon whose edge passes a tenth of a unit inside the top-left corner of the buffer of
+    // tile 1/1/1, leaving a sub-pixel sliver once clipped to it.
+    let geojson = load("tests/fixtures/corner-sliver.json");
+    let options = Options {
+        remove_slivers: true,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    assert!(
+        !geojsonvt
+            .tile(1, 0, 0)
+            .feature_collection
+            .features
+            .is_empty()
+    );
+    let tile = geojsonvt.tile(1, 1, 1);
+    assert!(tile.feature_collection.features.is_empty(), "{tile:?}");
+    assert_eq!(tile.simplified_count, 0);
+
+    let mut kept = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    assert_eq!(kept.tile(1, 1, 1).feature_collection.features.len(), 1);
+}
+
+#[test]
+fn test_clipped_corner_filtered_by_its_area() {
+    // A large polygon whose corner enters tile 1/1/1 by a twentieth of a degree, about a pixel.
+    let ring = [
+        [-60., 60.],
+        [0.05, 60.],
+        [0.05, -0.05],
+        [-60., -0.05],
+        [-60., 60.],
+    ];
+    let ring = ring.iter().map(|p| p.to_vec()).collect();
+    let geojson = GeoJson::Geometry(Geometry::new(Value::Polygon(vec![ring])));
+    let options = Options {
+        max_zoom: 5,
+        buffer: 0,
+        remove_slivers: true,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    for (z, xy) in [(1, 1), (2, 2)] {
+        let tile = geojsonvt.tile(z, xy, xy);
+        assert!(
+            tile.feature_collection.features.is_empty(),
+            "z{z}: {tile:?}"
+        );
+    }
+    assert_eq!(
+        geojsonvt.tile(5, 16, 16).feature_collection.features.len(),
+        1
+    );
+
+    let options = Options {
+        remove_slivers: false,
+        ..options
+    };
+    let mut kept = GeoJSONVT::from_geojson(&geojson, &options);
+    assert_eq!(kept.tile(1, 1, 1).feature_collection.features.len(), 1);
+}
+
+#[test]
+fn test_explode_multipoints() {
+    let points = [-170., -160., 10., 20., 30.].map(|lng| vec![lng, 10.]);
+    let feature = |id: Option<Id>| {
+        let mut feature = geojson::Feature::from(Geometry::new(Value::MultiPoint(points.to_vec())));
+        feature.id = id;
+        GeoJson::Feature(feature)
+    };
+    let ids_in = |geojson: &GeoJson, options: &Options, (z, x, y): (u8, u32, u32)| {
+        let mut geojsonvt = GeoJSONVT::from_geojson(geojson, options);
+        let tile = geojsonvt.tile(z, x, y);
+        assert!(
+            tile.feature_collection
+                .features
+                .iter()
+                .all(|f| matches!(f.geometry.as_ref().unwrap().value, Value::Point(_)))
+        );
+        tile.feature_collection
+            .features
+            .iter()
+            .map(|f| f.id.clone())
+            .collect::<Vec<_>>()
+    };
+    let ids = |geojson: &GeoJson, options: &Options| ids_in(geojson, options, (1, 1, 0));
+    let options = Options {
+        explode_multipoints: true,
+        ..Options::default()
+    };
+    let string = |s: &str| Some(Id::String(s.to_string()));
+
+    let named = feature(string("a"));
+    assert_eq!(
+        ids(&named, &options),
+        [string("a-2"), string("a-3"), string("a-4")]
+    );
+    // A point keeps the id of its position in the source in every tile.
+    assert_eq!(
+        ids_in(&named, &options, (1, 0, 0)),
+        [string("a-0"), string("a-1")]
+    );
+    assert_eq!(
+        ids_in(&named, &options, (2, 2, 1)),
+        [string("a-2"), string("a-3"), string("a-4")]
+    );
+    let numbered = feature(Some(Id::Number(7.into())));
+    assert_eq!(ids(&numbered, &options), [None, None, None]);
+    let generated = Options {
+        generate_id: true,
+        ..options.clone()
+    };
+    assert_eq!(
+        ids(&named, &generated),
+        [string("0-2"), string("0-3"), string("0-4")]
+    );
+
+    let mut geojsonvt = GeoJSONVT::from_geojson(&named, &Options::default());
+    let features = &geojsonvt.tile(1, 1, 0).feature_collection.features;
+    assert_eq!(features.len(), 1);
+    assert!(matches!(
+        features[0].geometry.as_ref().unwrap().value,
+        Value::MultiPoint(ref points) if points.len() == 3
+    ));
+}
+
+#[test]
+fn test_get_tile() {
+    let geojson = load("tests/fixtures/us-states.json");
+    for capacity in [None, Some(4)] {
+        let options = Options {
+            tile_cache_capacity: capacity,
+            min_zoom: 1,
+            ..Options::default()
+        };
+        let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+        assert_eq!(geojsonvt.get_tile(7, 37, 48), None);
+        let tile = geojsonvt.tile(7, 37, 48).clone();
+        assert_eq!(geojsonvt.get_tile(7, 37, 48), Some(tile));
+        // Below `min_zoom` tiles are empty, above `max_zoom` there are none.
+        assert_eq!(
+            geojsonvt.get_tile(0, 0, 0),
+            Some(geojsonvt.tile(0, 0, 0).clone())
+        );
+        assert_eq!(geojsonvt.get_tile(19, 0, 0), None);
+
+        assert!(geojsonvt.evict_tile(7, 37, 48));
+        assert_eq!(geojsonvt.get_tile(7, 37, 48), None);
+    }
+}
+
+#[test]
+fn test_features_in_tile() {
+    let geojson = load("tests/fixtures/us-states.json");
+    for capacity in [None, Some(4)] {
+        let options = Options {
+            tile_cache_capacity: capacity,
+            ..Options::default()
+        };
+        let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+        let names = |properties: Option<&geojson::JsonObject>| {
+            properties.and_then(|p| p["name"].as_str().map(str::to_string))
+        };
+        let rendered = geojsonvt
+            .tile(7, 37, 48)
+            .feature_collection
+            .features
+            .iter()
+            .filter_map(|feature| names(feature.properties.as_ref()))
+            .collect::<HashSet<_>>();
+        let (x0, y0, x1, y1) = (37. / 128., 48. / 128., 38. / 128., 49. / 128.);
+        let b = 64. / 4096. / 128.;
+        let mut sources = HashSet::new();
+        for feature in geojsonvt.features_in_tile(7, 37, 48) {
+            let bbox = feature.bbox.unwrap();
+            assert!(bbox.min_x >= x0 - b && bbox.max_x <= x1 + b, "{bbox:?}");
+            assert!(bbox.min_y >= y0 - b && bbox.max_y <= y1 + b, "{bbox:?}");
+            sources.extend(names(feature.properties.as_ref().as_ref()));
+        }
+        assert!(!rendered.is_empty());
+        assert_eq!(sources, rendered);
+
+        // Generating a tile splits its ancestors, unless it is kept in the cache instead.
+        let root = geojsonvt.features_in_tile(0, 0, 0).count();
+        assert_eq!(root == 0, capacity.is_none());
+        assert_eq!(geojsonvt.features_in_tile(7, 0, 0).count(), 0);
+    }
+}
+
+#[test]
+fn test_ring_metrics() {
+    let square = |lng: f64| {
+        vec![vec![
+            vec![lng - 10., 20.],
+            vec![lng + 10., 20.],
+            vec![lng + 10., 40.],
+            vec![lng - 10., 40.],
+            vec![lng - 10., 20.],
+        ]]
+    };
+    let metrics = |feature: &geojson::Feature| {
+        let properties = feature.properties.as_ref().unwrap();
+        let value = |key: &str| properties[key].as_f64().unwrap();
+        (value("mapbox_clip_start"), value("mapbox_clip_end"))
+    };
+    let options = Options {
+        buffer: 0,
+        line_metrics: true,
+        ring_metrics: true,
+        ..Options::default()
+    };
+    // The first point of the square is in the left tile, where its part wraps around it.
+    let polygon = GeoJson::from(Geometry::new(Value::Polygon(square(0.))));
+    let mut geojsonvt = GeoJSONVT::from_geojson(&polygon, &options);
+    assert_eq!(
+        geojsonvt.tile(0, 0, 0).feature_collection.features[0].properties,
+        None
+    );
+    let (left_start, left_end) = metrics(&geojsonvt.tile(1, 0, 0).feature_collection.features[0]);
+    let (right_start, right_end) = metrics(&geojsonvt.tile(1, 1, 0).feature_collection.features[0]);
+    assert!(left_end < left_start);
+    assert!(right_start < right_end);
+    assert!((left_end - right_start).abs() < 1e-12);
+    assert!((right_end - left_start).abs() < 1e-12);
+    let span = |start: f64, end: f64| (end - start).rem_euclid(1.);
+    let total = span(left_start, left_end) + span(right_start, right_end);
+    assert!((total - 1.).abs() < 1e-12, "{total}");
+
+    // A ring cut along both axes is measured along its own perimeter in each quadrant.
+    let corner = GeoJson::from(Geometry::new(Value::Polygon(vec![vec![
+        vec![-10., -10.],
+        vec![10., -10.],
+        vec![10., 10.],
+        vec![-10., 10.],
+        vec![-10., -10.],
+    ]])));
+    let mut geojsonvt = GeoJSONVT::from_geojson(&corner, &options);
+    let mut total = 0.;
+    for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+        let (start, end) = metrics(&geojsonvt.tile(1, x, y).feature_collection.features[0]);
+        assert!(
+            (span(start, end) - 0.25).abs() < 1e-12,
+            "{x}/{y}: {start}..{end}"
+        );
+        total += span(start, end);
+    }
+    assert!((total - 1.).abs() < 1e-12, "{total}");
+
+    // Each polygon of a multipolygon is measured along its own outer ring.
+    let multi = GeoJson::from(Geometry::new(Value::MultiPolygon(vec![
+        square(0.),
+        square(0.),
+    ])));
+    let mut geojsonvt = GeoJSONVT::from_geojson(&multi, &options);
+    let features = &geojsonvt.tile(1, 1, 0).feature_collection.features;
+    assert_eq!(features.len(), 2);
+    assert_eq!(metrics(&features[0]), (right_start, right_end));
+    assert_eq!(metrics(&features[1]), (right_start, right_end));
+
+    // Without `ring_metrics`, polygons are not measured.
+    let options = Options {
+        ring_metrics: false,
+        ..options
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&polygon, &options);
+    assert_eq!(
+        geojsonvt.tile(1, 1, 0).feature_collection.features[0].properties,
+        None
+    );
+}
+
+#[test]
+fn test_query_radius() {
+    let geojson = GeoJson::from_str(
+        r#"{"type": "FeatureCollection", "features": [
+            {"type": "Feature", "properties": {"name": "poi"},
+             "geometry": {"type": "Point", "coordinates": [10, 0]}},
+            {"type": "Feature", "properties": {"name": "road"},
+             "geometry": {"type": "LineString", "coordinates": [[0, 5], [20, 5]]}},
+            {"type": "Feature", "properties": {"name": "donut"},
+             "geometry": {"type": "Polygon", "coordinates": [
+                [[30, -10], [50, -10], [50, 10], [30, 10], [30, -10]],
+                [[35, -5], [45, -5], [45, 5], [35, 5], [35, -5]]]}},
+            {"type": "Feature", "properties": {"name": "dateline"},
+             "geometry": {"type": "LineString", "coordinates": [[170, 0], [190, 0]]}}
+        ]}"#,
+    )
+    .unwrap();
+    let options = Options {
+        retain_sources: true,
+        ..Options::default()
+    };
+    let geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    // A degree of longitude spans 111 km in EPSG:3857 meters.
+    let degree = 2. * PI * 6378137. / 360.;
+    let names_at = |lng, lat, degrees: f64, zoom| {
+        let mut names = geojsonvt
+            .query_radius(lng, lat, degrees * degree, zoom)
+            .unwrap()
+            .iter()
+            .map(|f| f.property("name").unwrap().as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    let names = |lng, lat, degrees| names_at(lng, lat, degrees, 8);
+    // Distance to points and segments.
+    assert_eq!(names(10., 1., 1.01), ["poi"]);
+    assert_eq!(names(10., 1., 4.1), ["poi", "road"]);
+    assert_eq!(names(24., 5., 3.9), Vec::<String>::new());
+    assert_eq!(names(24., 5., 4.1), ["road"]);
+    // Inside a polygon, but not in its hole unless close to its edges.
+    assert_eq!(names(32., 0., 0.1), ["donut"]);
+    assert_eq!(names(40., 0., 4.9), Vec::<String>::new());
+    assert_eq!(names(40., 0., 5.1), ["donut"]);
+    // The line crossing the antimeridian is found on both sides.
+    assert_eq!(names(-175., 0.5, 1.), ["dateline"]);
+    assert_eq!(names(175., 0.5, 1.), ["dateline"]);
+    // Features are found whichever tile they fall in.
+    assert_eq!(names(-1., 1., 12.), ["poi", "road"]);
+    assert_eq!(names(-1., 1., 10.), ["road"]);
+    // The zoom only changes how far the tiles prune the candidates.
+    for zoom in [0, 3, 14, u8::MAX] {
+        assert_eq!(names_at(-1., 1., 12., zoom), ["poi", "road"]);
+        assert_eq!(names_at(175., 0.5, 1., zoom), ["dateline"]);
+    }
+
+    let road = geojsonvt
+        .query_radius(24., 5., 4.1 * degree, 8)
+        .unwrap()
+        .pop()
+        .unwrap();
+    let Value::LineString(coords) = &road.geometry.unwrap().value else {
+        panic!("expected a line");
+    };
+    assert!((coords[1][0] - 20.).abs() < 1e-9);
+
+    let default = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    assert_eq!(
+        default.query_radius(10., 1., degree, 8),
+        Err(QueryError::NoSources)
+    );
+}
+
+#[test]
+fn test_tile_property_hook() {
+    let hook = TilePropertyHook::new(|z, x, y, properties| {
+        let mut properties = properties.clone();
+        properties.insert("zoom".to_string(), z.into());
+        properties.insert("tile".to_string(), format!("{z}/{x}/{y}").into());
+        // Line metrics are added before the hook runs.
+        if let Some(start) = properties.remove("mapbox_clip_start") {
+            properties.insert("start".to_string(), start);
+        }
+        Some(properties)
+    });
+    let options = Options {
+        line_metrics: true,
+        tile_property_hook: Some(hook),
+        retain_sources: true,
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&load("tests/fixtures/us-states.json"), &options);
+    for (z, x, y) in [(0, 0, 0), (4, 3, 6), (7, 37, 48)] {
+        let features = &geojsonvt.tile(z, x, y).feature_collection.features;
+        assert!(!features.is_empty());
+        for feature in features {
+            assert_eq!(feature.property("zoom"), Some(&z.into()));
+            let tile = format!("{z}/{x}/{y}");
+            assert_eq!(feature.property("tile"), Some(&tile.into()));
+            assert!(feature.property("name").is_some());
+        }
+    }
+
+    // Source properties are left untouched.
+    let colorado = geojsonvt
+        .query_bbox([-104., 38., -103., 39.])
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert!(colorado.property("zoom").is_none());
+
+    let line =
+        GeoJson::from_str(r#"{"type": "LineString", "coordinates": [[-100, 40], [-90, 41]]}"#)
+            .unwrap();
+    let mut geojsonvt = GeoJSONVT::from_geojson(&line, &options);
+    let feature = &geojsonvt.tile(7, 30, 48).feature_collection.features[0];
+    assert!(feature.property("start").is_some());
+    assert!(feature.property("mapbox_clip_start").is_none());
+    assert!(feature.property("mapbox_clip_end").is_some());
+}
+
+#[test]
+fn test_layer_by_property() {
+    let geojson = load("tests/fixtures/layered.json");
+    let layer_counts = |tile: &geojsonvt::Tile| {
+        let layers = tile.layers.iter();
+        let counts = layers.map(|(name, layer)| (name.clone(), layer.features.len()));
+        counts.collect::<Vec<_>>()
+    };
+    let counts = |layers: &[(Option<&str>, usize)]| {
+        layers
+            .iter()
+            .map(|&(name, len)| (name.map(str::to_string), len))
+            .collect::<Vec<_>>()
+    };
+    let mut plain = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    assert!(plain.tile(0, 0, 0).layers.is_empty());
+
+    let options = Options {
+        layer_by_property: Some("layer".to_string()),
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let tile = geojsonvt.tile(0, 0, 0);
+    assert_eq!(
+        layer_counts(tile),
+        counts(&[
+            (None, 1),
+            (Some("landuse"), 1),
+            (Some("roads"), 3),
+            (Some("water"), 2)
+        ])
+    );
+    assert!(tile.feature_collection.features.is_empty());
+    for (name, layer) in &tile.layers {
+        for feature in &layer.features {
+            let layer = feature.property("layer").and_then(|layer| layer.as_str());
+            assert_eq!(layer, name.as_deref());
+        }
+    }
+    // Input order is kept within each layer.
+    let roads: Vec<_> = tile
+        .layers
+        .iter()
+        .find(|(name, _)| name.as_deref() == Some("roads"))
+        .unwrap()
+        .1
+        .features
+        .iter()
+        .map(|feature| feature.property("name").unwrap().as_str().unwrap())
+        .collect();
+    assert_eq!(roads, ["route 1", "route 2", "route 3"]);
+
+    let expected = [
+        ((1, 0, 0), counts(&[(Some("roads"), 1), (Some("water"), 1)])),
+        (
+            (1, 1, 0),
+            counts(&[(None, 1), (Some("landuse"), 1), (Some("roads"), 1)]),
+        ),
+        ((1, 1, 1), counts(&[(Some("roads"), 1), (Some("water"), 1)])),
+    ];
+    for ((z, x, y), layers) in expected {
+        let tile = geojsonvt.tile(z, x, y);
+        assert_eq!(layer_counts(tile), layers, "{z}/{x}/{y}");
+        // The same features as without layers, grouped.
+        let len = plain.tile(z, x, y).feature_collection.features.len();
+        assert_eq!(tile.features().count(), len);
+    }
+    assert!(geojsonvt.tile(1, 0, 1).is_placeholder());
+
+    // The layer property can be stripped from the output, layers are named before.
+    let hook = TilePropertyHook::new(|_, _, _, properties| {
+        let mut properties = properties.clone();
+        properties.remove("layer");
+        Some(properties)
+    });
+    let options = Options {
+        tile_property_hook: Some(hook),
+        ..options
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let tile = geojsonvt.tile(1, 1, 0);
+    assert_eq!(
+        layer_counts(tile),
+        counts(&[(None, 1), (Some("landuse"), 1), (Some("roads"), 1)])
+    );
+    assert!(
+        tile.features()
+            .all(|feature| feature.property("layer").is_none())
+    );
+}
+
+#[test]
+fn test_layer_by_property_round_trip() {
+    // A "default" value is a layer of its own, apart from the features without the property.
+    let geojson = GeoJson::from_str(
+        r#"{"type":"FeatureCollection","features":[
+            {"type":"Feature","properties":{"layer":"default"},"geometry":{"type":"Point","coordinates":[10,10]}},
+            {"type":"Feature","properties":{},"geometry":{"type":"Point","coordinates":[20,20]}},
+            {"type":"Feature","properties":{"layer":"roads"},"geometry":{"type":"LineString","coordinates":[[0,0],[30,5]]}}
+        ]}"#,
+    )
+    .unwrap();
+    let options = Options {
+        layer_by_property: Some("layer".to_string()),
+        ..Options::default()
+    };
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let tile = geojsonvt.tile(1, 1, 0).clone();
+    let names: Vec<_> = tile
+        .layers
+        .iter()
+        .map(|(name, _)| name.as_deref())
+        .collect();
+    assert_eq!(names, [None, Some("default"), Some("roads")]);
+
+    let compact = CompactTile::from_bytes(tile.to_compact().into_bytes()).unwrap();
+    assert_eq!(compact.to_tile(), Ok(tile.clone()));
+
+    // The GeoJSON output holds one collection per layer, as does the encoded tile.
+    let json: serde_json::Value = serde_json::from_str(&tile.to_geojson_string()).unwrap();
+    let layers = json.as_array().unwrap();
+    assert_eq!(layers.len(), 3);
+    for (layer, (name, collection)) in layers.iter().zip(&tile.layers) {
+        assert_eq!(layer["layer"].as_str(), name.as_deref());
+        let parsed: FeatureCollection = serde_json::from_value(layer.clone()).unwrap();
+        assert_eq!(parsed.features, collection.features);
+    }
+    assert_eq!(
+        tile.to_geojson_bytes(),
+        tile.to_geojson_string().into_bytes()
+    );
+}
+
+#[test]
+fn test_numeric_property_style() {
+    let line = GeoJson::from_str(
+        r#"{"type": "LineString", "coordinates": [[-120, 30], [-60, 45], [0, 20], [60, 40]]}"#,
+    )
+    .unwrap();
+    let clip_values = |style| {
+        let options = Options {
+            line_metrics: true,
+            numeric_property_style: style,
+            ..Options::default()
+        };
+        let geojsonvt = GeoJSONVT::from_geojson(&line, &options);
+        let mut values = Vec::new();
+        for z in 1..=4 {
+            for (_, tile) in geojsonvt.tiles_at_zoom(z) {
+                for feature in &tile.feature_collection.features {
+                    let properties = feature.properties.as_ref().unwrap();
+                    values.push(properties["mapbox_clip_start"].clone());
+                    values.push(properties["mapbox_clip_end"].clone());
+                }
+            }
+        }
+        values
+    };
+    // Whole values, such as the start of the first part, are floats like the others.
+    let floats = clip_values(NumberStyle::AlwaysFloat);
+    assert!(floats.len() > 8);
+    assert!(floats.iter().all(|value| value.is_f64()), "{floats:?}");
+    assert!(floats.iter().any(|value| value.as_f64() == Some(0.)));
+
+    let mixed = clip_values(NumberStyle::Mixed);
+    assert!(mixed.iter().any(|value| value.is_u64()));
+    assert!(mixed.iter().any(|value| value.is_f64()));
+    let as_f64 =
+        |values: &[geojson::JsonValue]| values.iter().map(|v| v.as_f64()).collect::<Vec<_>>();
+    assert_eq!(as_f64(&floats), as_f64(&mixed));
+}
+
+#[test]
+fn test_split_once_matches_pyramid() {
+    let options = Options {
+        index_max_zoom: 0,
+        ..Options::default()
+    };
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let root: Vec<Arc<VtFeature>> = geojsonvt.features_in_tile(0, 0, 0).cloned().collect();
+    assert!(!root.is_empty());
+
+    let mut level = vec![(TileCoord::new(0, 0, 0), root)];
+    for _ in 0..3 {
+        level = level
+            .into_iter()
+            .filter(|(_, features)| !features.is_empty())
+            .flat_map(|(coord, features)| {
+                split_once(
+                    &features,
+                    coord.z(),
+                    coord.x(),
+                    coord.y(),
+                    geojsonvt.options(),
+                )
+            })
+            .collect();
+    }
+    let geometries = |features: &[Arc<VtFeature>]| {
+        features
+            .iter()
+            .map(|feature| (feature.geometry.clone(), feature.properties.clone()))
+            .collect::<Vec<_>>()
+    };
+    let mut compared = 0;
+    for (coord, features) in &level {
+        let (z, x, y) = (coord.z(), coord.x(), coord.y());
+        let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+        let expected: Vec<_> = geojsonvt.features_in_tile(z, x, y).cloned().collect();
+        assert_eq!(geometries(features), geometries(&expected), "{z}/{x}/{y}");
+        compared += !features.is_empty() as usize;
+    }
+    assert!(compared > 3);
+}
+
+#[test]
+fn test_wrapped_tile_x() {
+    let geojson = load("tests/fixtures/us-states.json");
+    for capacity in [None, Some(4)] {
+        let options = Options {
+            tile_cache_capacity: capacity,
+            ..Options::default()
+        };
+        let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+        // Requested first wrapped, so the tile is generated from the wrapped request.
+        let wrapped = geojsonvt.tile(7, 128 + 37, 48).clone();
+        assert!(!wrapped.is_placeholder());
+        assert_eq!(wrapped.coord, Some(TileCoord::new(37, 48, 7)));
+        let mut fresh = GeoJSONVT::from_geojson(&geojson, &options);
+        assert_eq!(fresh.tile(7, 37, 48), &wrapped);
+        assert_eq!(geojsonvt.tile(7, 2 * 128 + 37, 48), &wrapped);
+
+        let empty = geojsonvt.tile(7, 128, 0);
+        assert!(empty.is_placeholder());
+        assert!(empty.feature_collection.features.is_empty());
+    }
+}
+
+#[test]
+fn test_try_tile_wraps_x() {
+    let geojson = load("tests/fixtures/us-states.json");
+    for capacity in [None, Some(4)] {
+        let options = Options {
+            tile_cache_capacity: capacity,
+            ..Options::default()
+        };
+        let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+        for (z, x, y) in [(0, 0, 0), (3, 1, 3), (5, 7, 12), (7, 37, 48)] {
+            let expected = geojsonvt.tile(z, x, y).clone();
+            let z2 = 1i64 << z;
+            for wrapped in [x as i64 - z2, x as i64 + z2, x as i64 - 3 * z2] {
+                assert_eq!(
+                    geojsonvt.try_tile(z, wrapped, y),
+                    Ok(&expected),
+                    "{z}/{wrapped}/{y}"
+                );
+            }
+            let last = geojsonvt.tile(z, z2 as u32 - 1, y).clone();
+            assert_eq!(geojsonvt.try_tile(z, -1, y), Ok(&last));
+            let first = geojsonvt.tile(z, 0, y).clone();
+            assert_eq!(geojsonvt.try_tile(z, z2, y), Ok(&first));
+
+            let y = z2 as u32;
+            assert_eq!(
+                geojsonvt.try_tile(z, x as i64, y),
+                Err(TileError::RowOutOfRange { z, y })
+            );
+            assert!(geojsonvt.tile(z, x, y).is_placeholder());
+        }
+        assert_eq!(
+            geojsonvt.try_tile(19, 0, 0),
+            Err(TileError::ZoomTooHigh {
+                z: 19,
+                max_zoom: 18
+            })
+        );
+    }
+}
@@ -25,7 +25,7 @@ fn test_multi_world() {
         foreign_members: None,
     });
     let vt = &mut GeoJSONVT::from_geojson(&right_point, &Options::default());
-    let g = &vt.internal_tiles().get(&0).unwrap().source_feature[0].geometry;
+    let g = &vt.internal_tiles().get(&0).unwrap().source_features()[0].geometry;
 
     match g {
         VtGeometry::Point(p) => {
@@ -43,7 +43,7 @@ fn test_multi_world() {
         }
     }
     let vt = &mut GeoJSONVT::from_geojson(&left_point, &Options::default());
-    let g = &vt.internal_tiles().get(&0).unwrap().source_feature[0].geometry;
+    let g = &vt.internal_tiles().get(&0).unwrap().source_features()[0].geometry;
 
     match g {
         VtGeometry::Point(p) => {
@@ -67,10 +67,10 @@ fn test_multi_world() {
     let GeoJson::Feature(f2) = right_point else {
         panic!("not a feature");
     };
-    let fc = GeoJson::FeatureCollection(FeatureCollection::from_iter(vec![f1, f2].into_iter()));
+    let fc = GeoJson::FeatureCollection(FeatureCollection::from_iter(vec![f1, f2]));
     let vt = GeoJSONVT::from_geojson(&fc, &Options::default());
     let tile = vt.internal_tiles().get(&0).unwrap();
-    match &tile.source_feature[0].geometry {
+    match &tile.source_features()[0].geometry {
         VtGeometry::Point(p) => {
             assert_eq!(
                 p,
@@ -85,7 +85,7 @@ fn test_multi_world() {
             panic!("not a point");
         }
     };
-    match &tile.source_feature[1].geometry {
+    match &tile.source_features()[1].geometry {
         VtGeometry::Point(p) => {
             assert_eq!(
                 p,
@@ -0,0 +1,155 @@
+#![cfg(feature = "persist")]
+
+use geojson::{GeoJson, feature::Id};
+use geojsonvt::{DeserializeError, GeoJSONVT, Options, OptionsError, TilePropertyHook};
+use std::fs::File;
+use std::io::BufReader;
+use std::str::FromStr;
+
+fn load(path: &str) -> GeoJson {
+    GeoJson::from_reader(BufReader::new(File::open(path).unwrap())).unwrap()
+}
+
+#[test]
+fn test_index_round_trip() {
+    let options = Options {
+        max_zoom: 10,
+        index_feature_ids: true,
+        ..Options::default()
+    };
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let deep = geojsonvt.tile(9, 148, 194).clone();
+    assert!(!deep.feature_collection.features.is_empty());
+
+    let mut restored = GeoJSONVT::deserialize_index(&geojsonvt.serialize_index(), None).unwrap();
+    assert_eq!(restored.tile_coords(), geojsonvt.tile_coords());
+    assert_eq!(
+        restored.index_stats().total_tiles,
+        geojsonvt.index_stats().total_tiles
+    );
+    assert_eq!(restored.index_stats(), geojsonvt.index_stats());
+    assert_eq!(restored.options().max_zoom, 10);
+    assert_eq!(
+        restored.internal_tiles().len(),
+        geojsonvt.internal_tiles().len()
+    );
+    assert_eq!(restored.tile(9, 148, 194), &deep);
+    // Tiles that were never generated are still generated from the restored sources.
+    for (z, x, y) in [(0, 0, 0), (7, 37, 48), (10, 296, 388)] {
+        let expected = geojsonvt.tile(z, x, y).clone();
+        assert_eq!(restored.tile(z, x, y), &expected, "z{z}-{x}-{y}");
+    }
+
+    let id = Id::String("06".to_string());
+    assert_eq!(restored.feature_by_id(&id), geojsonvt.feature_by_id(&id));
+    assert!(restored.feature_by_id(&id).is_some());
+}
+
+#[test]
+fn test_read_only_index_round_trip() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &Options::default());
+    let generated = geojsonvt.tile(7, 37, 48).clone();
+    geojsonvt.drop_sources();
+
+    let mut restored = GeoJSONVT::deserialize_index(&geojsonvt.serialize_index(), None).unwrap();
+    assert!(restored.is_read_only());
+    assert_eq!(restored.tile(7, 37, 48), &generated);
+    assert!(
+        restored
+            .tile(7, 20, 48)
+            .feature_collection
+            .features
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_deserialize_errors() {
+    let geojson = load("tests/fixtures/us-states.json");
+    let bytes = GeoJSONVT::from_geojson(&geojson, &Options::default()).serialize_index();
+
+    let error = |data: &[u8]| GeoJSONVT::deserialize_index(data, None).unwrap_err();
+    assert!(matches!(error(b""), DeserializeError::InvalidHeader));
+    assert!(matches!(
+        error(b"{\"type\": 1}"),
+        DeserializeError::InvalidHeader
+    ));
+    let mut newer = bytes.clone();
+    newer[4] = 3;
+    assert!(matches!(
+        error(&newer),
+        DeserializeError::UnsupportedVersion(3)
+    ));
+    assert!(matches!(
+        error(&bytes[..bytes.len() / 2]),
+        DeserializeError::Decode(_)
+    ));
+}
+
+#[test]
+fn test_deserialize_corrupted_options() {
+    // Indexes of no features that only differ by their max zoom first differ by that byte, the
+    // options being written first.
+    let serialize = |max_zoom| {
+        let geojson = GeoJson::from_str(r#"{"type":"FeatureCollection","features":[]}"#).unwrap();
+        let options = Options {
+            max_zoom,
+            ..Options::default()
+        };
+        GeoJSONVT::from_geojson(&geojson, &options).serialize_index()
+    };
+    let (bytes, other) = (serialize(14), serialize(15));
+    let pos = (0..bytes.len()).find(|&i| bytes[i] != other[i]).unwrap();
+    assert_eq!((bytes[pos], other[pos]), (14, 15));
+
+    for max_zoom in [0, 4, 30] {
+        let mut corrupted = bytes.clone();
+        corrupted[pos] = max_zoom;
+        let error = GeoJSONVT::deserialize_index(&corrupted, None).unwrap_err();
+        let expected = match max_zoom {
+            4 => OptionsError::IndexMaxZoomAboveMaxZoom {
+                index_max_zoom: 5,
+                max_zoom,
+            },
+            _ => OptionsError::MaxZoomOutOfRange { max_zoom },
+        };
+        assert!(
+            matches!(&error, DeserializeError::InvalidOptions(e) if *e == expected),
+            "{error}"
+        );
+    }
+}
+
+#[test]
+fn test_tile_property_hook_round_trip() {
+    let hook = TilePropertyHook::new(|z, _, _, properties| {
+        let mut properties = properties.clone();
+        properties.insert("zoom".to_string(), z.into());
+        Some(properties)
+    });
+    let options = Options {
+        tile_property_hook: Some(hook.clone()),
+        ..Options::default()
+    };
+    let geojson = load("tests/fixtures/us-states.json");
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, &options);
+    let bytes = geojsonvt.serialize_index();
+
+    assert!(matches!(
+        GeoJSONVT::deserialize_index(&bytes, None),
+        Err(DeserializeError::TilePropertyHookMismatch)
+    ));
+    let plain = GeoJSONVT::from_geojson(&geojson, &Options::default()).serialize_index();
+    assert!(matches!(
+        GeoJSONVT::deserialize_index(&plain, Some(hook.clone())),
+        Err(DeserializeError::TilePropertyHookMismatch)
+    ));
+
+    let mut restored = GeoJSONVT::deserialize_index(&bytes, Some(hook)).unwrap();
+    let tile = restored.tile(7, 37, 48).clone();
+    let properties = tile.feature_collection.features[0].properties.as_ref();
+    assert_eq!(properties.unwrap()["zoom"], 7);
+    assert_eq!(&tile, geojsonvt.tile(7, 37, 48));
+}
@@ -0,0 +1,121 @@
+use geojson::{GeoJson, Value};
+use geojsonvt::{GeoJSONVT, Options, Tile, TileCoord, TileSize};
+use std::fs::File;
+use std::io::BufReader;
+
+fn us_states() -> GeoJson {
+    GeoJson::from_reader(BufReader::new(
+        File::open("tests/fixtures/us-states.json").unwrap(),
+    ))
+    .unwrap()
+}
+
+fn points() -> GeoJson {
+    let features = (0..40)
+        .flat_map(|i| (0..20).map(move |j| (i, j)))
+        .map(|(i, j)| {
+            let (lng, lat) = (-120. + i as f64 * 1.37, 20. + j as f64 * 1.53);
+            format!(r#"{{"type":"Feature","properties":{{}},"geometry":{{"type":"Point","coordinates":[{lng},{lat}]}}}}"#)
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(r#"{{"type":"FeatureCollection","features":[{features}]}}"#)
+        .parse()
+        .unwrap()
+}
+
+fn tile_points(tile: &Tile) -> Vec<(i64, i64)> {
+    let mut points: Vec<_> = tile
+        .feature_collection
+        .features
+        .iter()
+        .flat_map(|feature| match &feature.geometry.as_ref().unwrap().value {
+            Value::Point(p) => vec![p.clone()],
+            Value::MultiPoint(points) => points.clone(),
+            _ => unreachable!(),
+        })
+        .map(|p| (p[0] as i64, p[1] as i64))
+        .collect();
+    points.sort();
+    points
+}
+
+#[test]
+fn test_512_tile_is_quadrant_of_content_one_zoom_lower() {
+    let geojson = points();
+    let mut px256 = GeoJSONVT::from_geojson(
+        &geojson,
+        &Options {
+            extent: 8192,
+            buffer: 128,
+            ..Options::default()
+        },
+    );
+    let mut px512 = GeoJSONVT::from_geojson(
+        &geojson,
+        &Options {
+            tile_size: TileSize::Px512,
+            ..Options::default()
+        },
+    );
+    for (z, x, y) in [
+        (1, 0, 0),
+        (3, 2, 3),
+        (4, 4, 6),
+        (5, 9, 12),
+        (5, 8, 13),
+        (6, 18, 25),
+    ] {
+        let tile = px512.tile(z, x, y).clone();
+        assert_eq!(tile.coord, Some(TileCoord::new(x, y, z)));
+        assert_eq!(tile.extent, 4096);
+        let (qx, qy) = ((x & 1) as i64 * 4096, (y & 1) as i64 * 4096);
+        let expected: Vec<_> = tile_points(px256.tile(z - 1, x >> 1, y >> 1))
+            .into_iter()
+            .map(|(px, py)| (px - qx, py - qy))
+            .filter(|&(px, py)| (-64..=4160).contains(&px) && (-64..=4160).contains(&py))
+            .collect();
+        assert!(!expected.is_empty(), "{z}/{x}/{y}");
+        assert_eq!(tile_points(&tile), expected, "{z}/{x}/{y}");
+
+        let (lng, lat) = tile.pixel_to_lnglat(1024., 3072.).unwrap();
+        let (px, py) = px256
+            .tile(z - 1, x >> 1, y >> 1)
+            .lnglat_to_pixel(lng, lat)
+            .unwrap();
+        assert!((px - qx as f64 - 1024.).abs() < 1e-6 && (py - qy as f64 - 3072.).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_512_tile_max_zoom_accounts_for_offset() {
+    let mut px512 = GeoJSONVT::from_geojson(
+        &us_states(),
+        &Options {
+            max_zoom: 6,
+            tile_size: TileSize::Px512,
+            ..Options::default()
+        },
+    );
+    px512.tile(7, 20, 40);
+}
+
+#[test]
+#[should_panic(expected = "Requested zoom higher than maxZoom")]
+fn test_256_tile_rejects_zoom_above_max() {
+    let mut px256 = GeoJSONVT::from_geojson(
+        &us_states(),
+        &Options {
+            max_zoom: 6,
+            ..Options::default()
+        },
+    );
+    px256.tile(7, 20, 40);
+}
+
+#[test]
+fn test_tile_size_pixels() {
+    for tile_size in [TileSize::Px256, TileSize::Px512] {
+        assert_eq!(tile_size.pixels(), 256 << tile_size.zoom_offset());
+    }
+}
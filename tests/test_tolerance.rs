@@ -0,0 +1,295 @@
+use geojson::{Feature, FeatureCollection, GeoJson, Geometry, Value};
+use geojsonvt::{BufferUnit, BuildError, GeoJSONVT, Options, OptionsError, ToleranceUnit};
+
+fn circle(points: usize) -> Vec<Vec<f64>> {
+    let mut coords = (0..points)
+        .map(|i| {
+            let angle = i as f64 / points as f64 * std::f64::consts::TAU;
+            vec![40. * angle.cos(), 40. * angle.sin()]
+        })
+        .collect::<Vec<_>>();
+    coords.push(coords[0].clone());
+    coords
+}
+
+fn line_and_ring() -> GeoJson {
+    let coords = circle(256);
+    let features = [
+        Value::LineString(coords.clone()),
+        Value::Polygon(vec![coords]),
+    ]
+    .into_iter()
+    .map(|value| Feature {
+        geometry: Some(Geometry::new(value)),
+        ..Feature::default()
+    })
+    .collect::<Vec<_>>();
+    GeoJson::FeatureCollection(FeatureCollection::from_iter(features))
+}
+
+fn point_counts(options: &Options) -> (usize, usize) {
+    let mut geojsonvt = GeoJSONVT::from_geojson(&line_and_ring(), options);
+    let tile = geojsonvt.tile(0, 0, 0);
+    let mut line = 0;
+    let mut ring = 0;
+    for feature in &tile.feature_collection.features {
+        match &feature.geometry.as_ref().unwrap().value {
+            Value::LineString(coords) => line += coords.len(),
+            Value::Polygon(rings) => ring += rings.iter().map(Vec::len).sum::<usize>(),
+            _ => panic!("unexpected geometry"),
+        }
+    }
+    (line, ring)
+}
+
+#[test]
+fn test_per_geometry_type_tolerance() {
+    let base = Options {
+        tolerance: 20.,
+        ..Options::default()
+    };
+    let (base_line, base_ring) = point_counts(&base);
+
+    let (line, ring) = point_counts(&Options {
+        tolerance_polygons: Some(0.5),
+        ..base.clone()
+    });
+    assert_eq!(line, base_line);
+    assert!(ring > base_ring, "ring: {ring}, base: {base_ring}");
+
+    let (line, ring) = point_counts(&Options {
+        tolerance_lines: Some(0.5),
+        ..base.clone()
+    });
+    assert!(line > base_line, "line: {line}, base: {base_line}");
+    assert_eq!(ring, base_ring);
+}
+
+#[test]
+fn test_tolerance_schedule() {
+    let count = |options: &Options, z: u8| {
+        let mut geojsonvt = GeoJSONVT::from_geojson(&line_and_ring(), options);
+        // The tile at the east end of the circle.
+        let z2 = (1u32 << z) as f64;
+        let x = ((0.5 + 40. / 360.) * z2) as u32;
+        geojsonvt.tile(z, x, (z2 / 2.) as u32).simplified_count
+    };
+    let base = Options {
+        max_zoom: 4,
+        index_max_zoom: 4,
+        tolerance: 1.,
+        ..Options::default()
+    };
+    let scheduled = Options {
+        tolerance_schedule: Some(vec![40., 20., 10.]),
+        ..base.clone()
+    };
+    // Coarser tolerances at low zooms, the last one repeated up to and including `max_zoom`.
+    for z in 0..=4 {
+        assert!(count(&scheduled, z) < count(&base, z), "z{z}");
+    }
+    let finer_first = Options {
+        tolerance_schedule: Some(vec![20., 20., 10.]),
+        ..base.clone()
+    };
+    assert!(count(&scheduled, 0) < count(&finer_first, 0));
+    let repeated = Options {
+        tolerance_schedule: Some(vec![40., 20., 10., 10., 10.]),
+        ..base.clone()
+    };
+    for z in 2..=4 {
+        assert_eq!(count(&scheduled, z), count(&repeated, z), "z{z}");
+    }
+    // The schedule replaces `tolerance` for the tiles.
+    let tolerance = Options {
+        tolerance: 10.,
+        ..base.clone()
+    };
+    let flat = Options {
+        tolerance_schedule: Some(vec![10.]),
+        ..base
+    };
+    assert_eq!(count(&flat, 1), count(&tolerance, 1));
+}
+
+fn ring_coords(options: &Options) -> Vec<Vec<f64>> {
+    let mut geojsonvt = GeoJSONVT::from_geojson(&line_and_ring(), options);
+    let tile = geojsonvt.tile(1, 1, 0);
+    tile.feature_collection
+        .features
+        .iter()
+        .find_map(|feature| match &feature.geometry.as_ref().unwrap().value {
+            Value::Polygon(rings) => Some(rings[0].clone()),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_pixel_tolerance_is_extent_independent() {
+    let base = Options {
+        tolerance: 2.,
+        tolerance_unit: ToleranceUnit::Pixels { tile_size: 256 },
+        buffer: 0,
+        ..Options::default()
+    };
+    let small = ring_coords(&Options {
+        extent: 4096,
+        ..base.clone()
+    });
+    let large = ring_coords(&Options {
+        extent: 8192,
+        ..base.clone()
+    });
+    assert_eq!(small.len(), large.len());
+    for (a, b) in small.iter().zip(&large) {
+        assert!((a[0] * 2. - b[0]).abs() <= 1.);
+        assert!((a[1] * 2. - b[1]).abs() <= 1.);
+    }
+
+    let extent_units = ring_coords(&Options {
+        extent: 8192,
+        tolerance_unit: ToleranceUnit::ExtentUnits,
+        ..base
+    });
+    assert!(extent_units.len() > large.len());
+}
+
+#[test]
+fn test_buffer_in_pixels() {
+    // The ring crosses the left and bottom edges of tile 1/1/0, clipped at the buffer.
+    let margins = |options: &Options| {
+        let coords = ring_coords(options);
+        let min_x = coords.iter().map(|p| p[0]).fold(f64::INFINITY, f64::min);
+        let max_y = coords
+            .iter()
+            .map(|p| p[1])
+            .fold(f64::NEG_INFINITY, f64::max);
+        (-min_x, max_y - options.extent as f64)
+    };
+    let base = Options {
+        buffer: 4,
+        buffer_unit: BufferUnit::Pixels { tile_size: 256 },
+        ..Options::default()
+    };
+    let small = Options {
+        extent: 4096,
+        ..base.clone()
+    };
+    let large = Options {
+        extent: 8192,
+        ..base.clone()
+    };
+    assert_eq!(margins(&small), (64., 64.));
+    assert_eq!(margins(&large), (128., 128.));
+    let geojsonvt = GeoJSONVT::from_geojson(&line_and_ring(), &large);
+    assert_eq!(geojsonvt.options().buffer, 128);
+    assert_eq!(geojsonvt.options().buffer_unit, BufferUnit::ExtentUnits);
+
+    let extent_units = Options {
+        extent: 8192,
+        buffer_unit: BufferUnit::ExtentUnits,
+        ..base.clone()
+    };
+    assert_eq!(margins(&extent_units), (4., 4.));
+
+    // Buffers in pixels scale past `u16` along with high extents.
+    let high = Options {
+        extent: 1 << 20,
+        buffer: 64,
+        ..base
+    };
+    assert_eq!(high.extent_buffer(), 262_144);
+    let geojsonvt = GeoJSONVT::from_geojson(&line_and_ring(), &high);
+    assert_eq!(geojsonvt.options().buffer, 262_144);
+}
+
+#[test]
+fn test_large_extent() {
+    let extent = 1u32 << 20;
+    let options = Options {
+        extent,
+        max_zoom: 20,
+        ..Options::default()
+    };
+    let (lng, lat) = (45.123456789, 45.987654321);
+    let point = GeoJson::from(Geometry::new(Value::Point(vec![lng, lat])));
+    let mut geojsonvt = GeoJSONVT::from_geojson(&point, &options);
+    let z2 = (1u32 << 18) as f64;
+    let x = (lng / 360. + 0.5) * z2;
+    let sin = lat.to_radians().sin();
+    let y = (0.5 - 0.25 * ((1. + sin) / (1. - sin)).ln() / std::f64::consts::PI) * z2;
+    let tile = geojsonvt.tile(18, x as u32, y as u32);
+    assert_eq!(tile.extent, extent);
+    let Value::Point(position) = &tile.feature_collection.features[0]
+        .geometry
+        .as_ref()
+        .unwrap()
+        .value
+    else {
+        panic!("expected a point");
+    };
+    let expected = [
+        (x.fract() * extent as f64).round(),
+        (y.fract() * extent as f64).round(),
+    ];
+    assert_eq!(position[..], expected);
+    // One extent unit is well below a millimeter at this zoom.
+    let (back_lng, back_lat) = tile.pixel_to_lnglat(position[0], position[1]).unwrap();
+    assert!((back_lng - lng).abs() < 1e-9 && (back_lat - lat).abs() < 1e-9);
+}
+
+#[test]
+fn test_buffer_too_large() {
+    let GeoJson::FeatureCollection(features) = line_and_ring() else {
+        unreachable!()
+    };
+    let options = Options {
+        buffer: 4096,
+        ..Options::default()
+    };
+    assert_eq!(
+        GeoJSONVT::try_new(features.clone(), options).err(),
+        Some(BuildError::InvalidOptions(OptionsError::BufferTooLarge {
+            buffer: 4096,
+            extent: 4096,
+        }))
+    );
+    let options = Options {
+        buffer: 64,
+        buffer_unit: BufferUnit::Pixels { tile_size: 8 },
+        ..Options::default()
+    };
+    assert!(matches!(
+        options.validate(),
+        Err(OptionsError::BufferTooLarge { buffer: 32768, .. })
+    ));
+    let options = Options {
+        extent: (1 << 30) + 1,
+        ..Options::default()
+    };
+    assert_eq!(
+        options.validate(),
+        Err(OptionsError::ExtentTooLarge {
+            extent: (1 << 30) + 1
+        })
+    );
+    let options = Options {
+        extent: 1 << 30,
+        ..Options::default()
+    };
+    assert!(options.validate().is_ok());
+    for options in [
+        Options {
+            buffer_unit: BufferUnit::Pixels { tile_size: 0 },
+            ..Options::default()
+        },
+        Options {
+            tolerance_unit: ToleranceUnit::Pixels { tile_size: 0 },
+            ..Options::default()
+        },
+    ] {
+        assert_eq!(options.validate(), Err(OptionsError::ZeroTileSize));
+    }
+    assert!(GeoJSONVT::try_new(features, Options::default()).is_ok());
+}
@@ -0,0 +1,87 @@
+use geojson::GeoJson;
+use geojsonvt::validate::{check_coverage, check_tile_invariants, check_unique_ids};
+use geojsonvt::{GeoJSONVT, Options, QueryError};
+use std::fs;
+use std::str::FromStr;
+
+const FIXTURES: [&str; 11] = [
+    "tests/fixtures/collection.json",
+    "tests/fixtures/dateline-triangle.json",
+    "tests/fixtures/dateline.json",
+    "tests/fixtures/empty-coords.json",
+    "tests/fixtures/empty.json",
+    "tests/fixtures/feature-null-geometry.json",
+    "tests/fixtures/feature.json",
+    "tests/fixtures/ids.json",
+    "tests/fixtures/linestring.json",
+    "tests/fixtures/polygon-bug.json",
+    "tests/fixtures/single-geom.json",
+];
+
+fn build(path: &str, options: &Options) -> GeoJSONVT {
+    let geojson = GeoJson::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    let mut geojsonvt = GeoJSONVT::from_geojson(&geojson, options);
+    geojsonvt.generate_all(6);
+    geojsonvt
+}
+
+#[test]
+fn test_fixture_tile_invariants() {
+    for path in FIXTURES.iter().chain(&["tests/fixtures/us-states.json"]) {
+        for line_metrics in [false, true] {
+            let options = Options {
+                index_max_points: 100,
+                line_metrics,
+                ..Options::default()
+            };
+            let geojsonvt = build(path, &options);
+            for tile in geojsonvt.internal_tiles().values() {
+                let violations = check_tile_invariants(tile.tile(), options.extent, options.buffer);
+                let coord = tile.coord();
+                assert!(
+                    violations.is_empty(),
+                    "{path} z{}-{}-{}: {violations:?}",
+                    coord.z(),
+                    coord.x(),
+                    coord.y()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fixture_generated_ids_are_unique() {
+    let options = Options {
+        generate_id: true,
+        ..Options::default()
+    };
+    let geojsonvt = build("tests/fixtures/us-states.json", &options);
+    // Features crossing the antimeridian are copied into both halves of the lowest tiles.
+    for tile in geojsonvt
+        .internal_tiles()
+        .values()
+        .filter(|tile| tile.coord().z() > 1)
+    {
+        assert_eq!(check_unique_ids(tile.tile()), vec![]);
+    }
+}
+
+#[test]
+fn test_fixture_coverage() {
+    for path in FIXTURES.iter().chain(&["tests/fixtures/us-states.json"]) {
+        let options = Options {
+            retain_sources: true,
+            ..Options::default()
+        };
+        let geojsonvt = build(path, &options);
+        for z in [0, 3, 6] {
+            let report = check_coverage(&geojsonvt, z).unwrap();
+            assert!(report.is_complete(), "{path} z{z}: {report:?}");
+        }
+    }
+
+    // Without sources there is nothing to check against, which must not pass as complete.
+    let geojsonvt = build(FIXTURES[0], &Options::default());
+    assert_eq!(check_coverage(&geojsonvt, 0), Err(QueryError::NoSources));
+}